@@ -0,0 +1,7 @@
+-- Allow pausing subscriptions without deleting them.
+
+-- Paused subscriptions are skipped by the search bot.
+ALTER TABLE subscriptions ADD COLUMN is_paused INTEGER NOT NULL DEFAULT FALSE;
+
+-- Timestamp at which a paused subscription gets automatically resumed, or `NULL` to pause indefinitely.
+ALTER TABLE subscriptions ADD COLUMN resume_at TEXT NULL;
@@ -0,0 +1,12 @@
+-- Sellers muted by a chat, their items never get notified.
+
+CREATE TABLE muted_sellers
+(
+    chat_id     INTEGER NOT NULL,
+    seller_id   INTEGER NOT NULL,
+
+    -- Seller's display name at the moment of muting.
+    seller_name TEXT    NOT NULL,
+
+    PRIMARY KEY (chat_id, seller_id)
+) STRICT, WITHOUT ROWID;
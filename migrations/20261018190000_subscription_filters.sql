@@ -0,0 +1,5 @@
+-- Per-subscription filters for commercial listings.
+
+ALTER TABLE subscriptions ADD COLUMN exclude_business INTEGER NOT NULL DEFAULT FALSE;
+ALTER TABLE subscriptions ADD COLUMN exclude_auctions INTEGER NOT NULL DEFAULT FALSE;
+ALTER TABLE subscriptions ADD COLUMN exclude_promoted INTEGER NOT NULL DEFAULT FALSE;
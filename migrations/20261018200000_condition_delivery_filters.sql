@@ -0,0 +1,7 @@
+-- Per-subscription filters for item condition and delivery method.
+
+-- Bitmask of excluded condition classes.
+ALTER TABLE subscriptions ADD COLUMN excluded_conditions INTEGER NOT NULL DEFAULT 0;
+
+-- Bitmask of excluded delivery methods.
+ALTER TABLE subscriptions ADD COLUMN excluded_deliveries INTEGER NOT NULL DEFAULT 0;
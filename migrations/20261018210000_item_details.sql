@@ -0,0 +1,10 @@
+-- Cached item details, so that an item page is fetched at most once.
+CREATE TABLE item_details
+(
+    item_id    TEXT PRIMARY KEY NOT NULL REFERENCES items (id) ON UPDATE CASCADE ON DELETE CASCADE,
+
+    -- Serialized `marketplace::item::Details` in JSON.
+    details    TEXT             NOT NULL,
+
+    fetched_at TEXT             NOT NULL
+) STRICT, WITHOUT ROWID;
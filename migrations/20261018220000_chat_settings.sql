@@ -0,0 +1,12 @@
+-- Per-chat settings, which do not belong to a single subscription.
+
+CREATE TABLE chat_settings
+(
+    chat_id       INTEGER PRIMARY KEY NOT NULL,
+
+    -- Language chosen with `/language`, overrides the detected one.
+    language      TEXT,
+
+    -- Latest Telegram `language_code` of the chat's user.
+    language_code TEXT
+) STRICT;
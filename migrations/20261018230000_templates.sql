@@ -0,0 +1,7 @@
+-- Notification templates chosen with `/template` or in the subscription settings.
+
+-- Template of the chat's notifications, the built-in standard one if not set.
+ALTER TABLE chat_settings ADD COLUMN template TEXT;
+
+-- Template of the subscription's notifications, overrides the chat's one.
+ALTER TABLE subscriptions ADD COLUMN template TEXT;
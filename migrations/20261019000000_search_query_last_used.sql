@@ -0,0 +1,4 @@
+-- Search results link to their query by the hash, so an unsubscribed query must outlive the recent results.
+
+-- Last time the query was searched for or subscribed to, unknown for the older queries.
+ALTER TABLE search_queries ADD COLUMN last_used_at TEXT;
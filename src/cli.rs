@@ -1,18 +1,38 @@
-use std::path::PathBuf;
+use std::{
+    ffi::OsStr,
+    fmt::{Display, Formatter},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
-use clap::{Parser, Subcommand};
+use chrono::TimeDelta;
+use clap::{Parser, Subcommand, ValueEnum};
+use http::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::{
+    client::{self, ClientSettings, RateLimiter},
+    heartbeat::HeartbeatKind,
+    prelude::*,
+};
+
 #[derive(Parser)]
 #[command(author, version, about, long_about, propagate_version = true)]
 pub struct Args {
+    /// TOML configuration file, which is overridden by the environment variables and flags.
+    #[clap(long, env = "CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
     /// Sentry DSN: <https://docs.sentry.io/concepts/key-terms/dsn-explainer/>.
     #[clap(long, env = "SENTRY_DSN", hide_env_values = true)]
     pub sentry_dsn: Option<String>,
 
     /// Pydantic Logfire token: <https://logfire.pydantic.dev/docs/how-to-guides/create-write-tokens/>.
     #[clap(long, env = "LOGFIRE_TOKEN", hide_env_values = true)]
-    _logfire_token: Option<String>,
+    pub logfire_token: Option<String>,
 
     /// SQLite database path.
     #[expect(clippy::doc_markdown)]
@@ -29,8 +49,162 @@ pub struct Args {
 
 #[derive(Subcommand)]
 pub enum Command {
+    #[command(flatten)]
+    Database(DatabaseCommand),
+
+    /// Search once and print the results, including the items dropped by the query filter.
+    Search(Box<SearchArgs>),
+
+    /// Rerun the parsing and filtering against the responses stored with `--record-responses`.
+    Replay(ReplayArgs),
+
+    /// Inspect the configuration.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+/// Commands, which need the database.
+#[derive(Subcommand)]
+pub enum DatabaseCommand {
     /// Run the bot indefinitely.
     Run(Box<RunArgs>),
+
+    /// Prune stale items, notifications, and search queries, and optimize the database.
+    Prune(PruneArgs),
+
+    /// Export subscriptions and muted sellers as JSON.
+    Export(ExportArgs),
+
+    /// Merge previously exported subscriptions and muted sellers into the database.
+    Import(ImportArgs),
+
+    /// Manage subscriptions without the bot.
+    #[command(subcommand)]
+    Subscriptions(SubscriptionsCommand),
+
+    /// Inspect chats without the bot.
+    #[command(subcommand)]
+    Chats(ChatsCommand),
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Validate the configuration, which `run` would use, and print it with the secrets redacted.
+    Check(Box<RunArgs>),
+}
+
+#[derive(Subcommand)]
+pub enum SubscriptionsCommand {
+    /// List the subscriptions.
+    List {
+        /// Only list the specified chat's subscriptions.
+        #[clap(long = "chat")]
+        chat_id: Option<i64>,
+
+        #[clap(long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Subscribe the chat to the search query.
+    Add { chat_id: i64, query: String },
+
+    /// Unsubscribe the chat from the search query.
+    Remove { chat_id: i64, query: String },
+}
+
+#[derive(Subcommand)]
+pub enum ChatsCommand {
+    /// List the chats, which have subscriptions or muted sellers.
+    List {
+        #[clap(long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Parser)]
+pub struct SearchArgs {
+    /// Search query, `-` excludes a word: `unifi -switch`.
+    pub query: String,
+
+    /// Output format.
+    #[clap(long, value_enum, default_value = "table")]
+    pub format: OutputFormat,
+
+    /// Limit of search results, overrides the Marktplaats search limit.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub limit: Option<u32>,
+
+    #[command(flatten)]
+    pub marktplaats: MarktplaatsArgs,
+}
+
+#[derive(Parser)]
+pub struct ReplayArgs {
+    /// Recorded response files, or directories with them.
+    #[clap(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Search query to match the listings against, defaults to the recorded one.
+    #[clap(long)]
+    pub query: Option<String>,
+
+    /// Also print the rendered notifications.
+    #[clap(long)]
+    pub render: bool,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table.
+    Table,
+
+    /// Single JSON document.
+    Json,
+
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// Format of the exported file.
+#[derive(Copy, Clone, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+}
+
+impl ExportFormat {
+    /// Guess the format from the file extension, falling back to JSON.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct ExportArgs {
+    /// Output file path, the standard output is used by default.
+    #[clap(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Output format, guessed from the output file extension by default.
+    #[clap(long, value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Only export the specified chat.
+    #[clap(long = "chat-id")]
+    pub chat_id: Option<i64>,
+}
+
+#[derive(Parser)]
+pub struct ImportArgs {
+    /// Exported JSON or YAML file path, the format is guessed from the extension.
+    pub input: PathBuf,
+
+    /// Only log what would be imported, without changing the database.
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
 }
 
 #[derive(Parser)]
@@ -40,6 +214,7 @@ pub struct RunArgs {
         long = "search-interval-secs",
         env = "SEARCH_INTERVAL_SECS",
         default_value = "60",
+        value_parser = clap::value_parser!(u64).range(1..),
         hide_env_values = true
     )]
     pub search_interval_secs: u64,
@@ -49,6 +224,89 @@ pub struct RunArgs {
 
     #[command(flatten)]
     pub marktplaats: MarktplaatsArgs,
+
+    #[command(flatten)]
+    pub prune: PruneArgs,
+
+    #[command(flatten)]
+    pub status: StatusArgs,
+
+    /// Database maintenance interval, in hours.
+    #[clap(
+        long = "maintenance-interval-hours",
+        env = "MAINTENANCE_INTERVAL_HOURS",
+        default_value = "24",
+        value_parser = clap::value_parser!(u64).range(1..),
+        hide_env_values = true
+    )]
+    pub maintenance_interval_hours: u64,
+}
+
+impl RunArgs {
+    /// Check the options which depend on each other.
+    pub fn validate(&self) -> Result {
+        if self.status.address.is_none() {
+            // The staleness is only checked by the status server:
+            return Ok(());
+        }
+        let max_stale_secs = self.status.max_stale_secs;
+        if max_stale_secs <= self.search_interval_secs {
+            bail!(
+                "status max stale ({max_stale_secs}s) must exceed the search interval ({}s)",
+                self.search_interval_secs,
+            );
+        }
+        if max_stale_secs <= self.telegram.poll_timeout_secs {
+            bail!(
+                "status max stale ({max_stale_secs}s) must exceed the Telegram poll timeout ({}s)",
+                self.telegram.poll_timeout_secs,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser)]
+#[clap(next_help_heading = "Status")]
+pub struct StatusArgs {
+    /// Address to serve `/healthz`, `/readyz`, and `/status` on, for example: `0.0.0.0:8080`.
+    #[clap(long = "status-address", env = "STATUS_ADDRESS", hide_env_values = true)]
+    pub address: Option<SocketAddr>,
+
+    /// Fail `/healthz` when the Telegram or search loop has not completed an iteration
+    /// for this many seconds.
+    #[clap(
+        long = "status-max-stale-secs",
+        env = "STATUS_MAX_STALE_SECS",
+        default_value = "600",
+        value_parser = clap::value_parser!(u64).range(1..),
+        hide_env_values = true
+    )]
+    pub max_stale_secs: u64,
+
+    /// Address to serve the Prometheus metrics on, for example: `127.0.0.1:9090`.
+    #[clap(long = "metrics-address", env = "METRICS_ADDRESS", hide_env_values = true)]
+    pub metrics_address: Option<SocketAddr>,
+}
+
+#[derive(Parser)]
+#[clap(next_help_heading = "Pruning")]
+pub struct PruneArgs {
+    /// Remove items, which have not been seen for this many days, along with their notifications.
+    #[clap(
+        long = "item-retention-days",
+        env = "ITEM_RETENTION_DAYS",
+        default_value = "90",
+        value_parser = clap::value_parser!(u32).range(1..=36500),
+        hide_env_values = true
+    )]
+    pub item_retention_days: u32,
+}
+
+impl PruneArgs {
+    pub fn item_retention(&self) -> TimeDelta {
+        TimeDelta::days(self.item_retention_days.into())
+    }
 }
 
 #[derive(Parser)]
@@ -59,10 +317,31 @@ pub struct MarktplaatsArgs {
         long = "marktplaats-search-limit",
         env = "MARKTPLAATS_SEARCH_LIMIT",
         default_value = "10",
+        value_parser = clap::value_parser!(u32).range(1..),
         hide_env_values = true
     )]
     pub marktplaats_search_limit: u32,
 
+    /// Maximum number of the Marktplaats result pages per search, each of the search limit size.
+    /// The next page is only fetched while too few items match the query, and the previous search has not seen the page.
+    #[clap(
+        long = "marktplaats-max-pages",
+        env = "MARKTPLAATS_MAX_PAGES",
+        default_value = "1",
+        value_parser = clap::value_parser!(u32).range(1..),
+        hide_env_values = true
+    )]
+    pub max_pages: u32,
+
+    /// Stop fetching the Marktplaats pages once this many items match the query, defaults to the search limit.
+    #[clap(
+        long = "marktplaats-min-matches",
+        env = "MARKTPLAATS_MIN_MATCHES",
+        value_parser = clap::value_parser!(u32).range(1..),
+        hide_env_values = true
+    )]
+    pub min_matches: Option<u32>,
+
     /// Heartbeat URL for the Marktplaats connection.
     #[clap(
         long = "marktplaats-heartbeat-url",
@@ -72,6 +351,93 @@ pub struct MarktplaatsArgs {
     )]
     pub heartbeat_url: Option<Url>,
 
+    /// Heartbeat URL scheme for the Marktplaats connection.
+    #[clap(
+        long = "marktplaats-heartbeat-kind",
+        env = "MARKTPLAATS_HEARTBEAT_KIND",
+        id = "marktplaats_heartbeat_kind",
+        value_enum,
+        default_value = "generic",
+        hide_env_values = true
+    )]
+    pub heartbeat_kind: HeartbeatKind,
+
+    /// Proxy URL for the Marktplaats requests, for example: `socks5h://127.0.0.1:1080`.
+    #[clap(
+        long = "marktplaats-proxy",
+        env = "MARKTPLAATS_PROXY",
+        id = "marktplaats_proxy",
+        hide_env_values = true
+    )]
+    pub proxy: Option<Url>,
+
+    /// User agent for the Marktplaats requests.
+    #[clap(
+        long = "marktplaats-user-agent",
+        env = "MARKTPLAATS_USER_AGENT",
+        id = "marktplaats_user_agent",
+        hide_env_values = true
+    )]
+    pub user_agent: Option<String>,
+
+    /// Default connection and request timeout for the Marktplaats requests, in seconds.
+    #[clap(
+        long = "marktplaats-timeout-secs",
+        env = "MARKTPLAATS_TIMEOUT_SECS",
+        id = "marktplaats_timeout_secs",
+        value_parser = clap::value_parser!(u64).range(1..),
+        hide_env_values = true
+    )]
+    pub timeout_secs: Option<u64>,
+
+    /// `Accept-Language` for the Marktplaats requests, for example: `nl-NL,nl;q=0.9`.
+    #[clap(
+        long = "marktplaats-accept-language",
+        env = "MARKTPLAATS_ACCEPT_LANGUAGE",
+        id = "marktplaats_accept_language",
+        hide_env_values = true
+    )]
+    pub accept_language: Option<String>,
+
+    /// Extra header for the Marktplaats requests: `Name: value`. Separate the headers with newlines in the environment variable.
+    #[clap(
+        long = "marktplaats-header",
+        env = "MARKTPLAATS_HEADERS",
+        id = "marktplaats_headers",
+        value_delimiter = '\n',
+        hide_env_values = true
+    )]
+    pub headers: Vec<HttpHeader>,
+
+    /// Sustained rate of the Marktplaats requests, shared by the crawler and the interactive searches.
+    #[clap(
+        long = "marktplaats-requests-per-minute",
+        env = "MARKTPLAATS_REQUESTS_PER_MINUTE",
+        default_value = "30",
+        value_parser = clap::value_parser!(u32).range(1..),
+        hide_env_values = true
+    )]
+    pub requests_per_minute: u32,
+
+    /// Number of the Marktplaats requests, which may go through at once before the rate applies.
+    #[clap(
+        long = "marktplaats-burst",
+        env = "MARKTPLAATS_BURST",
+        default_value = "5",
+        value_parser = clap::value_parser!(u32).range(1..),
+        hide_env_values = true
+    )]
+    pub burst: u32,
+
+    /// Maximum random delay added to a throttled Marktplaats request, in milliseconds.
+    #[clap(
+        long = "marktplaats-jitter-millis",
+        env = "MARKTPLAATS_JITTER_MILLIS",
+        default_value = "1000",
+        hide_env_values = true
+    )]
+    pub jitter_millis: u64,
+
     /// Enable search in descriptions for Marktplaats.
     #[clap(
         long = "marktplaats-search-in-title-and-description",
@@ -79,6 +445,23 @@ pub struct MarktplaatsArgs {
         hide_env_values = true
     )]
     pub search_in_title_and_description: bool,
+
+    /// Fetch item pages for the full descriptions, all pictures, and attributes of new items.
+    #[clap(
+        long = "marktplaats-fetch-details",
+        env = "MARKTPLAATS_FETCH_DETAILS",
+        hide_env_values = true
+    )]
+    pub fetch_details: bool,
+
+    /// Store the raw Marktplaats search responses in the directory, to be used with `replay`.
+    #[clap(
+        long = "record-responses",
+        env = "MARKTPLAATS_RECORD_RESPONSES",
+        value_name = "DIR",
+        hide_env_values = true
+    )]
+    pub record_responses: Option<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -88,6 +471,14 @@ pub struct TelegramArgs {
     #[clap(long = "telegram-bot-token", env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
     pub bot_token: String,
 
+    /// Bot API server root URL, for example, of the self-hosted `telegram-bot-api` server.
+    #[clap(
+        long = "telegram-api-url",
+        env = "TELEGRAM_API_URL",
+        default_value = "https://api.telegram.org"
+    )]
+    pub api_url: Url,
+
     /// Timeout for Telegram long polling, in seconds.
     #[clap(
         long = "telegram-poll-timeout-secs",
@@ -97,7 +488,7 @@ pub struct TelegramArgs {
     )]
     pub poll_timeout_secs: u64,
 
-    /// Authorize chat ID to use the bot.
+    /// Authorize chat ID to use the bot, optionally with a display name: `42:Family`.
     #[clap(
         long = "telegram-authorize-chat-id",
         env = "TELEGRAM_AUTHORIZED_CHAT_IDS",
@@ -105,7 +496,16 @@ pub struct TelegramArgs {
         visible_alias = "chat-id",
         hide_env_values = true
     )]
-    pub authorized_chat_ids: Vec<i64>,
+    pub authorized_chats: Vec<AuthorizedChat>,
+
+    /// Load the custom notification templates from the `*.html` files in the directory.
+    #[clap(
+        long = "telegram-templates",
+        env = "TELEGRAM_TEMPLATES",
+        value_name = "DIR",
+        hide_env_values = true
+    )]
+    pub templates: Option<PathBuf>,
 
     /// Heartbeat URL for the Telegram bot.
     #[clap(
@@ -115,4 +515,226 @@ pub struct TelegramArgs {
         hide_env_values = true
     )]
     pub heartbeat_url: Option<Url>,
+
+    /// Heartbeat URL scheme for the Telegram bot.
+    #[clap(
+        long = "telegram-heartbeat-kind",
+        env = "TELEGRAM_HEARTBEAT_KIND",
+        id = "telegram_heartbeat_kind",
+        value_enum,
+        default_value = "generic",
+        hide_env_values = true
+    )]
+    pub heartbeat_kind: HeartbeatKind,
+
+    /// Proxy URL for the Telegram Bot API requests, for example: `socks5h://127.0.0.1:1080`.
+    #[clap(
+        long = "telegram-proxy",
+        env = "TELEGRAM_PROXY",
+        id = "telegram_proxy",
+        hide_env_values = true
+    )]
+    pub proxy: Option<Url>,
+
+    /// User agent for the Telegram Bot API requests.
+    #[clap(
+        long = "telegram-user-agent",
+        env = "TELEGRAM_USER_AGENT",
+        id = "telegram_user_agent",
+        hide_env_values = true
+    )]
+    pub user_agent: Option<String>,
+
+    /// Default connection and request timeout for the Telegram Bot API requests, in seconds.
+    #[clap(
+        long = "telegram-timeout-secs",
+        env = "TELEGRAM_TIMEOUT_SECS",
+        id = "telegram_timeout_secs",
+        value_parser = clap::value_parser!(u64).range(1..),
+        hide_env_values = true
+    )]
+    pub timeout_secs: Option<u64>,
+
+    /// `Accept-Language` for the Telegram Bot API requests, for example: `nl-NL,nl;q=0.9`.
+    #[clap(
+        long = "telegram-accept-language",
+        env = "TELEGRAM_ACCEPT_LANGUAGE",
+        id = "telegram_accept_language",
+        hide_env_values = true
+    )]
+    pub accept_language: Option<String>,
+
+    /// Extra header for the Telegram Bot API requests: `Name: value`. Separate the headers with newlines in the environment variable.
+    #[clap(
+        long = "telegram-header",
+        env = "TELEGRAM_HEADERS",
+        id = "telegram_headers",
+        value_delimiter = '\n',
+        hide_env_values = true
+    )]
+    pub headers: Vec<HttpHeader>,
+}
+
+impl MarktplaatsArgs {
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::builder()
+            .requests_per_minute(self.requests_per_minute)
+            .burst(self.burst)
+            .max_jitter(Duration::from_millis(self.jitter_millis))
+            .build()
+    }
+
+    pub fn client_settings(&self, connection_verbose: bool) -> ClientSettings {
+        ClientSettings {
+            connection_verbose,
+            proxy: self.proxy.clone(),
+            user_agent: self.user_agent.clone(),
+            timeout: self.timeout_secs.map(Duration::from_secs),
+            accept_language: self.accept_language.clone(),
+            headers: self.headers.clone(),
+            rate_limiter: Some(self.rate_limiter()),
+            circuit_breaker: true,
+        }
+    }
+}
+
+impl TelegramArgs {
+    pub fn timeout(&self) -> Duration {
+        self.timeout_secs.map_or(client::DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    pub fn client_settings(&self, connection_verbose: bool) -> ClientSettings {
+        ClientSettings {
+            connection_verbose,
+            proxy: self.proxy.clone(),
+            user_agent: self.user_agent.clone(),
+            timeout: Some(self.timeout()),
+            accept_language: self.accept_language.clone(),
+            headers: self.headers.clone(),
+            rate_limiter: None,
+            circuit_breaker: false,
+        }
+    }
+}
+
+/// Extra HTTP header, which is parsed from `Name: value`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpHeader {
+    pub name: HeaderName,
+    pub value: HeaderValue,
+}
+
+impl FromStr for HttpHeader {
+    type Err = Error;
+
+    fn from_str(header: &str) -> Result<Self> {
+        let (name, value) = header
+            .split_once(':')
+            .with_context(|| format!("expected `Name: value`, got `{header}`"))?;
+        Ok(Self {
+            name: name.trim().parse().with_context(|| format!("invalid header name `{name}`"))?,
+            value: value.trim().parse().context("invalid header value")?,
+        })
+    }
+}
+
+/// Chat, which is authorized to use the bot.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorizedChat {
+    pub id: i64,
+
+    /// Display name for the logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl FromStr for AuthorizedChat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (id, name) = match value.split_once(':') {
+            Some((id, name)) => (id, Some(name.trim().to_string())),
+            None => (value, None),
+        };
+        let id = id.trim().parse().with_context(|| format!("invalid chat ID `{id}`"))?;
+        Ok(Self { id, name })
+    }
+}
+
+impl Display for AuthorizedChat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}:{name}", self.id),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn args_ok() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn parse_authorized_chat_ok() -> Result {
+        assert_eq!("42".parse::<AuthorizedChat>()?, AuthorizedChat { id: 42, name: None });
+        assert_eq!(
+            "-100:Family".parse::<AuthorizedChat>()?,
+            AuthorizedChat { id: -100, name: Some("Family".to_string()) },
+        );
+        assert!("family".parse::<AuthorizedChat>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_run_args_ok() -> Result {
+        let parse = |extra_args: &[&str]| {
+            RunArgs::try_parse_from(
+                ["run", "--telegram-bot-token", "test"].iter().chain(extra_args),
+            )
+        };
+        parse(&[])?.validate()?;
+        parse(&["--status-max-stale-secs", "60"])?.validate()?;
+        parse(&["--search-interval-secs", "600"])?.validate()?;
+
+        let parse_with_status = |extra_args: &[&str]| {
+            parse(&[&["--status-address", "127.0.0.1:8080"], extra_args].concat())
+        };
+        parse_with_status(&[])?.validate()?;
+        assert!(parse_with_status(&["--status-max-stale-secs", "60"])?.validate().is_err());
+        assert!(parse_with_status(&["--search-interval-secs", "600"])?.validate().is_err());
+        assert!(parse_with_status(&["--telegram-poll-timeout-secs", "900"])?.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_item_retention_ok() {
+        let parse = |days| PruneArgs::try_parse_from(["prune", "--item-retention-days", days]);
+        assert!(parse("36500").is_ok());
+        assert!(parse("4000000000").is_err());
+    }
+
+    #[test]
+    fn export_format_of_ok() {
+        assert!(matches!(ExportFormat::of(Path::new("export.yml")), ExportFormat::Yaml));
+        assert!(matches!(ExportFormat::of(Path::new("export.yaml")), ExportFormat::Yaml));
+        assert!(matches!(ExportFormat::of(Path::new("export.json")), ExportFormat::Json));
+        assert!(matches!(ExportFormat::of(Path::new("export")), ExportFormat::Json));
+    }
+
+    #[test]
+    fn parse_http_header_ok() -> Result {
+        let header: HttpHeader = "X-Forwarded-For:  127.0.0.1 ".parse()?;
+        assert_eq!(header.name, "x-forwarded-for");
+        assert_eq!(header.value, "127.0.0.1");
+        assert!("X-Forwarded-For".parse::<HttpHeader>().is_err());
+        Ok(())
+    }
 }
@@ -1,37 +1,106 @@
 //! Provides the global `Client` instance.
 
+mod circuit_breaker;
+mod rate_limiter;
+mod retry;
+
 use std::time::Duration;
 
 use clap::crate_version;
 use reqwest::{
+    Proxy,
     header,
     header::{HeaderMap, HeaderValue},
 };
 use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
 
-use crate::prelude::*;
+pub use self::rate_limiter::RateLimiter;
+use crate::{
+    cli::HttpHeader,
+    client::{circuit_breaker::CircuitBreaker, retry::Retry},
+    prelude::*,
+};
 
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Retries of idempotent requests after a transient error.
+const MAX_RETRIES: u32 = 2;
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Consecutive transient failures, which open a host's circuit.
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+const CIRCUIT_OPEN_DURATION: Duration = Duration::from_mins(5);
 const USER_AGENT: &str = concat!(
     "mrktpltsbot / ",
     crate_version!(),
     " (Rust; https://github.com/eigenein/mrktpltsbot)",
 );
 
-#[instrument(name = "🌏 Instantiating client…")]
-pub fn try_new(connection_verbose: bool) -> Result<ClientWithMiddleware> {
+/// Settings of a client for a single consumer, like Telegram or a marketplace.
+#[derive(Default)]
+pub struct ClientSettings {
+    pub connection_verbose: bool,
+    pub proxy: Option<Url>,
+    pub user_agent: Option<String>,
+    pub timeout: Option<Duration>,
+    pub accept_language: Option<String>,
+    pub headers: Vec<HttpHeader>,
+    pub rate_limiter: Option<RateLimiter>,
+
+    /// Fail fast after repeated transient failures, see [`CircuitBreaker`].
+    pub circuit_breaker: bool,
+}
+
+#[instrument(
+    name = "🌏 Instantiating client…",
+    skip_all,
+    fields(
+        connection_verbose = settings.connection_verbose,
+        has_proxy = settings.proxy.is_some(),
+    ),
+)]
+pub fn try_new(settings: &ClientSettings) -> Result<ClientWithMiddleware> {
     let mut headers = HeaderMap::new();
-    headers.insert(header::USER_AGENT, HeaderValue::from_static(USER_AGENT));
-    let client = reqwest::Client::builder()
+    let user_agent = match &settings.user_agent {
+        Some(user_agent) => HeaderValue::from_str(user_agent).context("invalid user agent")?,
+        None => HeaderValue::from_static(USER_AGENT),
+    };
+    headers.insert(header::USER_AGENT, user_agent);
+    if let Some(accept_language) = &settings.accept_language {
+        let accept_language =
+            HeaderValue::from_str(accept_language).context("invalid `Accept-Language`")?;
+        headers.insert(header::ACCEPT_LANGUAGE, accept_language);
+    }
+    for header in &settings.headers {
+        headers.insert(header.name.clone(), header.value.clone());
+    }
+    let timeout = settings.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    let mut builder = reqwest::Client::builder()
         .gzip(true)
         .use_rustls_tls()
         .default_headers(headers)
-        .timeout(DEFAULT_TIMEOUT)
-        .connect_timeout(DEFAULT_TIMEOUT)
-        .pool_idle_timeout(Some(Duration::from_secs(300)))
-        .connection_verbose(connection_verbose)
-        .build()
-        .context("failed to build an HTTP client")?;
-    let client = reqwest_middleware::ClientBuilder::new(client).build();
-    Ok(client)
+        .timeout(timeout)
+        .connect_timeout(timeout)
+        .pool_idle_timeout(Some(Duration::from_mins(5)))
+        .connection_verbose(settings.connection_verbose);
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(Proxy::all(proxy.as_str()).context("invalid proxy URL")?);
+    }
+    let client = builder.build().context("failed to build an HTTP client")?;
+    // The limiter and the breaker go inside the retries, so that they see every attempt:
+    let mut builder = reqwest_middleware::ClientBuilder::new(client).with(Retry {
+        max_retries: MAX_RETRIES,
+        min_backoff: MIN_BACKOFF,
+        max_backoff: MAX_BACKOFF,
+    });
+    if let Some(rate_limiter) = &settings.rate_limiter {
+        builder = builder.with(rate_limiter.clone());
+    }
+    if settings.circuit_breaker {
+        builder =
+            builder.with(CircuitBreaker::new(CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_OPEN_DURATION));
+    }
+    Ok(builder.build())
 }
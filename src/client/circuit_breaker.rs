@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+
+use crate::{client::retry::is_transient, prelude::*};
+
+/// Per-host circuit breaker.
+///
+/// After `failure_threshold` consecutive transient failures, the requests to the host fail fast
+/// for `open_duration`. Then, a single probe request is let through to decide whether to close
+/// the circuit again.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+#[derive(Default)]
+struct HostState {
+    n_consecutive_failures: u32,
+
+    /// The circuit is open since then, `None` means it is closed.
+    opened_at: Option<Instant>,
+
+    /// A half-open circuit lets a single probe through.
+    is_probing: bool,
+}
+
+#[async_trait]
+impl Middleware for CircuitBreaker {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let Some(host) = request.url().host_str().map(ToString::to_string) else {
+            return next.run(request, extensions).await;
+        };
+        if let Err(retry_after) = self.before_request(&host, Instant::now()) {
+            return Err(reqwest_middleware::Error::Middleware(anyhow!(
+                "circuit breaker for `{host}` is open, retry in {}s",
+                retry_after.as_secs(),
+            )));
+        }
+        let result = next.run(request, extensions).await;
+        self.after_request(&host, is_transient(&result), Instant::now());
+        result
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self { failure_threshold, open_duration, hosts: Mutex::default() }
+    }
+
+    /// Check whether the request may go through.
+    ///
+    /// # Returns
+    ///
+    /// Time until the next probe, if the circuit is open.
+    fn before_request(&self, host: &str, now: Instant) -> Result<(), Duration> {
+        let mut hosts = self.lock();
+        let state = hosts.entry(host.to_string()).or_default();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+        let elapsed = now.duration_since(opened_at);
+        if elapsed < self.open_duration {
+            return Err(self.open_duration.saturating_sub(elapsed));
+        }
+        if state.is_probing {
+            // Another request is already probing the host:
+            return Err(Duration::ZERO);
+        }
+        info!("🔌 Circuit is half-open, probing…", host = host.to_string());
+        state.is_probing = true;
+        Ok(())
+    }
+
+    fn after_request(&self, host: &str, is_failure: bool, now: Instant) {
+        let mut hosts = self.lock();
+        let state = hosts.entry(host.to_string()).or_default();
+        let was_probing = state.is_probing;
+        state.is_probing = false;
+        if !is_failure {
+            if state.opened_at.is_some() {
+                info!("🔌 Circuit is closed", host = host.to_string());
+            }
+            *state = HostState::default();
+            return;
+        }
+        state.n_consecutive_failures += 1;
+        if was_probing
+            || (state.opened_at.is_none() && state.n_consecutive_failures >= self.failure_threshold)
+        {
+            warn!(
+                "🔌 Circuit is open",
+                host = host.to_string(),
+                n_consecutive_failures = i64::from(state.n_consecutive_failures),
+                open_secs = self.open_duration.as_secs_f64(),
+            );
+            state.opened_at = Some(now);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, HostState>> {
+        self.hosts.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "www.marktplaats.nl";
+
+    #[test]
+    fn transitions_ok() {
+        let breaker = CircuitBreaker::new(2, Duration::from_mins(1));
+        let now = Instant::now();
+
+        // Closed:
+        assert!(breaker.before_request(HOST, now).is_ok());
+        breaker.after_request(HOST, true, now);
+        assert!(breaker.before_request(HOST, now).is_ok());
+        breaker.after_request(HOST, true, now);
+
+        // Open:
+        assert_eq!(breaker.before_request(HOST, now), Err(Duration::from_mins(1)));
+        assert!(breaker.before_request("api.telegram.org", now).is_ok(), "hosts are independent");
+
+        // Half-open, and the probe fails:
+        let now = now + Duration::from_mins(1);
+        assert!(breaker.before_request(HOST, now).is_ok());
+        assert!(breaker.before_request(HOST, now).is_err(), "only one probe is allowed");
+        breaker.after_request(HOST, true, now);
+        assert!(breaker.before_request(HOST, now).is_err());
+
+        // Half-open again, and the probe succeeds:
+        let now = now + Duration::from_mins(1);
+        assert!(breaker.before_request(HOST, now).is_ok());
+        breaker.after_request(HOST, false, now);
+        assert!(breaker.before_request(HOST, now).is_ok());
+        assert!(breaker.before_request(HOST, now).is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bon::bon;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use tokio::time::sleep;
+use url::Url;
+
+use crate::prelude::*;
+
+/// Per-host token bucket, which keeps the request rate polite.
+///
+/// Clones share the buckets, so that all the users of a marketplace client are limited together.
+/// As a middleware, it goes inside the retries, so that every attempt takes a token.
+#[must_use]
+#[derive(Clone)]
+pub struct RateLimiter(Arc<RateLimiterInner>);
+
+struct RateLimiterInner {
+    /// Tokens added per second.
+    refill_rate: f64,
+
+    /// Bucket capacity: how many requests may go through without waiting.
+    burst: f64,
+
+    /// Maximum random delay added to a throttled request.
+    max_jitter: Duration,
+
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    /// Available tokens, negative when the requests are queued.
+    tokens: f64,
+
+    updated_at: Instant,
+}
+
+#[bon]
+impl RateLimiter {
+    #[builder]
+    pub fn new(requests_per_minute: u32, burst: u32, max_jitter: Duration) -> Self {
+        Self(Arc::new(RateLimiterInner {
+            refill_rate: f64::from(requests_per_minute) / 60.0,
+            burst: f64::from(burst),
+            max_jitter,
+            buckets: Mutex::default(),
+        }))
+    }
+
+    /// Wait until the request to the URL is allowed.
+    pub async fn acquire(&self, url: &Url) {
+        let host = url.host_str().unwrap_or_default();
+        let delay = self.reserve(host, Instant::now());
+        if delay.is_zero() {
+            return;
+        }
+        let delay = delay + self.0.max_jitter.mul_f64(rand::random_range(0.0..=1.0));
+        debug!("🐢 Throttling…", host = host.to_string(), delay_secs = delay.as_secs_f64());
+        sleep(delay).await;
+    }
+
+    /// Take a token and return the time to wait for it.
+    ///
+    /// The token is taken immediately, even if it is not available yet,
+    /// so that the concurrent requests queue up instead of racing for the next token.
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        let inner = &self.0;
+        let mut buckets = inner.buckets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let bucket = buckets
+            .entry(host.to_string())
+            .or_insert_with(|| Bucket { tokens: inner.burst, updated_at: now });
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = elapsed.mul_add(inner.refill_rate, bucket.tokens).min(inner.burst);
+        bucket.updated_at = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / inner.refill_rate)
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimiter {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.acquire(request.url()).await;
+        next.run(request, extensions).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "www.marktplaats.nl";
+
+    #[test]
+    fn reserve_ok() {
+        let limiter = RateLimiter::builder()
+            .requests_per_minute(60)
+            .burst(2)
+            .max_jitter(Duration::ZERO)
+            .build();
+        let now = Instant::now();
+
+        // Burst:
+        assert_eq!(limiter.reserve(HOST, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(HOST, now), Duration::ZERO);
+
+        // Queued:
+        assert_eq!(limiter.reserve(HOST, now), Duration::from_secs(1));
+        assert_eq!(limiter.reserve(HOST, now), Duration::from_secs(2));
+        assert_eq!(limiter.reserve("api.telegram.org", now), Duration::ZERO);
+
+        // Refilled, but no more than the burst:
+        let now = now + Duration::from_mins(1);
+        assert_eq!(limiter.reserve(HOST, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(HOST, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(HOST, now), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn clones_share_buckets_ok() {
+        let limiter = RateLimiter::builder()
+            .requests_per_minute(1)
+            .burst(1)
+            .max_jitter(Duration::ZERO)
+            .build();
+        let other = limiter.clone();
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(HOST, now), Duration::ZERO);
+        assert_eq!(other.reserve(HOST, now), Duration::from_mins(1));
+    }
+}
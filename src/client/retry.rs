@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use http::Extensions;
+use reqwest::{Request, Response, StatusCode, header};
+use reqwest_middleware::{Middleware, Next};
+use tokio::time::sleep;
+
+use crate::prelude::*;
+
+/// Retries idempotent requests, which failed with a transient error, with jittered exponential backoff.
+///
+/// The server's `Retry-After` takes precedence over the backoff, but is still capped at the maximal backoff.
+pub struct Retry {
+    pub max_retries: u32,
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+#[async_trait]
+impl Middleware for Retry {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if !request.method().is_idempotent() {
+            return next.run(request, extensions).await;
+        }
+        let mut n_retries = 0;
+        loop {
+            // Streaming bodies cannot be cloned, and so cannot be retried:
+            let Some(attempt) = request.try_clone() else {
+                return next.run(request, extensions).await;
+            };
+            let result = next.clone().run(attempt, extensions).await;
+            if n_retries >= self.max_retries || !is_transient(&result) {
+                return result;
+            }
+            let backoff = result.as_ref().ok().and_then(retry_after).map_or_else(
+                || self.backoff(n_retries),
+                |retry_after| retry_after.min(self.max_backoff),
+            );
+            warn!(
+                "🔁 Retrying after a transient error…",
+                url = request.url().to_string(),
+                n_retries = i64::from(n_retries),
+                backoff_secs = backoff.as_secs_f64(),
+            );
+            sleep(backoff).await;
+            n_retries += 1;
+        }
+    }
+}
+
+impl Retry {
+    /// Exponential backoff with the "equal jitter": half of the delay is fixed, and half is random.
+    fn backoff(&self, n_retries: u32) -> Duration {
+        let backoff =
+            self.min_backoff.saturating_mul(2_u32.saturating_pow(n_retries)).min(self.max_backoff);
+        backoff / 2 + backoff.mul_f64(rand::random_range(0.0..=0.5))
+    }
+}
+
+/// Transient errors are worth retrying: connection errors, timeouts, and server-side failures.
+pub fn is_transient(result: &reqwest_middleware::Result<Response>) -> bool {
+    match result {
+        Ok(response) => is_transient_status(response.status()),
+        Err(reqwest_middleware::Error::Reqwest(error)) => error.is_connect() || error.is_timeout(),
+        Err(reqwest_middleware::Error::Middleware(_)) => false,
+    }
+}
+
+/// Delay requested by the server with the `Retry-After` header.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Parse `Retry-After`, which is either the number of seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((retry_at.to_utc() - now).to_std().unwrap_or_default())
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_status_ok() {
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        assert!(!is_transient_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn parse_retry_after_ok() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z").unwrap().to_utc();
+        assert_eq!(parse_retry_after("45", now), Some(Duration::from_secs(45)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30)),
+        );
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backoff_ok() {
+        let retry = Retry {
+            max_retries: 10,
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        for (n_retries, expected) in [(0, 1), (1, 2), (2, 4), (3, 5), (10, 5)] {
+            let expected = Duration::from_secs(expected);
+            let backoff = retry.backoff(n_retries);
+            assert!(backoff >= expected / 2 && backoff <= expected, "{n_retries}: {backoff:?}");
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use std::{collections::BTreeMap, ffi::OsString, fs, net::SocketAddr, path::PathBuf};
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    cli::{Args, AuthorizedChat, HttpHeader, RunArgs},
+    heartbeat::HeartbeatKind,
+    prelude::*,
+};
+
+/// Environment variable, which may specify the configuration file instead of `--config`.
+const CONFIG_ENV: &str = "CONFIG";
+
+/// Replacement for the secrets in the printed configuration.
+const REDACTED: &str = "<redacted>";
+
+/// Configuration file, which mirrors the command-line options.
+///
+/// It has the lowest priority: the values are only used for the options,
+/// which are set neither by the environment variables nor by the flags.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentry_dsn: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logfire_token: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db: Option<PathBuf>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_requests: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_interval_secs: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintenance_interval_hours: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_retention_days: Option<u32>,
+
+    pub telegram: TelegramConfig,
+    pub marktplaats: MarktplaatsConfig,
+    pub status: StatusConfig,
+}
+
+/// Read and merge the configuration file, if any, into the environment.
+///
+/// This must be called before the arguments are parsed and before any threads are spawned.
+pub fn apply(args: impl IntoIterator<Item = OsString>) -> Result<Option<PathBuf>> {
+    let Some(path) = Config::path(args) else {
+        return Ok(None);
+    };
+    let config = Config::read(&path)?;
+    for (key, value) in config.to_env()? {
+        if std::env::var_os(key).is_none() {
+            // SAFETY: we are still single-threaded, see the function's docs.
+            unsafe { std::env::set_var(key, value) };
+        }
+    }
+    Ok(Some(path))
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelegramConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bot_token: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<Url>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll_timeout_secs: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_url: Option<Url>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_kind: Option<HeartbeatKind>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authorized_chats: Vec<AuthorizedChat>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub templates: Option<PathBuf>,
+
+    pub http: HttpConfig,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarktplaatsConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_limit: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pages: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_matches: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_url: Option<Url>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_kind: Option<HeartbeatKind>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_in_title_and_description: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetch_details: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter_millis: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_responses: Option<PathBuf>,
+
+    pub http: HttpConfig,
+}
+
+/// HTTP client settings of a single consumer.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Url>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_language: Option<String>,
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<SocketAddr>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stale_secs: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_address: Option<SocketAddr>,
+}
+
+impl Config {
+    /// Find the configuration file path before the arguments are parsed by `clap`.
+    fn path(args: impl IntoIterator<Item = OsString>) -> Option<PathBuf> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--config" {
+                return args.next().map(PathBuf::from);
+            }
+            if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+                return Some(PathBuf::from(path));
+            }
+        }
+        std::env::var_os(CONFIG_ENV).map(PathBuf::from)
+    }
+
+    fn read(path: &PathBuf) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| {
+            format!("failed to read the configuration file `{}`", path.display())
+        })?;
+        toml::from_str(&contents)
+            .with_context(|| format!("invalid configuration file `{}`", path.display()))
+    }
+
+    /// Convert the configuration into the environment variables, which `clap` reads.
+    fn to_env(&self) -> Result<Vec<(&'static str, String)>> {
+        let mut env = Vec::new();
+        let mut push = |key, value: Option<String>| {
+            if let Some(value) = value {
+                env.push((key, value));
+            }
+        };
+        push("SENTRY_DSN", self.sentry_dsn.clone());
+        push("LOGFIRE_TOKEN", self.logfire_token.clone());
+        push("DB", self.db.as_ref().map(|db| db.display().to_string()));
+        push("TRACE_REQUESTS", self.trace_requests.map(|flag| flag.to_string()));
+        push("SEARCH_INTERVAL_SECS", self.search_interval_secs.map(|secs| secs.to_string()));
+        push(
+            "MAINTENANCE_INTERVAL_HOURS",
+            self.maintenance_interval_hours.map(|hours| hours.to_string()),
+        );
+        push("ITEM_RETENTION_DAYS", self.item_retention_days.map(|days| days.to_string()));
+
+        push("TELEGRAM_BOT_TOKEN", self.telegram.bot_token.clone());
+        push("TELEGRAM_API_URL", self.telegram.api_url.as_ref().map(Url::to_string));
+        push(
+            "TELEGRAM_POLL_TIMEOUT_SECS",
+            self.telegram.poll_timeout_secs.map(|secs| secs.to_string()),
+        );
+        push("TELEGRAM_HEARTBEAT_URL", self.telegram.heartbeat_url.as_ref().map(Url::to_string));
+        push("TELEGRAM_HEARTBEAT_KIND", self.telegram.heartbeat_kind.as_ref().map(value_name));
+        push(
+            "TELEGRAM_TEMPLATES",
+            self.telegram.templates.as_ref().map(|dir| dir.display().to_string()),
+        );
+        if !self.telegram.authorized_chats.is_empty() {
+            for chat in &self.telegram.authorized_chats {
+                if chat.name.as_deref().is_some_and(|name| name.contains(',')) {
+                    bail!("authorized chat #{} name must not contain commas", chat.id);
+                }
+            }
+            push(
+                "TELEGRAM_AUTHORIZED_CHAT_IDS",
+                Some(self.telegram.authorized_chats.iter().map(ToString::to_string).join(",")),
+            );
+        }
+
+        push(
+            "MARKTPLAATS_SEARCH_LIMIT",
+            self.marktplaats.search_limit.map(|limit| limit.to_string()),
+        );
+        push("MARKTPLAATS_MAX_PAGES", self.marktplaats.max_pages.map(|pages| pages.to_string()));
+        push(
+            "MARKTPLAATS_MIN_MATCHES",
+            self.marktplaats.min_matches.map(|matches| matches.to_string()),
+        );
+        push(
+            "MARKTPLAATS_HEARTBEAT_URL",
+            self.marktplaats.heartbeat_url.as_ref().map(Url::to_string),
+        );
+        push(
+            "MARKTPLAATS_HEARTBEAT_KIND",
+            self.marktplaats.heartbeat_kind.as_ref().map(value_name),
+        );
+        push(
+            "MARKTPLAATS_SEARCH_IN_TITLE_AND_DESCRIPTION",
+            self.marktplaats.search_in_title_and_description.map(|flag| flag.to_string()),
+        );
+        push(
+            "MARKTPLAATS_FETCH_DETAILS",
+            self.marktplaats.fetch_details.map(|flag| flag.to_string()),
+        );
+        push(
+            "MARKTPLAATS_REQUESTS_PER_MINUTE",
+            self.marktplaats.requests_per_minute.map(|rate| rate.to_string()),
+        );
+        push("MARKTPLAATS_BURST", self.marktplaats.burst.map(|burst| burst.to_string()));
+        push(
+            "MARKTPLAATS_JITTER_MILLIS",
+            self.marktplaats.jitter_millis.map(|millis| millis.to_string()),
+        );
+        push(
+            "MARKTPLAATS_RECORD_RESPONSES",
+            self.marktplaats.record_responses.as_ref().map(|dir| dir.display().to_string()),
+        );
+
+        push("STATUS_ADDRESS", self.status.address.map(|address| address.to_string()));
+        push("STATUS_MAX_STALE_SECS", self.status.max_stale_secs.map(|secs| secs.to_string()));
+        push("METRICS_ADDRESS", self.status.metrics_address.map(|address| address.to_string()));
+        env.extend(self.telegram.http.to_env([
+            "TELEGRAM_PROXY",
+            "TELEGRAM_USER_AGENT",
+            "TELEGRAM_TIMEOUT_SECS",
+            "TELEGRAM_ACCEPT_LANGUAGE",
+            "TELEGRAM_HEADERS",
+        ])?);
+        env.extend(self.marktplaats.http.to_env([
+            "MARKTPLAATS_PROXY",
+            "MARKTPLAATS_USER_AGENT",
+            "MARKTPLAATS_TIMEOUT_SECS",
+            "MARKTPLAATS_ACCEPT_LANGUAGE",
+            "MARKTPLAATS_HEADERS",
+        ])?);
+        Ok(env)
+    }
+
+    /// Build the effective configuration from the parsed arguments, with the secrets redacted.
+    pub fn redacted(args: &Args, run_args: &RunArgs) -> Self {
+        Self {
+            sentry_dsn: args.sentry_dsn.as_ref().map(|_| REDACTED.to_string()),
+            logfire_token: args.logfire_token.as_ref().map(|_| REDACTED.to_string()),
+            db: Some(args.db.clone()),
+            trace_requests: Some(args.trace_requests),
+            search_interval_secs: Some(run_args.search_interval_secs),
+            maintenance_interval_hours: Some(run_args.maintenance_interval_hours),
+            item_retention_days: Some(run_args.prune.item_retention_days),
+            telegram: TelegramConfig {
+                bot_token: Some(REDACTED.to_string()),
+                api_url: Some(run_args.telegram.api_url.clone()),
+                poll_timeout_secs: Some(run_args.telegram.poll_timeout_secs),
+                heartbeat_url: run_args.telegram.heartbeat_url.as_ref().map(redacted_heartbeat_url),
+                heartbeat_kind: Some(run_args.telegram.heartbeat_kind),
+                authorized_chats: run_args.telegram.authorized_chats.clone(),
+                templates: run_args.telegram.templates.clone(),
+                http: HttpConfig::redacted(
+                    run_args.telegram.proxy.as_ref(),
+                    run_args.telegram.user_agent.clone(),
+                    run_args.telegram.timeout_secs,
+                    run_args.telegram.accept_language.clone(),
+                    &run_args.telegram.headers,
+                ),
+            },
+            marktplaats: MarktplaatsConfig {
+                search_limit: Some(run_args.marktplaats.marktplaats_search_limit),
+                max_pages: Some(run_args.marktplaats.max_pages),
+                min_matches: run_args.marktplaats.min_matches,
+                heartbeat_url: run_args
+                    .marktplaats
+                    .heartbeat_url
+                    .as_ref()
+                    .map(redacted_heartbeat_url),
+                heartbeat_kind: Some(run_args.marktplaats.heartbeat_kind),
+                search_in_title_and_description: Some(
+                    run_args.marktplaats.search_in_title_and_description,
+                ),
+                fetch_details: Some(run_args.marktplaats.fetch_details),
+                requests_per_minute: Some(run_args.marktplaats.requests_per_minute),
+                burst: Some(run_args.marktplaats.burst),
+                jitter_millis: Some(run_args.marktplaats.jitter_millis),
+                record_responses: run_args.marktplaats.record_responses.clone(),
+                http: HttpConfig::redacted(
+                    run_args.marktplaats.proxy.as_ref(),
+                    run_args.marktplaats.user_agent.clone(),
+                    run_args.marktplaats.timeout_secs,
+                    run_args.marktplaats.accept_language.clone(),
+                    &run_args.marktplaats.headers,
+                ),
+            },
+            status: StatusConfig {
+                address: run_args.status.address,
+                max_stale_secs: Some(run_args.status.max_stale_secs),
+                metrics_address: run_args.status.metrics_address,
+            },
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Convert into the environment variables with the keys:
+    /// `[proxy, user_agent, timeout_secs, accept_language, headers]`.
+    fn to_env(&self, keys: [&'static str; 5]) -> Result<Vec<(&'static str, String)>> {
+        let [proxy_key, user_agent_key, timeout_secs_key, accept_language_key, headers_key] = keys;
+        let mut env = Vec::new();
+        if let Some(proxy) = &self.proxy {
+            env.push((proxy_key, proxy.to_string()));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            env.push((user_agent_key, user_agent.clone()));
+        }
+        if let Some(timeout_secs) = self.timeout_secs {
+            env.push((timeout_secs_key, timeout_secs.to_string()));
+        }
+        if let Some(accept_language) = &self.accept_language {
+            env.push((accept_language_key, accept_language.clone()));
+        }
+        if !self.headers.is_empty() {
+            for (name, value) in &self.headers {
+                if name.contains('\n') || value.contains('\n') {
+                    bail!("header `{name}` must not contain line breaks");
+                }
+            }
+            let headers =
+                self.headers.iter().map(|(name, value)| format!("{name}: {value}")).join("\n");
+            env.push((headers_key, headers));
+        }
+        Ok(env)
+    }
+
+    /// The proxy password and the header values may contain credentials, so they are redacted.
+    fn redacted(
+        proxy: Option<&Url>,
+        user_agent: Option<String>,
+        timeout_secs: Option<u64>,
+        accept_language: Option<String>,
+        headers: &[HttpHeader],
+    ) -> Self {
+        let proxy = proxy.map(|proxy| {
+            let mut proxy = proxy.clone();
+            if proxy.password().is_some() {
+                // Only fails for the URLs without a host, which cannot have a password anyway:
+                let _ = proxy.set_password(Some(REDACTED));
+            }
+            proxy
+        });
+        Self {
+            proxy,
+            user_agent,
+            timeout_secs,
+            accept_language,
+            headers: headers
+                .iter()
+                .map(|header| (header.name.to_string(), REDACTED.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Heartbeat URLs carry the check's token in the path or the query, so only the origin is kept.
+fn redacted_heartbeat_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    // Only fail for the URLs without a host, which have nothing to keep anyway:
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.set_path(REDACTED);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+/// Value name, which `clap` parses back.
+fn value_name(value: &impl ValueEnum) -> String {
+    value.to_possible_value().map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::cli::{Command, ConfigCommand};
+
+    // language=toml
+    const CONFIG: &str = r#"
+        db = "/var/lib/mrktpltsbot.sqlite3"
+        search_interval_secs = 120
+
+        [telegram]
+        bot_token = "123:secret"
+
+        [[telegram.authorized_chats]]
+        id = 42
+        name = "Family"
+
+        [[telegram.authorized_chats]]
+        id = -100
+
+        [marktplaats]
+        fetch_details = true
+        heartbeat_kind = "uptime-kuma"
+
+        [marktplaats.http]
+        proxy = "socks5h://127.0.0.1:9050"
+        headers = { "X-Forwarded-For" = "127.0.0.1", "DNT" = "1" }
+    "#;
+
+    #[test]
+    fn path_from_args_ok() {
+        let path = Config::path(["mrktpltsbot", "--config", "a.toml", "run"].map(OsString::from));
+        assert_eq!(path, Some(PathBuf::from("a.toml")));
+        let path = Config::path(["mrktpltsbot", "--config=b.toml", "run"].map(OsString::from));
+        assert_eq!(path, Some(PathBuf::from("b.toml")));
+    }
+
+    #[test]
+    fn to_env_ok() -> Result {
+        let config: Config = toml::from_str(CONFIG)?;
+        let env = config.to_env()?;
+        assert!(env.contains(&("DB", "/var/lib/mrktpltsbot.sqlite3".to_string())));
+        assert!(env.contains(&("SEARCH_INTERVAL_SECS", "120".to_string())));
+        assert!(env.contains(&("TELEGRAM_AUTHORIZED_CHAT_IDS", "42:Family,-100".to_string())));
+        assert!(env.contains(&("MARKTPLAATS_FETCH_DETAILS", "true".to_string())));
+        assert!(env.contains(&("MARKTPLAATS_HEARTBEAT_KIND", "uptime-kuma".to_string())));
+        assert!(env.contains(&("MARKTPLAATS_PROXY", "socks5h://127.0.0.1:9050".to_string())));
+        assert!(
+            env.contains(&(
+                "MARKTPLAATS_HEADERS",
+                "DNT: 1\nX-Forwarded-For: 127.0.0.1".to_string()
+            ))
+        );
+        assert!(!env.iter().any(|(key, _)| *key == "MARKTPLAATS_SEARCH_LIMIT"));
+        assert!(!env.iter().any(|(key, _)| *key == "TELEGRAM_PROXY"));
+        Ok(())
+    }
+
+    #[test]
+    fn redacted_ok() -> Result {
+        let args = Args::try_parse_from([
+            "mrktpltsbot",
+            "config",
+            "check",
+            "--telegram-bot-token",
+            "123:secret",
+            "--telegram-heartbeat-url",
+            "https://hc-ping.com/0e3f0c8a-secret",
+            "--marktplaats-heartbeat-url",
+            "https://uptime.example.com/api/push/secret?status=up",
+        ])?;
+        let Command::Config(ConfigCommand::Check(run_args)) = &args.command else {
+            bail!("unexpected command");
+        };
+        let config = toml::to_string(&Config::redacted(&args, run_args))?;
+        assert!(!config.contains("secret"), "{config}");
+        assert!(config.contains("https://hc-ping.com/"), "{config}");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_key_err() {
+        let error = toml::from_str::<Config>("[telegram]\nbot_tokn = \"123\"").unwrap_err();
+        assert!(error.to_string().contains("bot_tokn"), "{error}");
+    }
+
+    #[test]
+    fn comma_in_chat_name_err() -> Result {
+        let config: Config =
+            toml::from_str("[[telegram.authorized_chats]]\nid = 42\nname = \"Me, myself\"")?;
+        assert!(config.to_env().is_err());
+        Ok(())
+    }
+}
@@ -1,54 +1,126 @@
+mod chat;
+mod export;
 mod item;
+mod item_details;
 mod key_values;
+mod muted_seller;
 mod notification;
 mod search_query;
 mod subscription;
 
-use std::{path::Path, sync::Arc};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
+use chrono::{DateTime, Utc};
+use metrics::{counter, gauge, histogram};
 use sqlx::{
-    ConnectOptions,
     FromRow,
-    SqliteConnection,
+    Sqlite,
+    SqlitePool,
+    Transaction,
     migrate::Migrator,
-    sqlite::SqliteConnectOptions,
+    pool::PoolConnection,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
 };
 use sqlx_sqlite::SqliteRow;
-use tokio::sync::{Mutex, MutexGuard};
 
 pub use self::{
+    chat::{ChatSettings, Chats},
+    export::{Export, ExportedSubscription},
     item::{Item, Items},
+    item_details::ItemDetails,
+    key_values::{KeyValues, KeyedMessage},
+    muted_seller::{MutedSeller, MutedSellers},
     notification::{Notification, Notifications},
     search_query::{SearchQueries, SearchQuery},
     subscription::{Subscription, Subscriptions},
 };
-use crate::prelude::*;
+use crate::{
+    prelude::*,
+    prometheus::{
+        DB_ACQUIRE_DURATION,
+        DB_CONNECTIONS,
+        DB_IDLE_CONNECTIONS,
+        DB_QUERY_DURATION,
+        DB_TRANSACTIONS,
+    },
+};
 
 static MIGRATOR: Migrator = sqlx::migrate!();
 
+/// How long a connection waits for a concurrent writer before failing with `SQLITE_BUSY`.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_CONNECTIONS: u32 = 4;
+
 #[must_use]
 #[derive(Clone)]
-pub struct Db(Arc<Mutex<SqliteConnection>>);
+pub struct Db(SqlitePool);
 
 impl Db {
     /// TODO: change `Path` into `AsRef<Path>`.
     #[instrument(name = "💾 Opening the database…")]
     pub async fn try_new(path: &Path) -> Result<Self> {
-        let mut connection = SqliteConnectOptions::new()
+        let connect_options = SqliteConnectOptions::new()
             .create_if_missing(true)
             .filename(path)
-            .connect()
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT);
+
+        // Every in-memory connection opens its own database, so the pool must stick to exactly one:
+        let pool_options = if path == Path::new(":memory:") {
+            SqlitePoolOptions::new().max_connections(1).idle_timeout(None).max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new().max_connections(MAX_CONNECTIONS)
+        };
+
+        let pool = pool_options
+            .connect_with(connect_options)
             .await
             .with_context(|| format!("failed to open database `{}`", path.display()))?;
-        MIGRATOR.run(&mut connection).await.context("failed to migrate the database")?;
+        MIGRATOR.run(&pool).await.context("failed to migrate the database")?;
         info!("✅ The database is ready");
-        Ok(Self(Arc::new(Mutex::new(connection))))
+        Ok(Self(pool))
+    }
+
+    /// Acquire a connection from the pool.
+    pub async fn connection(&self) -> Result<PoolConnection<Sqlite>> {
+        let start_time = Instant::now();
+        let connection =
+            self.0.acquire().await.context("failed to acquire a database connection")?;
+        self.record_pool_metrics(start_time);
+        Ok(connection)
     }
 
-    /// Lock and return the connection.
-    pub async fn connection(&self) -> MutexGuard<'_, SqliteConnection> {
-        self.0.lock().await
+    /// Begin a transaction, which is rolled back unless explicitly committed.
+    pub async fn begin(&self) -> Result<Transaction<'static, Sqlite>> {
+        self.begin_with("BEGIN").await
+    }
+
+    /// Begin a transaction, which takes the write lock right away.
+    ///
+    /// Use it to read and then write, so that a concurrent writer cannot fail the upgrade from the read lock.
+    pub async fn begin_immediate(&self) -> Result<Transaction<'static, Sqlite>> {
+        self.begin_with("BEGIN IMMEDIATE").await
+    }
+
+    async fn begin_with(&self, statement: &'static str) -> Result<Transaction<'static, Sqlite>> {
+        let start_time = Instant::now();
+        let transaction =
+            self.0.begin_with(statement).await.context("failed to begin a transaction")?;
+        self.record_pool_metrics(start_time);
+        counter!(DB_TRANSACTIONS).increment(1);
+        Ok(transaction)
+    }
+
+    fn record_pool_metrics(&self, acquire_start_time: Instant) {
+        histogram!(DB_ACQUIRE_DURATION).record(acquire_start_time.elapsed());
+        gauge!(DB_CONNECTIONS).set(self.0.size());
+        gauge!(DB_IDLE_CONNECTIONS).set(u32::try_from(self.0.num_idle()).unwrap_or(u32::MAX));
     }
 
     pub async fn subscriptions_of(&self, chat_id: i64) -> Result<Vec<(Subscription, SearchQuery)>> {
@@ -62,7 +134,8 @@ impl Db {
 
         sqlx::query(QUERY)
             .bind(chat_id)
-            .fetch_all(&mut *self.connection().await)
+            .fetch_all(&mut *self.connection().await?)
+            .timed("db.subscriptions_of")
             .await
             .with_context(|| format!("failed to fetch subscriptions of chat #{chat_id}"))?
             .into_iter()
@@ -70,25 +143,46 @@ impl Db {
             .collect()
     }
 
-    /// Retrieve the first subscription, or `None` – if there are no subscriptions.
+    /// Fetch the subscriptions of all the chats.
+    pub async fn all_subscriptions(&self) -> Result<Vec<(Subscription, SearchQuery)>> {
+        // language=sql
+        const QUERY: &str = r"
+            SELECT search_queries.*, subscriptions.* FROM subscriptions
+            JOIN search_queries ON search_queries.hash = subscriptions.query_hash
+            ORDER BY subscriptions.chat_id, search_queries.text
+        ";
+
+        sqlx::query(QUERY)
+            .fetch_all(&mut *self.connection().await?)
+            .timed("db.all_subscriptions")
+            .await
+            .context("failed to fetch all subscriptions")?
+            .into_iter()
+            .map(enriched_subscription_from_row)
+            .collect()
+    }
+
+    /// Retrieve the first active subscription, or `None` – if there are no active subscriptions.
     pub async fn first_subscription(&self) -> Result<Option<(Subscription, SearchQuery)>> {
         // language=sql
         const QUERY: &str = r"
             SELECT search_queries.*, subscriptions.* FROM subscriptions
             JOIN search_queries ON search_queries.hash = subscriptions.query_hash
+            WHERE NOT subscriptions.is_paused
             ORDER BY subscriptions.chat_id, subscriptions.query_hash
             LIMIT 1
         ";
 
         sqlx::query(QUERY)
-            .fetch_optional(&mut *self.connection().await)
+            .fetch_optional(&mut *self.connection().await?)
+            .timed("db.first_subscription")
             .await
             .context("failed to fetch the first subscription")?
             .map(enriched_subscription_from_row)
             .transpose()
     }
 
-    /// Retrieve the next subscription, or [`None`] – if `current` is the last subscription.
+    /// Retrieve the next active subscription, or [`None`] – if `current` is the last one.
     #[instrument(
         name = "💾 Fetching next subscription…",
         level = Level::DEBUG,
@@ -103,14 +197,15 @@ impl Db {
         const QUERY: &str = r"
             SELECT search_queries.*, subscriptions.* FROM subscriptions
             JOIN search_queries ON search_queries.hash = subscriptions.query_hash
-            WHERE (subscriptions.chat_id, subscriptions.query_hash) > (?1, ?2)
+            WHERE (subscriptions.chat_id, subscriptions.query_hash) > (?1, ?2) AND NOT subscriptions.is_paused
             ORDER BY subscriptions.chat_id, subscriptions.query_hash
             LIMIT 1
         ";
         sqlx::query(QUERY)
             .bind(current.chat_id)
             .bind(current.query_hash)
-            .fetch_optional(&mut *self.connection().await)
+            .fetch_optional(&mut *self.connection().await?)
+            .timed("db.next_subscription")
             .await
             .context("failed to fetch the next subscription")?
             .map(enriched_subscription_from_row)
@@ -118,6 +213,61 @@ impl Db {
     }
 }
 
+impl Db {
+    /// Delete the items which have not been seen since `updated_before`, together with their
+    /// notifications, and the search queries without subscribers, which have not been used since then.
+    #[instrument(name = "💾 Pruning the database…", skip_all, fields(updated_before = ?updated_before))]
+    pub async fn prune(&self, updated_before: DateTime<Utc>) -> Result<PruneReport> {
+        let mut transaction = self.begin().await?;
+        let n_notifications =
+            Notifications(&mut transaction).delete_of_stale_items(updated_before).await?;
+        let n_items = Items(&mut transaction).delete_stale(updated_before).await?;
+        let n_search_queries =
+            SearchQueries(&mut transaction).delete_orphans(updated_before).await?;
+        transaction.commit().await.context("failed to commit the pruning")?;
+        Ok(PruneReport { n_items, n_notifications, n_search_queries })
+    }
+
+    /// Let `SQLite` update its statistics, and rebuild the database file to reclaim the free space.
+    #[instrument(name = "💾 Optimizing the database…", skip_all)]
+    pub async fn optimize(&self) -> Result {
+        let mut connection = self.connection().await?;
+        sqlx::query("PRAGMA optimize")
+            .execute(&mut *connection)
+            .timed("db.optimize")
+            .await
+            .context("failed to optimize the database")?;
+        sqlx::query("VACUUM")
+            .execute(&mut *connection)
+            .timed("db.vacuum")
+            .await
+            .context("failed to vacuum")?;
+        Ok(())
+    }
+}
+
+/// Records the latency of a database query.
+trait Timed: Future + Sized {
+    /// Await the query, and record its latency under the name, like `subscription.fetch`.
+    async fn timed(self, query: &'static str) -> Self::Output {
+        let start_time = Instant::now();
+        let output = self.await;
+        histogram!(DB_QUERY_DURATION, "query" => query).record(start_time.elapsed());
+        output
+    }
+}
+
+impl<F: Future> Timed for F {}
+
+/// Numbers of the rows removed by the pruning.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[expect(clippy::struct_field_names)]
+pub struct PruneReport {
+    pub n_items: u64,
+    pub n_notifications: u64,
+    pub n_search_queries: u64,
+}
+
 #[expect(clippy::needless_pass_by_value)]
 fn enriched_subscription_from_row(row: SqliteRow) -> Result<(Subscription, SearchQuery)> {
     Ok((Subscription::from_row(&row)?, SearchQuery::from_row(&row)?))
@@ -125,9 +275,34 @@ fn enriched_subscription_from_row(row: SqliteRow) -> Result<(Subscription, Searc
 
 #[cfg(test)]
 mod tests {
+    use chrono::TimeDelta;
+    use metrics_util::debugging::DebuggingRecorder;
+
     use super::*;
     use crate::db::{search_query::SearchQueries, subscription::Subscriptions};
 
+    #[tokio::test]
+    async fn metrics_ok() -> Result {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        db.subscriptions_of(42).await?;
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let has_histogram = |name: &str, query: Option<&str>| {
+            snapshot.iter().any(|(key, _, _, _)| {
+                key.key().name() == name
+                    && query
+                        .is_none_or(|query| key.key().labels().any(|label| label.value() == query))
+            })
+        };
+        assert!(has_histogram(DB_ACQUIRE_DURATION, None));
+        assert!(has_histogram(DB_QUERY_DURATION, Some("db.subscriptions_of")));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_fetch_subscriptions_ok() -> Result {
         let db = Db::try_new(Path::new(":memory:")).await?;
@@ -137,13 +312,13 @@ mod tests {
         let search_query_2 = SearchQuery::from("unifi");
 
         // Subscriptions, the ordering matches the primary key and the queries:
-        let subscription_first = Subscription { chat_id: 42, query_hash: search_query_1.hash };
-        let subscription_middle = Subscription { chat_id: 42, query_hash: search_query_2.hash };
-        let subscription_last = Subscription { chat_id: 43, query_hash: search_query_2.hash };
+        let subscription_first = Subscription::new(search_query_1.hash, 42);
+        let subscription_middle = Subscription::new(search_query_2.hash, 42);
+        let subscription_last = Subscription::new(search_query_2.hash, 43);
 
         // Setting up:
         {
-            let connection = &mut *db.connection().await;
+            let connection = &mut *db.connection().await?;
             SearchQueries(connection).upsert(&search_query_1).await?;
             SearchQueries(connection).upsert(&search_query_2).await?;
             Subscriptions(connection).upsert(subscription_first).await?;
@@ -173,6 +348,63 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn file_database_is_in_wal_mode_ok() -> Result {
+        let path = std::env::temp_dir().join(format!("mrktpltsbot-{}.sqlite3", std::process::id()));
+        let db = Db::try_new(&path).await?;
+
+        // Hold one connection while using another one:
+        let mut connection_1 = db.connection().await?;
+        let mut connection_2 = db.connection().await?;
+        let journal_mode: String =
+            sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&mut *connection_1).await?;
+        assert_eq!(journal_mode, "wal");
+        SearchQueries(&mut connection_2).upsert(&SearchQuery::from("tado")).await?;
+
+        drop((connection_1, connection_2));
+        db.0.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prune_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let now = Utc::now();
+        let subscribed_query = SearchQuery::from("tado");
+        {
+            let connection = &mut *db.connection().await?;
+            SearchQueries(connection).upsert(&subscribed_query).await?;
+            SearchQueries(connection).upsert(&SearchQuery::from("unifi")).await?;
+            Subscriptions(connection).upsert(Subscription::new(subscribed_query.hash, 42)).await?;
+            Items(connection)
+                .upsert(Item { id: "m1", updated_at: now - TimeDelta::days(100) })
+                .await?;
+            Items(connection).upsert(Item { id: "m2", updated_at: now }).await?;
+            for item_id in ["m1", "m2"] {
+                Notifications(connection)
+                    .upsert(&Notification { item_id: item_id.to_string(), chat_id: 42 })
+                    .await?;
+            }
+        }
+
+        let report = db.prune(now - TimeDelta::days(30)).await?;
+        assert_eq!(
+            report,
+            PruneReport { n_items: 1, n_notifications: 1, n_search_queries: 0 },
+            "the unsubscribed query has just been used",
+        );
+        db.optimize().await?;
+
+        let connection = &mut *db.connection().await?;
+        let notification = Notification { item_id: "m2".to_string(), chat_id: 42 };
+        assert!(Notifications(connection).exists(&notification).await?);
+        assert_eq!(SearchQueries(connection).fetch_text(subscribed_query.hash).await?, "tado");
+        Ok(())
+    }
+
     /// Test the subscription stream on an empty database.
     #[tokio::test]
     async fn test_empty_ok() -> Result {
@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+
+use crate::{db::Timed, prelude::*};
+
+/// Chat, which is known by its subscriptions or muted sellers.
+#[derive(Clone, Debug, Eq, PartialEq, FromRow, Serialize)]
+#[expect(clippy::struct_field_names)]
+pub struct Chat {
+    pub chat_id: i64,
+    pub n_subscriptions: i64,
+    pub n_paused_subscriptions: i64,
+    pub n_muted_sellers: i64,
+}
+
+/// Settings chosen by the chat, the detected language code is not included.
+#[derive(Clone, Debug, Eq, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct ChatSettings {
+    pub chat_id: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+pub struct Chats<'a>(pub &'a mut SqliteConnection);
+
+impl Chats<'_> {
+    /// Fetch all the known chats ordered by ID.
+    #[instrument(name = "💾 Fetching chats…", level = Level::DEBUG, skip_all)]
+    pub async fn fetch_all(&mut self) -> Result<Vec<Chat>> {
+        // language=sql
+        const QUERY: &str = "
+            SELECT
+                chats.chat_id,
+                (SELECT COUNT(*) FROM subscriptions WHERE subscriptions.chat_id = chats.chat_id) AS n_subscriptions,
+                (
+                    SELECT COUNT(*) FROM subscriptions
+                    WHERE subscriptions.chat_id = chats.chat_id AND subscriptions.is_paused
+                ) AS n_paused_subscriptions,
+                (SELECT COUNT(*) FROM muted_sellers WHERE muted_sellers.chat_id = chats.chat_id) AS n_muted_sellers
+            FROM (SELECT chat_id FROM subscriptions UNION SELECT chat_id FROM muted_sellers) AS chats
+            ORDER BY chats.chat_id
+        ";
+        sqlx::query_as(QUERY)
+            .fetch_all(&mut *self.0)
+            .timed("chat.fetch_all")
+            .await
+            .context("failed to fetch the chats")
+    }
+
+    /// Remember the latest Telegram language code of the chat's user.
+    #[instrument(
+        name = "💾 Upserting chat language code…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id, language_code = language_code),
+    )]
+    pub async fn upsert_language_code(&mut self, chat_id: i64, language_code: &str) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO chat_settings (chat_id, language_code) VALUES (?1, ?2)
+            ON CONFLICT DO UPDATE SET language_code = ?2
+        ";
+        sqlx::query(QUERY)
+            .bind(chat_id)
+            .bind(language_code)
+            .execute(&mut *self.0)
+            .timed("chat.upsert_language_code")
+            .await
+            .with_context(|| format!("failed to store the language code of chat #{chat_id}"))?;
+        Ok(())
+    }
+
+    /// Fetch the latest Telegram language code of the chat's user.
+    #[instrument(
+        name = "💾 Fetching chat language code…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_language_code(&mut self, chat_id: i64) -> Result<Option<String>> {
+        // language=sql
+        const QUERY: &str = "SELECT language_code FROM chat_settings WHERE chat_id = ?1";
+        let language_code: Option<Option<String>> = sqlx::query_scalar(QUERY)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("chat.fetch_language_code")
+            .await
+            .with_context(|| format!("failed to fetch the language code of chat #{chat_id}"))?;
+        Ok(language_code.flatten())
+    }
+
+    /// Choose the chat's language, or reset it to the detected one – if `None`.
+    #[instrument(
+        name = "💾 Setting chat language…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id, language = language),
+    )]
+    pub async fn set_language(&mut self, chat_id: i64, language: Option<&str>) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO chat_settings (chat_id, language) VALUES (?1, ?2)
+            ON CONFLICT DO UPDATE SET language = ?2
+        ";
+        sqlx::query(QUERY)
+            .bind(chat_id)
+            .bind(language)
+            .execute(&mut *self.0)
+            .timed("chat.set_language")
+            .await
+            .with_context(|| format!("failed to set the language of chat #{chat_id}"))?;
+        Ok(())
+    }
+
+    /// Fetch the chosen language code, or the detected one – if none is chosen.
+    #[instrument(
+        name = "💾 Fetching chat language…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_language(&mut self, chat_id: i64) -> Result<Option<String>> {
+        // language=sql
+        const QUERY: &str =
+            "SELECT COALESCE(language, language_code) FROM chat_settings WHERE chat_id = ?1";
+        let language: Option<Option<String>> = sqlx::query_scalar(QUERY)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("chat.fetch_language")
+            .await
+            .with_context(|| format!("failed to fetch the language of chat #{chat_id}"))?;
+        Ok(language.flatten())
+    }
+
+    /// Choose the chat's notification template, or reset it to the default one – if `None`.
+    #[instrument(
+        name = "💾 Setting chat template…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id, template = template),
+    )]
+    pub async fn set_template(&mut self, chat_id: i64, template: Option<&str>) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO chat_settings (chat_id, template) VALUES (?1, ?2)
+            ON CONFLICT DO UPDATE SET template = ?2
+        ";
+        sqlx::query(QUERY)
+            .bind(chat_id)
+            .bind(template)
+            .execute(&mut *self.0)
+            .timed("chat.set_template")
+            .await
+            .with_context(|| format!("failed to set the template of chat #{chat_id}"))?;
+        Ok(())
+    }
+
+    /// Fetch the chat's chosen settings, or `None` – if nothing is chosen.
+    #[instrument(
+        name = "💾 Fetching chat settings…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_settings(&mut self, chat_id: i64) -> Result<Option<ChatSettings>> {
+        // language=sql
+        const QUERY: &str = "
+            SELECT chat_id, language, template FROM chat_settings
+            WHERE chat_id = ?1 AND (language IS NOT NULL OR template IS NOT NULL)
+        ";
+        sqlx::query_as(QUERY)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("chat.fetch_settings")
+            .await
+            .with_context(|| format!("failed to fetch the settings of chat #{chat_id}"))
+    }
+
+    /// Fetch the chosen settings of all the chats ordered by ID.
+    #[instrument(name = "💾 Fetching all chat settings…", level = Level::DEBUG, skip_all)]
+    pub async fn fetch_all_settings(&mut self) -> Result<Vec<ChatSettings>> {
+        // language=sql
+        const QUERY: &str = "
+            SELECT chat_id, language, template FROM chat_settings
+            WHERE language IS NOT NULL OR template IS NOT NULL
+            ORDER BY chat_id
+        ";
+        sqlx::query_as(QUERY)
+            .fetch_all(&mut *self.0)
+            .timed("chat.fetch_all_settings")
+            .await
+            .context("failed to fetch all the chat settings")
+    }
+
+    #[instrument(
+        name = "💾 Fetching chat template…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_template(&mut self, chat_id: i64) -> Result<Option<String>> {
+        // language=sql
+        const QUERY: &str = "SELECT template FROM chat_settings WHERE chat_id = ?1";
+        let template: Option<Option<String>> = sqlx::query_scalar(QUERY)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("chat.fetch_template")
+            .await
+            .with_context(|| format!("failed to fetch the template of chat #{chat_id}"))?;
+        Ok(template.flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::db::{
+        Db,
+        MutedSeller,
+        MutedSellers,
+        SearchQueries,
+        SearchQuery,
+        Subscription,
+        Subscriptions,
+    };
+
+    #[tokio::test]
+    async fn fetch_all_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let search_query = SearchQuery::from("unifi");
+        SearchQueries(&mut connection).upsert(&search_query).await?;
+        Subscriptions(&mut connection).upsert(Subscription::new(search_query.hash, 42)).await?;
+        Subscriptions(&mut connection).pause(search_query.hash, 42, None).await?;
+        MutedSellers(&mut connection)
+            .upsert(&MutedSeller { chat_id: 43, seller_id: 1, seller_name: "FlimDeal".to_string() })
+            .await?;
+
+        assert_eq!(
+            Chats(&mut connection).fetch_all().await?,
+            [
+                Chat {
+                    chat_id: 42,
+                    n_subscriptions: 1,
+                    n_paused_subscriptions: 1,
+                    n_muted_sellers: 0
+                },
+                Chat {
+                    chat_id: 43,
+                    n_subscriptions: 0,
+                    n_paused_subscriptions: 0,
+                    n_muted_sellers: 1
+                },
+            ],
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn language_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let mut chats = Chats(&mut connection);
+        assert_eq!(chats.fetch_language(42).await?, None);
+
+        chats.upsert_language_code(42, "en").await?;
+        assert_eq!(chats.fetch_language(42).await?.as_deref(), Some("en"));
+
+        chats.set_language(42, Some("nl")).await?;
+        chats.upsert_language_code(42, "en-GB").await?;
+        assert_eq!(chats.fetch_language(42).await?.as_deref(), Some("nl"), "chosen overrides");
+
+        chats.set_language(42, None).await?;
+        assert_eq!(chats.fetch_language(42).await?.as_deref(), Some("en-GB"));
+        assert_eq!(chats.fetch_language_code(42).await?.as_deref(), Some("en-GB"));
+        assert_eq!(chats.fetch_language(43).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn template_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let search_query = SearchQuery::from("unifi");
+        SearchQueries(&mut connection).upsert(&search_query).await?;
+        Subscriptions(&mut connection).upsert(Subscription::new(search_query.hash, 42)).await?;
+
+        Chats(&mut connection).upsert_language_code(42, "nl").await?;
+        assert_eq!(Chats(&mut connection).fetch_template(42).await?, None);
+        Chats(&mut connection).set_template(42, Some("compact")).await?;
+        assert_eq!(Chats(&mut connection).fetch_template(42).await?.as_deref(), Some("compact"));
+        assert_eq!(
+            Chats(&mut connection).fetch_language(42).await?.as_deref(),
+            Some("nl"),
+            "the language is kept",
+        );
+
+        let mut subscriptions = Subscriptions(&mut connection);
+        assert_eq!(subscriptions.fetch_template(search_query.hash, 42).await?, None);
+        assert!(subscriptions.set_template(search_query.hash, 42, Some("full")).await?);
+        assert_eq!(
+            subscriptions.fetch_template(search_query.hash, 42).await?.as_deref(),
+            Some("full"),
+        );
+        assert!(!subscriptions.set_template(search_query.hash, 43, Some("full")).await?);
+        Ok(())
+    }
+}
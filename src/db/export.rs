@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+
+use crate::{
+    db::{
+        ChatSettings,
+        Chats,
+        Db,
+        MutedSeller,
+        MutedSellers,
+        SearchQueries,
+        SearchQuery,
+        Subscription,
+        Subscriptions,
+        subscription::SubscriptionFilters,
+    },
+    prelude::*,
+};
+
+/// Portable snapshot of the user data, which is independent of the database schema.
+///
+/// Items and notifications are not exported: they are re-crawled anyway.
+#[derive(Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Export {
+    pub subscriptions: Vec<ExportedSubscription>,
+    pub muted_sellers: Vec<MutedSeller>,
+    pub chat_settings: Vec<ChatSettings>,
+}
+
+/// Subscription with its search query text instead of the hash.
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ExportedSubscription {
+    pub chat_id: i64,
+    pub query: String,
+
+    #[serde(default)]
+    pub is_paused: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub filters: SubscriptionFilters,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+impl Export {
+    /// Move all the entries into the chat, so that a chat could only import into itself.
+    pub fn into_chat(mut self, chat_id: i64) -> Self {
+        for subscription in &mut self.subscriptions {
+            subscription.chat_id = chat_id;
+        }
+        for muted_seller in &mut self.muted_sellers {
+            muted_seller.chat_id = chat_id;
+        }
+        self.chat_settings.truncate(1);
+        for settings in &mut self.chat_settings {
+            settings.chat_id = chat_id;
+        }
+        self
+    }
+}
+
+impl From<(Subscription, SearchQuery)> for ExportedSubscription {
+    fn from((subscription, search_query): (Subscription, SearchQuery)) -> Self {
+        Self {
+            chat_id: subscription.chat_id,
+            query: search_query.text,
+            is_paused: subscription.is_paused,
+            resume_at: subscription.resume_at,
+            filters: subscription.filters,
+            template: None,
+        }
+    }
+}
+
+/// Numbers of the imported entries.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[expect(clippy::struct_field_names)]
+pub struct ImportReport {
+    pub n_subscriptions_added: u64,
+    pub n_subscriptions_updated: u64,
+    pub n_subscriptions_unchanged: u64,
+    pub n_muted_sellers_added: u64,
+    pub n_chat_settings_updated: u64,
+}
+
+impl Db {
+    /// Export the user data of the specified chat, or of all the chats.
+    #[instrument(name = "💾 Exporting…", skip_all, fields(chat_id = chat_id))]
+    pub async fn export(&self, chat_id: Option<i64>) -> Result<Export> {
+        let subscriptions = match chat_id {
+            Some(chat_id) => self.subscriptions_of(chat_id).await?,
+            None => self.all_subscriptions().await?,
+        };
+        let mut connection = self.connection().await?;
+        let mut exported_subscriptions = Vec::with_capacity(subscriptions.len());
+        let mut templates: HashMap<i64, HashMap<i64, String>> = HashMap::new();
+        for (subscription, search_query) in subscriptions {
+            let chat_templates = match templates.entry(subscription.chat_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    Subscriptions(&mut connection).fetch_templates(subscription.chat_id).await?,
+                ),
+            };
+            exported_subscriptions.push(ExportedSubscription {
+                template: chat_templates.get(&subscription.query_hash).cloned(),
+                ..ExportedSubscription::from((subscription, search_query))
+            });
+        }
+        let (muted_sellers, chat_settings) = match chat_id {
+            Some(chat_id) => (
+                MutedSellers(&mut connection).fetch_all(chat_id).await?,
+                Chats(&mut connection).fetch_settings(chat_id).await?.into_iter().collect(),
+            ),
+            None => (
+                MutedSellers(&mut connection).fetch_all_chats().await?,
+                Chats(&mut connection).fetch_all_settings().await?,
+            ),
+        };
+        Ok(Export { subscriptions: exported_subscriptions, muted_sellers, chat_settings })
+    }
+
+    /// Merge the export into the database.
+    ///
+    /// Existing subscriptions are updated, and nothing gets deleted, so importing is idempotent.
+    /// With `dry_run`, the changes are only logged and rolled back.
+    #[instrument(name = "💾 Importing…", skip_all, fields(dry_run = dry_run))]
+    pub async fn import(&self, export: &Export, dry_run: bool) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut transaction = self.begin().await?;
+        Self::import_subscriptions(&mut transaction, &export.subscriptions, &mut report).await?;
+        Self::import_muted_sellers(&mut transaction, &export.muted_sellers, &mut report).await?;
+
+        for settings in &export.chat_settings {
+            let mut chats = Chats(&mut transaction);
+            if chats.fetch_settings(settings.chat_id).await?.as_ref() != Some(settings) {
+                info!(
+                    "⚙️ Updating chat settings",
+                    chat_id = settings.chat_id,
+                    language = settings.language.clone(),
+                    template = settings.template.clone(),
+                );
+                chats.set_language(settings.chat_id, settings.language.as_deref()).await?;
+                chats.set_template(settings.chat_id, settings.template.as_deref()).await?;
+                report.n_chat_settings_updated += 1;
+            }
+        }
+
+        if dry_run {
+            transaction.rollback().await.context("failed to roll back the import")?;
+        } else {
+            transaction.commit().await.context("failed to commit the import")?;
+        }
+        Ok(report)
+    }
+
+    /// Add or update the subscriptions, looking up the existing templates once per chat.
+    async fn import_subscriptions(
+        connection: &mut SqliteConnection,
+        subscriptions: &[ExportedSubscription],
+        report: &mut ImportReport,
+    ) -> Result {
+        let mut templates: HashMap<i64, HashMap<i64, String>> = HashMap::new();
+        for exported in subscriptions {
+            let search_query = SearchQuery::from(&exported.query);
+            SearchQueries(&mut *connection).upsert(&search_query).await?;
+            let subscription = Subscription {
+                query_hash: search_query.hash,
+                chat_id: exported.chat_id,
+                is_paused: exported.is_paused,
+                resume_at: exported.resume_at,
+                filters: exported.filters,
+            };
+            let chat_templates = match templates.entry(exported.chat_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    Subscriptions(&mut *connection).fetch_templates(exported.chat_id).await?,
+                ),
+            };
+            let existing_template = match &exported.template {
+                Some(template) => chat_templates.insert(search_query.hash, template.clone()),
+                None => chat_templates.remove(&search_query.hash),
+            };
+            let mut subscriptions = Subscriptions(&mut *connection);
+            match subscriptions.fetch(search_query.hash, exported.chat_id).await? {
+                None => {
+                    info!(
+                        "➕ Adding subscription",
+                        chat_id = exported.chat_id,
+                        query = &exported.query
+                    );
+                    subscriptions.upsert(subscription).await?;
+                    subscriptions
+                        .set_template(
+                            search_query.hash,
+                            exported.chat_id,
+                            exported.template.as_deref(),
+                        )
+                        .await?;
+                    report.n_subscriptions_added += 1;
+                }
+                Some(existing)
+                    if existing != subscription || existing_template != exported.template =>
+                {
+                    info!(
+                        "✏️ Updating subscription",
+                        chat_id = exported.chat_id,
+                        query = &exported.query
+                    );
+                    subscriptions.upsert(subscription).await?;
+                    subscriptions
+                        .set_filters(search_query.hash, exported.chat_id, subscription.filters)
+                        .await?;
+                    subscriptions
+                        .set_template(
+                            search_query.hash,
+                            exported.chat_id,
+                            exported.template.as_deref(),
+                        )
+                        .await?;
+                    report.n_subscriptions_updated += 1;
+                }
+                Some(_) => {
+                    report.n_subscriptions_unchanged += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Mute the sellers, looking up the muted ones once per chat.
+    async fn import_muted_sellers(
+        connection: &mut SqliteConnection,
+        muted_sellers: &[MutedSeller],
+        report: &mut ImportReport,
+    ) -> Result {
+        let mut muted_seller_ids: HashMap<i64, HashSet<i64>> = HashMap::new();
+        for muted_seller in muted_sellers {
+            let seller_ids = match muted_seller_ids.entry(muted_seller.chat_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry
+                    .insert(MutedSellers(&mut *connection).fetch_ids(muted_seller.chat_id).await?),
+            };
+            if seller_ids.insert(muted_seller.seller_id) {
+                info!(
+                    "🔇 Muting seller",
+                    chat_id = muted_seller.chat_id,
+                    seller_name = &muted_seller.seller_name,
+                );
+                MutedSellers(&mut *connection).upsert(muted_seller).await?;
+                report.n_muted_sellers_added += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn export_and_import_ok() -> Result {
+        let source = Db::try_new(Path::new(":memory:")).await?;
+        {
+            let mut connection = source.connection().await?;
+            let search_query = SearchQuery::from("unifi");
+            SearchQueries(&mut connection).upsert(&search_query).await?;
+            let mut subscription = Subscription::new(search_query.hash, 42);
+            subscription.filters.exclude_business = true;
+            Subscriptions(&mut connection).upsert(subscription).await?;
+            Subscriptions(&mut connection)
+                .set_template(search_query.hash, 42, Some("full"))
+                .await?;
+            Chats(&mut connection).upsert_language_code(42, "en").await?;
+            Chats(&mut connection).set_language(42, Some("nl")).await?;
+            Chats(&mut connection).set_template(42, Some("compact")).await?;
+            MutedSellers(&mut connection)
+                .upsert(&MutedSeller {
+                    chat_id: 42,
+                    seller_id: 49_797_653,
+                    seller_name: "FlimDeal".to_string(),
+                })
+                .await?;
+        }
+        let export: Export =
+            serde_json::from_str(&serde_json::to_string(&source.export(None).await?)?)?;
+        assert_eq!(export.subscriptions.len(), 1);
+        assert!(export.subscriptions[0].filters.exclude_business);
+        assert_eq!(export.subscriptions[0].template.as_deref(), Some("full"));
+        assert_eq!(
+            export.chat_settings,
+            [ChatSettings {
+                chat_id: 42,
+                language: Some("nl".to_string()),
+                template: Some("compact".to_string()),
+            }],
+        );
+
+        assert_eq!(serde_norway::from_str::<Export>(&serde_norway::to_string(&export)?)?, export);
+
+        let target = Db::try_new(Path::new(":memory:")).await?;
+
+        let report = target.import(&export, true).await?;
+        assert_eq!(report.n_subscriptions_added, 1);
+        assert_eq!(report.n_chat_settings_updated, 1);
+        assert_eq!(
+            target.export(None).await?,
+            Export::default(),
+            "dry run must not change anything"
+        );
+
+        target.import(&export, false).await?;
+        assert_eq!(target.export(Some(42)).await?, export);
+
+        let report = target.import(&export, false).await?;
+        assert_eq!(
+            report,
+            ImportReport { n_subscriptions_unchanged: 1, ..Default::default() },
+            "importing must be idempotent",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn into_chat_ok() {
+        let export = Export {
+            subscriptions: vec![ExportedSubscription {
+                chat_id: 42,
+                query: "unifi".to_string(),
+                is_paused: false,
+                resume_at: None,
+                filters: SubscriptionFilters::default(),
+                template: None,
+            }],
+            muted_sellers: vec![MutedSeller {
+                chat_id: 42,
+                seller_id: 1,
+                seller_name: "FlimDeal".to_string(),
+            }],
+            chat_settings: vec![
+                ChatSettings { chat_id: 42, language: None, template: None },
+                ChatSettings { chat_id: 43, language: None, template: None },
+            ],
+        }
+        .into_chat(7);
+        assert_eq!(export.subscriptions[0].chat_id, 7);
+        assert_eq!(export.muted_sellers[0].chat_id, 7);
+        assert_eq!(
+            export.chat_settings,
+            [ChatSettings { chat_id: 7, language: None, template: None }]
+        );
+    }
+}
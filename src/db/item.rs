@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use sqlx::SqliteConnection;
 
-use crate::prelude::*;
+use crate::{db::Timed, prelude::*};
 
 #[derive(Copy, Clone)]
 pub struct Item<'a> {
@@ -28,9 +28,31 @@ impl Items<'_> {
             .bind(item.id)
             .bind(item.updated_at)
             .execute(&mut *self.0)
+            .timed("item.upsert")
             .await
             .with_context(|| format!("failed to upsert the item #{}", item.id))?;
 
         Ok(())
     }
+
+    /// Delete the items which have not been seen since the specified timestamp.
+    ///
+    /// Item details are deleted by the cascade.
+    #[instrument(
+        name = "💾 Deleting stale items…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(updated_before = ?updated_before),
+    )]
+    pub async fn delete_stale(&mut self, updated_before: DateTime<Utc>) -> Result<u64> {
+        // language=sql
+        const QUERY: &str = "DELETE FROM items WHERE updated_at < ?1";
+        let result = sqlx::query(QUERY)
+            .bind(updated_before)
+            .execute(&mut *self.0)
+            .timed("item.delete_stale")
+            .await
+            .context("failed to delete the stale items")?;
+        Ok(result.rows_affected())
+    }
 }
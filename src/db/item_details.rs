@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqliteConnection;
+
+use crate::{db::Timed, marketplace::item::Details, prelude::*};
+
+/// Cache of the item details.
+pub struct ItemDetails<'a>(pub &'a mut SqliteConnection);
+
+impl ItemDetails<'_> {
+    #[instrument(
+        name = "💾 Upserting item details…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(item_id = item_id),
+    )]
+    pub async fn upsert(
+        &mut self,
+        item_id: &str,
+        details: &Details,
+        fetched_at: DateTime<Utc>,
+    ) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO item_details (item_id, details, fetched_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET details = ?2, fetched_at = ?3
+        ";
+
+        let details = serde_json::to_string(details).context("failed to serialize the details")?;
+        sqlx::query(QUERY)
+            .bind(item_id)
+            .bind(details)
+            .bind(fetched_at)
+            .execute(&mut *self.0)
+            .timed("item_details.upsert")
+            .await
+            .with_context(|| format!("failed to upsert details of the item #{item_id}"))?;
+
+        Ok(())
+    }
+
+    #[instrument(
+        name = "💾 Fetching item details…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(item_id = item_id),
+    )]
+    pub async fn fetch(&mut self, item_id: &str) -> Result<Option<Details>> {
+        // language=sql
+        const QUERY: &str = "SELECT details FROM item_details WHERE item_id = ?1";
+
+        let details: Option<String> = sqlx::query_scalar(QUERY)
+            .bind(item_id)
+            .fetch_optional(&mut *self.0)
+            .timed("item_details.fetch")
+            .await
+            .with_context(|| format!("failed to fetch details of the item #{item_id}"))?;
+        details
+            .map(|details| serde_json::from_str(&details))
+            .transpose()
+            .context("failed to deserialize the details")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::db::{Db, Item, Items};
+
+    #[tokio::test]
+    async fn upsert_and_fetch_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        Items(&mut connection).upsert(Item { id: "m1", updated_at: Utc::now() }).await?;
+
+        let mut item_details = ItemDetails(&mut connection);
+        assert_eq!(item_details.fetch("m1").await?, None);
+
+        let details = Details {
+            description: Some("Full description".to_string()),
+            n_views: Some(42),
+            ..Default::default()
+        };
+        item_details.upsert("m1", &details, Utc::now()).await?;
+        item_details.upsert("m1", &details, Utc::now()).await?; // verify conflicts
+        assert_eq!(item_details.fetch("m1").await?, Some(details));
+
+        Ok(())
+    }
+}
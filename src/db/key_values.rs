@@ -1,7 +1,7 @@
 use prost::Message;
 use sqlx::SqliteConnection;
 
-use crate::prelude::*;
+use crate::{db::Timed, prelude::*};
 
 pub struct KeyValues<'a>(pub &'a mut SqliteConnection);
 
@@ -23,6 +23,7 @@ impl KeyValues<'_> {
             .bind(M::KEY)
             .bind(value.encode_to_vec())
             .execute(&mut *self.0)
+            .timed("key_values.upsert")
             .await
             .context("failed to upsert the subscription")?;
 
@@ -42,6 +43,7 @@ impl KeyValues<'_> {
         let value: Option<Vec<u8>> = sqlx::query_scalar(QUERY)
             .bind(V::KEY)
             .fetch_optional(&mut *self.0)
+            .timed("key_values.fetch")
             .await
             .with_context(|| format!("failed to fetch the value for key `{}`", V::KEY))?;
         value.map_or_else(
@@ -51,6 +53,7 @@ impl KeyValues<'_> {
     }
 }
 
+/// Message which is persisted under the fixed key, like a scheduler state.
 pub trait KeyedMessage: Message {
     const KEY: &'static str;
 }
@@ -75,7 +78,7 @@ mod tests {
         }
 
         let db = Db::try_new(Path::new(":memory:")).await?;
-        let mut connection = db.connection().await;
+        let mut connection = db.connection().await?;
         let mut key_values = KeyValues(&mut connection);
 
         assert!(key_values.fetch::<Value>().await?.is_none());
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+
+use crate::{db::Timed, prelude::*};
+
+/// Seller, whose items the chat does not want to be notified about.
+#[derive(Clone, Debug, Eq, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct MutedSeller {
+    pub chat_id: i64,
+    pub seller_id: i64,
+    pub seller_name: String,
+}
+
+pub struct MutedSellers<'a>(pub &'a mut SqliteConnection);
+
+impl MutedSellers<'_> {
+    #[instrument(
+        name = "💾 Upserting muted seller…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = muted_seller.chat_id, seller_id = muted_seller.seller_id),
+    )]
+    pub async fn upsert(&mut self, muted_seller: &MutedSeller) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO muted_sellers (chat_id, seller_id, seller_name) VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET seller_name = ?3
+        ";
+        sqlx::query(QUERY)
+            .bind(muted_seller.chat_id)
+            .bind(muted_seller.seller_id)
+            .bind(&muted_seller.seller_name)
+            .execute(&mut *self.0)
+            .timed("muted_seller.upsert")
+            .await
+            .with_context(|| format!("failed to mute the seller #{}", muted_seller.seller_id))?;
+        Ok(())
+    }
+
+    #[instrument(
+        name = "💾 Deleting muted seller…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id, seller_id = seller_id),
+    )]
+    pub async fn delete(&mut self, chat_id: i64, seller_id: i64) -> Result {
+        // language=sql
+        const QUERY: &str = "DELETE FROM muted_sellers WHERE chat_id = ?1 AND seller_id = ?2";
+        sqlx::query(QUERY)
+            .bind(chat_id)
+            .bind(seller_id)
+            .execute(&mut *self.0)
+            .timed("muted_seller.delete")
+            .await
+            .with_context(|| format!("failed to unmute the seller #{seller_id}"))?;
+        Ok(())
+    }
+
+    /// Fetch the chat's muted sellers ordered by name.
+    #[instrument(
+        name = "💾 Fetching muted sellers…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_all(&mut self, chat_id: i64) -> Result<Vec<MutedSeller>> {
+        // language=sql
+        const QUERY: &str = "SELECT * FROM muted_sellers WHERE chat_id = ?1 ORDER BY seller_name";
+        sqlx::query_as(QUERY)
+            .bind(chat_id)
+            .fetch_all(&mut *self.0)
+            .timed("muted_seller.fetch_all")
+            .await
+            .with_context(|| format!("failed to fetch muted sellers of chat #{chat_id}"))
+    }
+
+    /// Fetch the muted sellers of all the chats.
+    #[instrument(name = "💾 Fetching all muted sellers…", level = Level::DEBUG, skip_all)]
+    pub async fn fetch_all_chats(&mut self) -> Result<Vec<MutedSeller>> {
+        // language=sql
+        const QUERY: &str = "SELECT * FROM muted_sellers ORDER BY chat_id, seller_name";
+        sqlx::query_as(QUERY)
+            .fetch_all(&mut *self.0)
+            .timed("muted_seller.fetch_all_chats")
+            .await
+            .context("failed to fetch all muted sellers")
+    }
+
+    /// Fetch IDs of the chat's muted sellers.
+    #[instrument(
+        name = "💾 Fetching muted seller IDs…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_ids(&mut self, chat_id: i64) -> Result<HashSet<i64>> {
+        // language=sql
+        const QUERY: &str = "SELECT seller_id FROM muted_sellers WHERE chat_id = ?1";
+        let ids: Vec<i64> = sqlx::query_scalar(QUERY)
+            .bind(chat_id)
+            .fetch_all(&mut *self.0)
+            .timed("muted_seller.fetch_ids")
+            .await
+            .with_context(|| format!("failed to fetch muted seller IDs of chat #{chat_id}"))?;
+        Ok(ids.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::db::Db;
+
+    #[tokio::test]
+    async fn mute_and_unmute_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let mut muted_sellers = MutedSellers(&mut connection);
+
+        let muted_seller =
+            MutedSeller { chat_id: 42, seller_id: 49_797_653, seller_name: "FlimDeal".to_string() };
+        muted_sellers.upsert(&muted_seller).await?;
+        muted_sellers.upsert(&muted_seller).await?; // verify conflicts
+
+        assert_eq!(muted_sellers.fetch_all(42).await?, [muted_seller]);
+        assert!(muted_sellers.fetch_ids(42).await?.contains(&49_797_653));
+        assert!(muted_sellers.fetch_ids(43).await?.is_empty(), "muting is per chat");
+
+        muted_sellers.delete(42, 49_797_653).await?;
+        assert!(muted_sellers.fetch_ids(42).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
+use chrono::{DateTime, Utc};
 use sqlx::SqliteConnection;
 
-use crate::prelude::*;
+use crate::{db::Timed, prelude::*};
 
 /// Proof that the chat has received the item notification.
 #[derive(Eq, PartialEq)]
@@ -26,6 +27,7 @@ impl Notifications<'_> {
         .bind(&notification.item_id)
         .bind(notification.chat_id)
         .execute(&mut *self.0)
+        .timed("notification.upsert")
         .await
         .context("failed to upsert the notification")?;
 
@@ -46,17 +48,38 @@ impl Notifications<'_> {
             .bind(&notification.item_id)
             .bind(notification.chat_id)
             .fetch_one(&mut *self.0)
+            .timed("notification.exists")
             .await
             .context("failed to check for existence of notification")
     }
+
+    /// Delete the notifications of the items which have not been seen since the specified timestamp.
+    #[instrument(
+        name = "💾 Deleting notifications of stale items…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(updated_before = ?updated_before),
+    )]
+    pub async fn delete_of_stale_items(&mut self, updated_before: DateTime<Utc>) -> Result<u64> {
+        // language=sql
+        const QUERY: &str = "
+            DELETE FROM notifications
+            WHERE item_id IN (SELECT id FROM items WHERE updated_at < ?1)
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(updated_before)
+            .execute(&mut *self.0)
+            .timed("notification.delete_of_stale_items")
+            .await
+            .context("failed to delete the notifications of stale items")?;
+        Ok(result.rows_affected())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
-    use chrono::Utc;
-
     use super::*;
     use crate::db::{
         Db,
@@ -66,7 +89,7 @@ mod tests {
     #[tokio::test]
     async fn test_exists_ok() -> Result {
         let db = Db::try_new(Path::new(":memory:")).await?;
-        let mut connection = db.connection().await;
+        let mut connection = db.connection().await?;
 
         let item = Item { id: "m42", updated_at: Utc::now() };
         Items(&mut connection).upsert(item).await?;
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
 
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use sqlx::{FromRow, SqliteConnection};
 
-use crate::{marketplace::NormalisedQuery, prelude::*};
+use crate::{db::Timed, marketplace::NormalisedQuery, prelude::*};
 
 /// User's search query.
 #[derive(Clone, Debug, PartialEq, Eq, FromRow)]
@@ -45,11 +46,16 @@ impl SearchQueries<'_> {
     )]
     pub async fn upsert(&mut self, query: &SearchQuery) -> Result {
         // language=sql
-        const QUERY: &str = "INSERT INTO search_queries (hash, text) VALUES (?1, ?2) ON CONFLICT DO UPDATE SET text = ?2";
+        const QUERY: &str = "
+            INSERT INTO search_queries (hash, text, last_used_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET text = ?2, last_used_at = ?3
+        ";
         sqlx::query(QUERY)
             .bind(query.hash)
             .bind(&query.text)
+            .bind(Utc::now())
             .execute(&mut *self.0)
+            .timed("search_query.upsert")
             .await
             .with_context(|| format!("failed to upsert the search query `{}`", query.text))?;
         Ok(())
@@ -67,22 +73,51 @@ impl SearchQueries<'_> {
         sqlx::query_scalar(QUERY)
             .bind(hash)
             .fetch_one(&mut *self.0)
+            .timed("search_query.fetch_text")
             .await
             .with_context(|| format!("failed to fetch the query text for hash `{hash}`"))
     }
+
+    /// Delete the search queries which nobody is subscribed to, and which have not been used since `used_before`.
+    ///
+    /// Recent search results still link to their queries, even when nobody is subscribed.
+    #[instrument(
+        name = "💾 Deleting orphan search queries…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(used_before = ?used_before),
+    )]
+    pub async fn delete_orphans(&mut self, used_before: DateTime<Utc>) -> Result<u64> {
+        // language=sql
+        const QUERY: &str = "
+            DELETE FROM search_queries
+            WHERE
+                hash NOT IN (SELECT query_hash FROM subscriptions)
+                AND (last_used_at IS NULL OR last_used_at < ?1)
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(used_before)
+            .execute(&mut *self.0)
+            .timed("search_query.delete_orphans")
+            .await
+            .context("failed to delete the orphan search queries")?;
+        Ok(result.rows_affected())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
+    use chrono::TimeDelta;
+
     use super::*;
     use crate::db::Db;
 
     #[tokio::test]
     async fn search_query_ok() -> Result {
         let db = Db::try_new(Path::new(":memory:")).await?;
-        let mut connection = db.connection().await;
+        let mut connection = db.connection().await?;
         let mut search_queries = SearchQueries(&mut connection);
 
         let query = SearchQuery::from("test");
@@ -93,4 +128,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn delete_orphans_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let mut search_queries = SearchQueries(&mut connection);
+        let query = SearchQuery::from("test");
+        search_queries.upsert(&query).await?;
+
+        let now = Utc::now();
+        assert_eq!(
+            search_queries.delete_orphans(now - TimeDelta::hours(1)).await?,
+            0,
+            "recently used query must be kept",
+        );
+        assert_eq!(search_queries.delete_orphans(now + TimeDelta::seconds(1)).await?, 1);
+        assert!(search_queries.fetch_text(query.hash).await.is_err());
+
+        Ok(())
+    }
 }
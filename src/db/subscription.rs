@@ -1,16 +1,110 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqliteConnection};
 
-use crate::prelude::*;
+use crate::{
+    db::Timed,
+    marketplace::item::{Condition, ConditionClass, DeliveryMethod, Item},
+    prelude::*,
+};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, FromRow)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromRow)]
 pub struct Subscription {
     pub query_hash: i64,
     pub chat_id: i64,
+
+    /// Paused subscriptions are skipped by the search bot.
+    pub is_paused: bool,
+
+    /// Timestamp at which the paused subscription gets automatically resumed.
+    ///
+    /// [`None`] means that the subscription is paused indefinitely.
+    pub resume_at: Option<DateTime<Utc>>,
+
+    #[sqlx(flatten)]
+    pub filters: SubscriptionFilters,
+}
+
+impl Subscription {
+    pub fn new(query_hash: i64, chat_id: i64) -> Self {
+        Self { query_hash, chat_id, ..Default::default() }
+    }
+}
+
+/// Item filters applied before notifying the subscriber.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromRow, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionFilters {
+    pub exclude_business: bool,
+    pub exclude_auctions: bool,
+    pub exclude_promoted: bool,
+
+    /// Bitmask of [`ConditionClass`]'es to exclude.
+    pub excluded_conditions: i64,
+
+    /// Bitmask of [`DeliveryMethod`]'s to exclude.
+    pub excluded_deliveries: i64,
+}
+
+impl SubscriptionFilters {
+    /// Check whether the item passes the filters.
+    ///
+    /// Items with unknown condition or delivery pass the corresponding filters.
+    pub fn matches(self, item: &Item) -> bool {
+        let is_excluded = (self.exclude_business && item.seller.is_business)
+            || (self.exclude_auctions && item.is_auction)
+            || (self.exclude_promoted && item.is_promoted)
+            || item
+                .condition
+                .as_ref()
+                .and_then(Condition::class)
+                .is_some_and(|class| self.is_condition_excluded(class))
+            || item.delivery.as_ref().is_some_and(|delivery| {
+                // Exclude the item only when every way to receive it is excluded,
+                // and never exclude an unknown delivery:
+                DeliveryMethod::ALL.into_iter().all(|method| {
+                    delivery.supports(method).is_some_and(|is_supported| {
+                        !is_supported || self.is_delivery_excluded(method)
+                    })
+                })
+            });
+        !is_excluded
+    }
+
+    pub const fn is_condition_excluded(self, class: ConditionClass) -> bool {
+        self.excluded_conditions & class.bit() != 0
+    }
+
+    pub const fn set_condition_excluded(&mut self, class: ConditionClass, is_excluded: bool) {
+        if is_excluded {
+            self.excluded_conditions |= class.bit();
+        } else {
+            self.excluded_conditions &= !class.bit();
+        }
+    }
+
+    pub const fn is_delivery_excluded(self, method: DeliveryMethod) -> bool {
+        self.excluded_deliveries & method.bit() != 0
+    }
+
+    pub const fn set_delivery_excluded(&mut self, method: DeliveryMethod, is_excluded: bool) {
+        if is_excluded {
+            self.excluded_deliveries |= method.bit();
+        } else {
+            self.excluded_deliveries &= !method.bit();
+        }
+    }
 }
 
 pub struct Subscriptions<'a>(pub &'a mut SqliteConnection);
 
 impl Subscriptions<'_> {
+    /// Insert the subscription or update its state.
+    ///
+    /// Re-subscribing to a paused subscription, therefore, resumes it.
+    /// Filters of an existing subscription are preserved.
     #[instrument(
         name = "💾 Upserting subscription…",
         level = Level::DEBUG,
@@ -18,19 +112,85 @@ impl Subscriptions<'_> {
         fields(query_hash = subscription.query_hash, chat_id = subscription.chat_id),
     )]
     pub async fn upsert(&mut self, subscription: Subscription) -> Result {
-        sqlx::query(
-            // language=sql
-            "INSERT INTO subscriptions (query_hash, chat_id) VALUES (?1, ?2) ON CONFLICT DO NOTHING",
-        )
-        .bind(subscription.query_hash)
-        .bind(subscription.chat_id)
-        .execute(&mut *self.0)
-        .await
-        .context("failed to upsert the subscription")?;
+        // language=sql
+        const QUERY: &str = "
+            INSERT INTO subscriptions (
+                query_hash, chat_id, is_paused, resume_at,
+                exclude_business, exclude_auctions, exclude_promoted, excluded_conditions, excluded_deliveries
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT DO UPDATE SET is_paused = ?3, resume_at = ?4
+        ";
+        sqlx::query(QUERY)
+            .bind(subscription.query_hash)
+            .bind(subscription.chat_id)
+            .bind(subscription.is_paused)
+            .bind(subscription.resume_at)
+            .bind(subscription.filters.exclude_business)
+            .bind(subscription.filters.exclude_auctions)
+            .bind(subscription.filters.exclude_promoted)
+            .bind(subscription.filters.excluded_conditions)
+            .bind(subscription.filters.excluded_deliveries)
+            .execute(&mut *self.0)
+            .timed("subscription.upsert")
+            .await
+            .context("failed to upsert the subscription")?;
 
         Ok(())
     }
 
+    #[instrument(
+        name = "💾 Fetching subscription…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id),
+    )]
+    pub async fn fetch(&mut self, query_hash: i64, chat_id: i64) -> Result<Option<Subscription>> {
+        // language=sql
+        const QUERY: &str = "SELECT * FROM subscriptions WHERE query_hash = ?1 AND chat_id = ?2";
+        sqlx::query_as(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("subscription.fetch")
+            .await
+            .context("failed to fetch the subscription")
+    }
+
+    #[instrument(
+        name = "💾 Updating subscription filters…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id, filters = ?filters),
+    )]
+    pub async fn set_filters(
+        &mut self,
+        query_hash: i64,
+        chat_id: i64,
+        filters: SubscriptionFilters,
+    ) -> Result {
+        // language=sql
+        const QUERY: &str = "
+            UPDATE subscriptions
+            SET exclude_business = ?3, exclude_auctions = ?4, exclude_promoted = ?5,
+                excluded_conditions = ?6, excluded_deliveries = ?7
+            WHERE query_hash = ?1 AND chat_id = ?2
+        ";
+        sqlx::query(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .bind(filters.exclude_business)
+            .bind(filters.exclude_auctions)
+            .bind(filters.exclude_promoted)
+            .bind(filters.excluded_conditions)
+            .bind(filters.excluded_deliveries)
+            .execute(&mut *self.0)
+            .timed("subscription.set_filters")
+            .await
+            .context("failed to update the subscription filters")?;
+        Ok(())
+    }
+
     #[instrument(
         name = "💾 Deleting subscription…",,
         level = Level::DEBUG,
@@ -45,37 +205,415 @@ impl Subscriptions<'_> {
         .bind(subscription.query_hash)
         .bind(subscription.chat_id)
         .execute(&mut *self.0)
+        .timed("subscription.delete")
         .await
         .context("failed to delete the subscription")?;
 
         Ok(())
     }
+
+    /// Pause the existing subscription.
+    ///
+    /// # Returns
+    ///
+    /// Whether the subscription exists.
+    #[instrument(
+        name = "💾 Pausing subscription…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id, resume_at = ?resume_at),
+    )]
+    pub async fn pause(
+        &mut self,
+        query_hash: i64,
+        chat_id: i64,
+        resume_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        // language=sql
+        const QUERY: &str = "
+            UPDATE subscriptions SET is_paused = TRUE, resume_at = ?3
+            WHERE query_hash = ?1 AND chat_id = ?2
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .bind(resume_at)
+            .execute(&mut *self.0)
+            .timed("subscription.pause")
+            .await
+            .context("failed to pause the subscription")?;
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Resume the existing subscription.
+    ///
+    /// # Returns
+    ///
+    /// Whether the subscription exists.
+    #[instrument(
+        name = "💾 Resuming subscription…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id),
+    )]
+    pub async fn resume(&mut self, query_hash: i64, chat_id: i64) -> Result<bool> {
+        // language=sql
+        const QUERY: &str = "
+            UPDATE subscriptions SET is_paused = FALSE, resume_at = NULL
+            WHERE query_hash = ?1 AND chat_id = ?2
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .execute(&mut *self.0)
+            .timed("subscription.resume")
+            .await
+            .context("failed to resume the subscription")?;
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Choose the subscription's notification template, or reset it to the chat's one – if `None`.
+    ///
+    /// # Returns
+    ///
+    /// Whether the subscription exists.
+    #[instrument(
+        name = "💾 Setting subscription template…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id, template = template),
+    )]
+    pub async fn set_template(
+        &mut self,
+        query_hash: i64,
+        chat_id: i64,
+        template: Option<&str>,
+    ) -> Result<bool> {
+        // language=sql
+        const QUERY: &str =
+            "UPDATE subscriptions SET template = ?3 WHERE query_hash = ?1 AND chat_id = ?2";
+        let result = sqlx::query(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .bind(template)
+            .execute(&mut *self.0)
+            .timed("subscription.set_template")
+            .await
+            .context("failed to set the subscription template")?;
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Fetch the subscription's own notification template.
+    #[instrument(
+        name = "💾 Fetching subscription template…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(query_hash = query_hash, chat_id = chat_id),
+    )]
+    pub async fn fetch_template(
+        &mut self,
+        query_hash: i64,
+        chat_id: i64,
+    ) -> Result<Option<String>> {
+        // language=sql
+        const QUERY: &str =
+            "SELECT template FROM subscriptions WHERE query_hash = ?1 AND chat_id = ?2";
+        let template: Option<Option<String>> = sqlx::query_scalar(QUERY)
+            .bind(query_hash)
+            .bind(chat_id)
+            .fetch_optional(&mut *self.0)
+            .timed("subscription.fetch_template")
+            .await
+            .context("failed to fetch the subscription template")?;
+        Ok(template.flatten())
+    }
+
+    /// Fetch the own notification templates of the chat's subscriptions by their query hashes.
+    #[instrument(
+        name = "💾 Fetching subscription templates…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn fetch_templates(&mut self, chat_id: i64) -> Result<HashMap<i64, String>> {
+        // language=sql
+        const QUERY: &str = "
+            SELECT query_hash, template FROM subscriptions
+            WHERE chat_id = ?1 AND template IS NOT NULL
+        ";
+        let templates: Vec<(i64, String)> = sqlx::query_as(QUERY)
+            .bind(chat_id)
+            .fetch_all(&mut *self.0)
+            .timed("subscription.fetch_templates")
+            .await
+            .with_context(|| {
+                format!("failed to fetch the subscription templates of chat #{chat_id}")
+            })?;
+        Ok(templates.into_iter().collect())
+    }
+
+    /// Pause all subscriptions of the chat.
+    ///
+    /// # Returns
+    ///
+    /// Number of paused subscriptions.
+    #[instrument(
+        name = "💾 Pausing all subscriptions…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id, resume_at = ?resume_at),
+    )]
+    pub async fn pause_all(
+        &mut self,
+        chat_id: i64,
+        resume_at: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        // language=sql
+        const QUERY: &str =
+            "UPDATE subscriptions SET is_paused = TRUE, resume_at = ?2 WHERE chat_id = ?1";
+        let result = sqlx::query(QUERY)
+            .bind(chat_id)
+            .bind(resume_at)
+            .execute(&mut *self.0)
+            .timed("subscription.pause_all")
+            .await
+            .with_context(|| format!("failed to pause subscriptions of chat #{chat_id}"))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Resume all paused subscriptions of the chat.
+    ///
+    /// # Returns
+    ///
+    /// Number of resumed subscriptions.
+    #[instrument(
+        name = "💾 Resuming all subscriptions…",
+        level = Level::DEBUG,
+        skip_all,
+        fields(chat_id = chat_id),
+    )]
+    pub async fn resume_all(&mut self, chat_id: i64) -> Result<u64> {
+        // language=sql
+        const QUERY: &str = "
+            UPDATE subscriptions SET is_paused = FALSE, resume_at = NULL
+            WHERE chat_id = ?1 AND is_paused
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(chat_id)
+            .execute(&mut *self.0)
+            .timed("subscription.resume_all")
+            .await
+            .with_context(|| format!("failed to resume subscriptions of chat #{chat_id}"))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Resume the timed pauses which have expired by `now`.
+    ///
+    /// # Returns
+    ///
+    /// Number of resumed subscriptions.
+    #[instrument(name = "💾 Resuming expired pauses…", level = Level::DEBUG, skip_all)]
+    pub async fn resume_expired(&mut self, now: DateTime<Utc>) -> Result<u64> {
+        // language=sql
+        const QUERY: &str = "
+            UPDATE subscriptions SET is_paused = FALSE, resume_at = NULL
+            WHERE is_paused AND resume_at IS NOT NULL AND resume_at <= ?1
+        ";
+        let result = sqlx::query(QUERY)
+            .bind(now)
+            .execute(&mut *self.0)
+            .timed("subscription.resume_expired")
+            .await
+            .context("failed to resume the expired pauses")?;
+        Ok(result.rows_affected())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
+    use chrono::TimeDelta;
+    use url::Url;
+
     use super::*;
-    use crate::db::{
-        Db,
-        search_query::{SearchQueries, SearchQuery},
+    use crate::{
+        db::{
+            Db,
+            search_query::{SearchQueries, SearchQuery},
+        },
+        marketplace::item::{Condition, Delivery, Price, Seller, Used},
     };
 
     #[tokio::test]
     async fn upsert_subscription_ok() -> Result {
         let db = Db::try_new(Path::new(":memory:")).await?;
-        let mut connection = db.connection().await;
+        let mut connection = db.connection().await?;
 
         let query = SearchQuery::from("test");
         SearchQueries(&mut connection).upsert(&query).await?;
 
         let mut subscriptions = Subscriptions(&mut connection);
-        let subscription = Subscription { query_hash: query.hash, chat_id: 42 };
+        let subscription = Subscription::new(query.hash, 42);
 
         subscriptions.upsert(subscription).await?;
         subscriptions.upsert(subscription).await?; // verify conflicts
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn pause_and_resume_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let query = SearchQuery::from("test");
+        let subscription = Subscription::new(query.hash, 42);
+        {
+            let mut connection = db.connection().await?;
+            SearchQueries(&mut connection).upsert(&query).await?;
+            let mut subscriptions = Subscriptions(&mut connection);
+            subscriptions.upsert(subscription).await?;
+            assert!(subscriptions.pause(query.hash, 42, None).await?);
+            assert!(!subscriptions.pause(query.hash, 43, None).await?, "unknown subscription");
+        }
+        assert!(db.first_subscription().await?.is_none(), "the subscription is paused");
+
+        Subscriptions(&mut *db.connection().await?).resume(query.hash, 42).await?;
+        assert_eq!(db.first_subscription().await?.unwrap().0, subscription);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fetch_templates_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+        let (query, other_query) = (SearchQuery::from("test"), SearchQuery::from("other"));
+        SearchQueries(&mut connection).upsert(&query).await?;
+        SearchQueries(&mut connection).upsert(&other_query).await?;
+
+        let mut subscriptions = Subscriptions(&mut connection);
+        subscriptions.upsert(Subscription::new(query.hash, 42)).await?;
+        subscriptions.upsert(Subscription::new(other_query.hash, 42)).await?;
+        subscriptions.upsert(Subscription::new(query.hash, 43)).await?;
+        subscriptions.set_template(query.hash, 42, Some("full")).await?;
+        subscriptions.set_template(query.hash, 43, Some("compact")).await?;
+
+        assert_eq!(
+            subscriptions.fetch_templates(42).await?,
+            HashMap::from([(query.hash, "full".to_string())]),
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn set_filters_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+
+        let query = SearchQuery::from("test");
+        SearchQueries(&mut connection).upsert(&query).await?;
+
+        let mut subscriptions = Subscriptions(&mut connection);
+        subscriptions.upsert(Subscription::new(query.hash, 42)).await?;
+        let mut filters = SubscriptionFilters { exclude_business: true, ..Default::default() };
+        filters.set_condition_excluded(ConditionClass::NotWorking, true);
+        filters.set_delivery_excluded(DeliveryMethod::Collection, true);
+        subscriptions.set_filters(query.hash, 42, filters).await?;
+        subscriptions.upsert(Subscription::new(query.hash, 42)).await?; // the filters are kept
+
+        let subscription = subscriptions.fetch(query.hash, 42).await?.unwrap();
+        assert_eq!(subscription.filters, filters);
+        assert!(subscriptions.fetch(query.hash, 43).await?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn filters_match_ok() -> Result {
+        let item = Item::builder()
+            .id("m42".to_string())
+            .url(Url::parse("https://www.marktplaats.nl/v/m42")?)
+            .title("Tado".to_string())
+            .price(Price::OnRequest)
+            .seller(
+                Seller::builder()
+                    .id(42)
+                    .username("Pavel".to_string())
+                    .profile_url(Url::parse("https://www.marktplaats.nl/u/pavel/42/")?)
+                    .build(),
+            )
+            .condition(Condition::Used(Used::NotFullyFunctional))
+            .delivery(Delivery::Both)
+            .build();
+
+        let mut filters = SubscriptionFilters::default();
+        assert!(filters.matches(&item));
+
+        filters.set_delivery_excluded(DeliveryMethod::Collection, true);
+        assert!(filters.matches(&item), "shipping is still possible");
+
+        filters.set_delivery_excluded(DeliveryMethod::Shipping, true);
+        assert!(!filters.matches(&item), "neither collection nor shipping is accepted");
+
+        filters.excluded_deliveries = 0;
+        filters.set_condition_excluded(ConditionClass::NotWorking, true);
+        assert!(!filters.matches(&item));
+
+        filters.set_condition_excluded(ConditionClass::NotWorking, false);
+        assert!(filters.matches(&item));
+
+        Ok(())
+    }
+
+    #[test]
+    fn filters_match_unknown_ok() -> Result {
+        let item = Item::builder()
+            .id("m42".to_string())
+            .url(Url::parse("https://www.marktplaats.nl/v/m42")?)
+            .title("Tado".to_string())
+            .price(Price::Unknown("SWAP_SHOP".to_string()))
+            .seller(
+                Seller::builder()
+                    .id(42)
+                    .username("Pavel".to_string())
+                    .profile_url(Url::parse("https://www.marktplaats.nl/u/pavel/42/")?)
+                    .build(),
+            )
+            .condition(Condition::Unknown("Als een ruïne".to_string()))
+            .delivery(Delivery::Unknown("Per drone".to_string()))
+            .build();
+
+        let mut filters = SubscriptionFilters::default();
+        for class in ConditionClass::ALL {
+            filters.set_condition_excluded(class, true);
+        }
+        for method in DeliveryMethod::ALL {
+            filters.set_delivery_excluded(method, true);
+        }
+        assert!(filters.matches(&item), "unknown values are never filtered out");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resume_expired_ok() -> Result {
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let mut connection = db.connection().await?;
+
+        let query = SearchQuery::from("test");
+        SearchQueries(&mut connection).upsert(&query).await?;
+
+        let now = Utc::now();
+        let mut subscriptions = Subscriptions(&mut connection);
+        subscriptions.upsert(Subscription::new(query.hash, 42)).await?;
+        subscriptions.upsert(Subscription::new(query.hash, 43)).await?;
+        assert_eq!(subscriptions.pause_all(42, Some(now + TimeDelta::hours(1))).await?, 1);
+        assert_eq!(subscriptions.pause_all(43, None).await?, 1);
+
+        assert_eq!(subscriptions.resume_expired(now).await?, 0, "the pause has not expired yet");
+        assert_eq!(subscriptions.resume_expired(now + TimeDelta::hours(2)).await?, 1);
+        assert_eq!(subscriptions.resume_all(43).await?, 1);
+
+        Ok(())
+    }
 }
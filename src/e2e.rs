@@ -0,0 +1,384 @@
+//! End-to-end scenarios: the bots run against the fake Marktplaats and Bot API servers
+//! and the in-memory database.
+
+use std::{collections::HashSet, path::Path, time::Duration};
+
+use serde_json::json;
+use url::Url;
+
+use crate::{
+    client::{self, ClientSettings},
+    db::Db,
+    heartbeat::{Heartbeat, HeartbeatKind},
+    marketplace::{
+        Marketplaces,
+        Marktplaats,
+        SearchBot,
+        fake_marktplaats::{FakeMarktplaats, listing},
+    },
+    prelude::*,
+    status::Status,
+    telegram::{
+        TelegramBot,
+        commands::{CommandPayload, SubscriptionAction},
+        fake_api::FakeBotApi,
+    },
+};
+
+const CHAT_ID: i64 = 42;
+const STRANGER_CHAT_ID: i64 = 13;
+const GROUP_CHAT_ID: i64 = -100;
+
+/// The bots wired to the fake servers, driven one iteration at a time.
+struct Harness {
+    telegram_api: FakeBotApi,
+    marktplaats_api: FakeMarktplaats,
+    telegram_bot: TelegramBot,
+    search_bot: SearchBot,
+
+    /// Next Telegram update offset.
+    offset: u64,
+}
+
+impl Harness {
+    async fn start() -> Result<Self> {
+        let telegram_api = FakeBotApi::start().await?;
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        let db = Db::try_new(Path::new(":memory:")).await?;
+        let telegram = telegram_api.telegram()?;
+        let command_builder = telegram.command_builder().await?;
+        let status = Status::default();
+        let heartbeat = Heartbeat::new(
+            client::try_new(&ClientSettings::default())?,
+            HeartbeatKind::Generic,
+            None,
+        );
+        let marktplaats = Marktplaats::builder()
+            .client(marktplaats_api.client()?)
+            .search_limit(30)
+            .heartbeat(heartbeat.clone())
+            .status(status.clone())
+            .search_in_title_and_description(false)
+            .fetch_details(false)
+            .build();
+        let marketplaces = Marketplaces { marktplaats };
+        let telegram_bot = TelegramBot::builder()
+            .telegram(telegram.clone())
+            .command_builder(command_builder.clone())
+            .db(db.clone())
+            .marketplaces(marketplaces.clone())
+            .heartbeat(heartbeat)
+            .status(status.clone())
+            .authorized_chat_ids(HashSet::from([CHAT_ID, GROUP_CHAT_ID]))
+            .poll_timeout_secs(0)
+            .try_init()
+            .await?;
+        let search_bot = SearchBot::builder()
+            .db(db)
+            .command_builder(command_builder)
+            .search_interval(Duration::ZERO)
+            .telegram(telegram)
+            .marketplaces(marketplaces)
+            .status(status)
+            .build();
+        Ok(Self { telegram_api, marktplaats_api, telegram_bot, search_bot, offset: 0 })
+    }
+
+    /// Deliver the text message to the Telegram bot and let it handle the update.
+    async fn receive(&mut self, chat_id: i64, text: &str) {
+        self.receive_in(chat_id, None, text).await;
+    }
+
+    /// Deliver the text message from the user with the specified Telegram language code.
+    async fn receive_in(&mut self, chat_id: i64, language_code: Option<&str>, text: &str) {
+        self.telegram_api.queue_message(chat_id, language_code, text);
+        self.offset = self.telegram_bot.handle_updates(self.offset).await;
+    }
+
+    /// Search for the query and follow the subscribe link in the reply.
+    async fn subscribe(&mut self, chat_id: i64, query: &str) -> Result {
+        let n_sent = self.sent_to(chat_id).len();
+        self.receive(chat_id, query).await;
+        let reply = self.sent_to(chat_id).into_iter().nth(n_sent).context("no search reply")?;
+        let payload = start_payloads(&reply).into_iter().next().context("no subscribe link")?;
+        self.receive(chat_id, &format!("/start {payload}")).await;
+        Ok(())
+    }
+
+    /// Deliver the document to the Telegram bot and let it handle the update.
+    async fn receive_document(&mut self, chat_id: i64, file_id: &str, content: &[u8]) {
+        self.telegram_api.queue_document(chat_id, file_id, content);
+        self.offset = self.telegram_bot.handle_updates(self.offset).await;
+    }
+
+    /// Let the search bot handle the first subscription.
+    async fn crawl(&self) -> Result {
+        self.search_bot.advance_and_handle(None).await?;
+        Ok(())
+    }
+
+    /// Texts of all the messages sent to the chat.
+    fn sent_to(&self, chat_id: i64) -> Vec<String> {
+        self.telegram_api
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call.method.as_str(), "sendMessage" | "sendPhoto"))
+            .filter(|call| call.body["chat_id"] == chat_id)
+            .map(|call| {
+                let text = if call.body["text"].is_null() {
+                    &call.body["caption"]
+                } else {
+                    &call.body["text"]
+                };
+                text.as_str().unwrap_or_default().to_string()
+            })
+            .inspect(|text| assert!(!text.contains("internal error"), "{text}"))
+            .collect()
+    }
+}
+
+/// Extract the `/start` payloads from the links in the message.
+fn start_payloads(text: &str) -> Vec<String> {
+    text.split("href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .filter_map(|href| Url::parse(&href.replace("&amp;", "&")).ok())
+        .filter_map(|url| {
+            url.query_pairs().find(|(key, _)| key == "start").map(|(_, value)| value.into_owned())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn subscribe_then_notify_once_ok() -> Result {
+    let mut harness = Harness::start().await?;
+
+    // Nothing is found yet, but the bot offers to subscribe anyway:
+    harness.receive(CHAT_ID, "fiets").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].contains("There are no items"), "{}", sent[0]);
+    let payload = start_payloads(&sent[0]).into_iter().next().context("no subscribe link")?;
+
+    harness.receive(CHAT_ID, &format!("/start {payload}")).await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert_eq!(sent.len(), 2);
+    assert!(sent[1].contains("You are now subscribed"), "{}", sent[1]);
+
+    harness.crawl().await?;
+    assert_eq!(harness.sent_to(CHAT_ID).len(), 2, "nothing to notify about yet");
+
+    // The new listing appears:
+    harness.marktplaats_api.set_listings(vec![listing("m1", "Gazelle fiets")]);
+    harness.crawl().await?;
+    let sent = harness.sent_to(CHAT_ID);
+    assert_eq!(sent.len(), 3);
+    assert!(sent[2].contains("Gazelle fiets"), "{}", sent[2]);
+
+    // And it is not notified about again:
+    harness.crawl().await?;
+    assert_eq!(harness.sent_to(CHAT_ID).len(), 3);
+
+    let queries = harness.marktplaats_api.queries();
+    assert_eq!(queries.len(), 4);
+    assert!(queries.iter().all(|query| query.contains("query=fiets")), "{queries:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_listing_skipped_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.subscribe(CHAT_ID, "fiets").await?;
+
+    let mut unknown_price = listing("m2", "Batavus fiets");
+    unknown_price["priceInfo"] = json!({"priceType": "SWAP_SHOP"});
+    let mut broken_picture = listing("m3", "Sparta fiets");
+    broken_picture["pictures"] = json!([{"largeUrl": "not a URL"}]);
+    harness.marktplaats_api.set_listings(vec![
+        json!({"itemId": "m1"}),
+        unknown_price,
+        broken_picture,
+        listing("m4", "Gazelle fiets"),
+    ]);
+    harness.crawl().await?;
+
+    let sent = harness.sent_to(CHAT_ID);
+    assert_eq!(sent.len(), 4, "{sent:?}");
+    assert!(sent[2].contains("Batavus fiets") && sent[2].contains("SWAP_SHOP"), "{}", sent[2]);
+    assert!(sent[3].contains("Gazelle fiets"), "{}", sent[3]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn templates_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.subscribe(CHAT_ID, "fiets").await?;
+
+    harness.receive(CHAT_ID, "/template").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[2].contains("<code>photo</code>"), "{}", sent[2]);
+    harness.receive(CHAT_ID, "/template compact").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[3].contains("<strong>compact</strong>"), "{}", sent[3]);
+
+    // The chat's template applies:
+    harness.marktplaats_api.set_listings(vec![listing("m1", "Gazelle fiets")]);
+    harness.crawl().await?;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[4].contains("Gazelle fiets"), "{}", sent[4]);
+    assert!(!sent[4].contains("in good condition"), "{}", sent[4]);
+
+    // Until the subscription overrides it:
+    let settings_payload = start_payloads(&sent[1])
+        .into_iter()
+        .find(|payload| {
+            CommandPayload::from_base64(payload).is_ok_and(|payload| {
+                payload
+                    .subscription
+                    .is_some_and(|command| command.action == SubscriptionAction::Settings as i32)
+            })
+        })
+        .context("no settings link")?;
+    harness.receive(CHAT_ID, &format!("/start {settings_payload}")).await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[5].contains("<em>compact</em> (chat's default)"), "{}", sent[5]);
+    let full_payload = start_payloads(&sent[5])
+        .into_iter()
+        .find(|payload| {
+            CommandPayload::from_base64(payload).is_ok_and(|payload| {
+                payload.template.is_some_and(|command| command.template.as_deref() == Some("full"))
+            })
+        })
+        .context("no template link")?;
+    harness.receive(CHAT_ID, &format!("/start {full_payload}")).await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[6].contains("<em>full</em>\n"), "{}", sent[6]);
+
+    harness.marktplaats_api.set_listings(vec![listing("m2", "Batavus fiets")]);
+    harness.crawl().await?;
+    let sent = harness.sent_to(CHAT_ID);
+    assert_eq!(sent.len(), 8, "{sent:?}");
+    assert!(sent[7].contains("Batavus fiets in good condition"), "{}", sent[7]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unauthorized_chat_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.receive(STRANGER_CHAT_ID, "fiets").await;
+    let sent = harness.sent_to(STRANGER_CHAT_ID);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].contains("Thank you for your interest"), "{}", sent[0]);
+    assert!(harness.marktplaats_api.queries().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn bot_init_calls_ok() -> Result {
+    let harness = Harness::start().await?;
+    let methods: Vec<_> =
+        harness.telegram_api.calls().into_iter().map(|call| call.method).collect();
+    assert_eq!(
+        methods,
+        ["getMe", "setMyDescription", "setMyCommands", "setMyDescription", "setMyCommands"],
+    );
+    let calls = harness.telegram_api.calls_to("setMyCommands");
+    assert!(calls[0].get("language_code").is_none(), "{}", calls[0]);
+    assert!(calls[0]["commands"].to_string().contains("manage"), "{}", calls[0]);
+    assert!(calls[0]["commands"].to_string().contains("language"), "{}", calls[0]);
+    let commands = calls[0]["commands"].as_str().unwrap_or_default();
+    assert!(commands.contains(r#""command":"pause""#), "{commands}");
+    assert_eq!(calls[1]["language_code"], "nl");
+    Ok(())
+}
+
+#[tokio::test]
+async fn pause_link_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.subscribe(CHAT_ID, "fiets").await?;
+
+    harness.receive(CHAT_ID, "/manage").await;
+    let payloads = start_payloads(&harness.sent_to(CHAT_ID)[2]);
+    let (unsubscribe_payload, pause_payload) = (&payloads[0], &payloads[1]);
+    harness.receive(CHAT_ID, &format!("/start {pause_payload}")).await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[3].contains("Your subscription is now paused"), "{}", sent[3]);
+
+    // The link is stale after unsubscribing:
+    harness.receive(CHAT_ID, &format!("/start {unsubscribe_payload}")).await;
+    harness.receive(CHAT_ID, &format!("/start {pause_payload}")).await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[5].contains("You are not subscribed"), "{}", sent[5]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn language_ok() -> Result {
+    let mut harness = Harness::start().await?;
+
+    // Detected from the Telegram settings:
+    harness.receive_in(CHAT_ID, Some("nl-NL"), "fiets").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[0].contains("Er zijn geen advertenties"), "{}", sent[0]);
+
+    // Remembered for the next messages without the language code:
+    harness.receive(CHAT_ID, "/manage").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[1].contains("Je hebt op dit moment geen abonnementen"), "{}", sent[1]);
+
+    // Overridden by the command:
+    harness.receive_in(CHAT_ID, Some("nl"), "/language en").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[2].contains("The bot speaks English now"), "{}", sent[2]);
+    harness.receive_in(CHAT_ID, Some("nl"), "/manage").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[3].contains("You do not have any subscriptions"), "{}", sent[3]);
+
+    // And reset back:
+    harness.receive_in(CHAT_ID, Some("nl"), "/language auto").await;
+    let sent = harness.sent_to(CHAT_ID);
+    assert!(sent[4].contains("De bot volgt nu je Telegram-instellingen"), "{}", sent[4]);
+
+    // Group members may speak different languages, so their settings are ignored:
+    harness.receive_in(GROUP_CHAT_ID, Some("nl"), "/manage").await;
+    let sent = harness.sent_to(GROUP_CHAT_ID);
+    assert!(sent[0].contains("You do not have any subscriptions"), "{}", sent[0]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn internal_error_language_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.receive(CHAT_ID, "/language nl").await;
+
+    // The reply fails, and the error is reported in the chat's language, not in the user's one:
+    harness.telegram_api.respond_err("sendMessage", 400, "Bad Request");
+    harness.receive_in(CHAT_ID, Some("en"), "/manage").await;
+    let calls = harness.telegram_api.calls_to("sendMessage");
+    let text = calls.last().context("no error reply")?["text"].as_str().unwrap_or_default();
+    assert!(text.contains("Er is een interne fout opgetreden"), "{text}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_and_import_ok() -> Result {
+    let mut harness = Harness::start().await?;
+    harness.subscribe(CHAT_ID, "fiets").await?;
+
+    harness.receive(CHAT_ID, "/export").await;
+    let documents = harness.telegram_api.calls_to("sendDocument");
+    let export = documents[0]["document"].as_str().context("no exported document")?;
+
+    // Another chat imports it into itself:
+    harness.receive_document(GROUP_CHAT_ID, "1", export.as_bytes()).await;
+    let sent = harness.sent_to(GROUP_CHAT_ID);
+    assert!(sent[0].contains("Imported 1 subscription(s) and 0 muted seller(s)"), "{}", sent[0]);
+    harness.receive(GROUP_CHAT_ID, "/manage").await;
+    let sent = harness.sent_to(GROUP_CHAT_ID);
+    assert!(sent[1].contains("fiets"), "{}", sent[1]);
+
+    harness.receive_document(GROUP_CHAT_ID, "2", b"not an export").await;
+    let sent = harness.sent_to(GROUP_CHAT_ID);
+    assert!(sent[2].contains("only the files from /export"), "{}", sent[2]);
+    Ok(())
+}
@@ -1,26 +1,92 @@
+use clap::ValueEnum;
+use reqwest::Method;
 use reqwest_middleware::ClientWithMiddleware;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::prelude::*;
 
+/// Maximum size of the log excerpt attached to a failure signal.
+const MAX_LOG_EXCERPT_LEN: usize = 10_000;
+
+/// Uptime Kuma passes the message in the query string, so it must stay short.
+const MAX_KUMA_MESSAGE_LEN: usize = 200;
+
+/// Heartbeat URL scheme.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeartbeatKind {
+    /// Plain `POST` on success, other signals are not sent.
+    #[default]
+    Generic,
+
+    /// [Healthchecks.io](https://healthchecks.io/docs/http_api/):
+    /// `/start`, `/fail` with the log excerpt, and `/<exit status>` pings.
+    Healthchecks,
+
+    /// [Uptime Kuma](https://github.com/louislam/uptime-kuma) push monitor:
+    /// `status=up` or `status=down` with the message.
+    UptimeKuma,
+}
+
+/// Signal sent to the heartbeat URL.
+#[derive(Copy, Clone)]
+enum Signal<'a> {
+    Start,
+    Success,
+    Fail { log: &'a str },
+    ExitStatus(u8),
+}
+
 #[derive(Clone)]
 pub struct Heartbeat(Option<HeartbeatInner>);
 
 impl Heartbeat {
-    pub fn new(client: ClientWithMiddleware, url: Option<Url>) -> Self {
-        Self(url.map(|url| HeartbeatInner { client, url }))
+    pub fn new(client: ClientWithMiddleware, kind: HeartbeatKind, url: Option<Url>) -> Self {
+        Self(url.map(|url| HeartbeatInner { client, kind, url }))
     }
 
+    /// Signal that the job has started.
+    pub async fn start(&self) {
+        self.send(Signal::Start).await;
+    }
+
+    /// Signal the success.
     pub async fn check_in(&self) {
-        if let Err(error) = self.fallible_check_in().await {
+        self.send(Signal::Success).await;
+    }
+
+    /// Signal the failure with the error as the log excerpt.
+    pub async fn fail(&self, error: &Error) {
+        let log = format!("{error:#}");
+        self.send(Signal::Fail { log: truncate(&log, MAX_LOG_EXCERPT_LEN) }).await;
+    }
+
+    /// Signal that the process is exiting with the status.
+    pub async fn exit(&self, status: u8) {
+        self.send(Signal::ExitStatus(status)).await;
+    }
+
+    async fn send(&self, signal: Signal<'_>) {
+        if let Err(error) = self.fallible_send(signal).await {
             log::warn!("💔 Failed to send the heartbeat: {error:#}");
         }
     }
 
-    async fn fallible_check_in(&self) -> Result {
-        if let Some(inner) = &self.0 {
-            inner.client.post(inner.url.clone()).send().await?.error_for_status()?;
+    async fn fallible_send(&self, signal: Signal<'_>) -> Result {
+        let Some(inner) = &self.0 else {
+            return Ok(());
+        };
+        let Some((method, url)) = inner.kind.request(&inner.url, signal)? else {
+            return Ok(());
+        };
+        let mut request = inner.client.request(method, url);
+        if let Signal::Fail { log } = signal
+            && inner.kind == HeartbeatKind::Healthchecks
+        {
+            request = request.body(log.to_string());
         }
+        request.send().await?.error_for_status()?;
         Ok(())
     }
 }
@@ -28,5 +94,124 @@ impl Heartbeat {
 #[derive(Clone)]
 struct HeartbeatInner {
     client: ClientWithMiddleware,
+    kind: HeartbeatKind,
     url: Url,
 }
+
+impl HeartbeatKind {
+    /// Build the request for the signal, or `None` – if the scheme does not support the signal.
+    fn request(self, url: &Url, signal: Signal<'_>) -> Result<Option<(Method, Url)>> {
+        let request = match (self, signal) {
+            (Self::Generic | Self::Healthchecks, Signal::Success) => {
+                Some((Method::POST, url.clone()))
+            }
+            (Self::Generic, _) | (Self::UptimeKuma, Signal::Start) => None,
+
+            (Self::Healthchecks, Signal::Start) => {
+                Some((Method::POST, with_segment(url, "start")?))
+            }
+            (Self::Healthchecks, Signal::Fail { .. }) => {
+                Some((Method::POST, with_segment(url, "fail")?))
+            }
+            (Self::Healthchecks, Signal::ExitStatus(status)) => {
+                Some((Method::POST, with_segment(url, &status.to_string())?))
+            }
+
+            (Self::UptimeKuma, Signal::Success | Signal::ExitStatus(0)) => {
+                Some((Method::GET, with_kuma_status(url, "up", "OK")))
+            }
+            (Self::UptimeKuma, Signal::Fail { log }) => Some((
+                Method::GET,
+                with_kuma_status(url, "down", truncate(log, MAX_KUMA_MESSAGE_LEN)),
+            )),
+            (Self::UptimeKuma, Signal::ExitStatus(status)) => Some((
+                Method::GET,
+                with_kuma_status(url, "down", &format!("exited with status {status}")),
+            )),
+        };
+        Ok(request)
+    }
+}
+
+fn with_segment(url: &Url, segment: &str) -> Result<Url> {
+    let mut segment_url = url.clone();
+    segment_url
+        .path_segments_mut()
+        .map_err(|()| anyhow!("`{url}` cannot be a base URL"))?
+        .pop_if_empty()
+        .push(segment);
+    Ok(segment_url)
+}
+
+fn with_kuma_status(url: &Url, status: &str, message: &str) -> Url {
+    let mut url = url.clone();
+    let other_pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "status" && key != "msg")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(other_pairs)
+        .append_pair("status", status)
+        .append_pair("msg", message);
+    url
+}
+
+/// Keep the tail of the log, which is usually the most relevant part.
+fn truncate(text: &str, max_len: usize) -> &str {
+    if text.len() <= max_len {
+        text
+    } else {
+        &text[text.ceil_char_boundary(text.len() - max_len)..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthchecks_ok() -> Result {
+        let url = Url::parse("https://hc-ping.com/5bf66975-d4c7-4bf5-bcc8-b8d8a82ea278")?;
+        let kind = HeartbeatKind::Healthchecks;
+        let (method, fail_url) = kind.request(&url, Signal::Fail { log: "oops" })?.unwrap();
+        assert_eq!(method, Method::POST);
+        assert_eq!(
+            fail_url.as_str(),
+            "https://hc-ping.com/5bf66975-d4c7-4bf5-bcc8-b8d8a82ea278/fail"
+        );
+        let (_, exit_url) = kind.request(&url, Signal::ExitStatus(1))?.unwrap();
+        assert_eq!(exit_url.as_str(), "https://hc-ping.com/5bf66975-d4c7-4bf5-bcc8-b8d8a82ea278/1");
+        Ok(())
+    }
+
+    #[test]
+    fn uptime_kuma_ok() -> Result {
+        let url = Url::parse("https://kuma.example.com/api/push/abc?status=up&msg=OK&ping=")?;
+        let kind = HeartbeatKind::UptimeKuma;
+        assert!(kind.request(&url, Signal::Start)?.is_none());
+        let (method, url) = kind.request(&url, Signal::Fail { log: "no route" })?.unwrap();
+        assert_eq!(method, Method::GET);
+        assert_eq!(
+            url.as_str(),
+            "https://kuma.example.com/api/push/abc?ping=&status=down&msg=no+route"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn generic_ok() -> Result {
+        let url = Url::parse("https://example.com/ping")?;
+        assert!(HeartbeatKind::Generic.request(&url, Signal::Fail { log: "" })?.is_none());
+        assert!(HeartbeatKind::Generic.request(&url, Signal::Success)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn truncate_keeps_tail_ok() {
+        assert_eq!(truncate("abcdef", 10), "abcdef");
+        assert_eq!(truncate("abcdef", 2), "ef");
+        assert_eq!(truncate("aé", 1), "");
+    }
+}
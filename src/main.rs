@@ -1,37 +1,92 @@
 #![doc = include_str!("../README.md")]
 
-use std::time::Duration;
+use std::{fs, time::Duration};
 
+use ::serde::Serialize;
+use chrono::TimeDelta;
 use clap::Parser;
 use reqwest_middleware::ClientWithMiddleware;
 
 use crate::{
-    cli::{Args, Command, RunArgs},
-    db::Db,
+    cli::{
+        Args,
+        ChatsCommand,
+        Command,
+        ConfigCommand,
+        DatabaseCommand,
+        ExportArgs,
+        ExportFormat,
+        ImportArgs,
+        MarktplaatsArgs,
+        OutputFormat,
+        PruneArgs,
+        ReplayArgs,
+        RunArgs,
+        SearchArgs,
+        SubscriptionsCommand,
+    },
+    client::ClientSettings,
+    config::Config,
+    db::{
+        Chats,
+        Db,
+        ExportedSubscription,
+        SearchQueries,
+        SearchQuery,
+        Subscription,
+        Subscriptions,
+    },
     heartbeat::Heartbeat,
     logging::Logging,
-    marketplace::{Marketplaces, Marktplaats, MarktplaatsClient, SearchBot},
+    maintenance::Maintenance,
+    marketplace::{
+        DroppedItem,
+        Marketplaces,
+        Marktplaats,
+        MarktplaatsClient,
+        RecordedResponse,
+        ReplayedListing,
+        SearchBot,
+        SkippedListing,
+    },
     prelude::*,
-    telegram::{Telegram, TelegramBot},
+    status::{Status, StatusServer},
+    telegram::{
+        Telegram,
+        TelegramBot,
+        i18n::Language,
+        render::ManageSearchQuery,
+        template::{Template, Templates},
+    },
 };
 
 mod cli;
 mod client;
+mod config;
 mod db;
+#[cfg(test)]
+mod e2e;
 mod heartbeat;
 mod logging;
+mod maintenance;
 mod marketplace;
 mod prelude;
+mod prometheus;
 mod serde;
+mod status;
 mod telegram;
 
 fn main() -> Result {
     let dotenv_result = dotenvy::dotenv();
+    let config_path = config::apply(std::env::args_os())?;
     let cli = Args::parse();
     let _logging = Logging::init(cli.sentry_dsn.as_deref())?;
     if let Err(error) = dotenv_result {
         log::warn!("⚠️ Could not load `.env`: {error:#}");
     }
+    if let Some(config_path) = config_path {
+        log::info!("⚙️ Loaded `{}`", config_path.display());
+    }
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()?
@@ -42,50 +97,390 @@ fn main() -> Result {
 }
 
 async fn async_main(cli: Args) -> Result {
-    let db = Db::try_new(&cli.db).await?;
-    let client = client::try_new(cli.trace_requests)?;
+    // Default client for the heartbeats, Telegram and the marketplaces get their own:
+    let client = client::try_new(&ClientSettings {
+        connection_verbose: cli.trace_requests,
+        ..Default::default()
+    })?;
     match cli.command {
-        Command::Run(args) => run(db, client, *args).await,
+        Command::Search(args) => search(&client, cli.trace_requests, *args).await,
+        Command::Replay(args) => replay(&args),
+        Command::Config(ConfigCommand::Check(ref args)) => {
+            args.validate()?;
+            print!("{}", toml::to_string_pretty(&Config::redacted(&cli, args))?);
+            Ok(())
+        }
+        Command::Database(command) => {
+            let db = Db::try_new(&cli.db).await?;
+            manage_database(db, client, cli.trace_requests, command).await
+        }
     }
 }
 
-/// Run the bot indefinitely.
-async fn run(db: Db, client: ClientWithMiddleware, args: RunArgs) -> Result {
-    let telegram = Telegram::new(client.clone(), args.telegram.bot_token.into())?;
-    let command_builder = telegram.command_builder().await?;
+/// Open the database and run the command against it.
+async fn manage_database(
+    db: Db,
+    client: ClientWithMiddleware,
+    trace_requests: bool,
+    command: DatabaseCommand,
+) -> Result {
+    match command {
+        DatabaseCommand::Run(args) => run(db, client, trace_requests, *args).await,
+        DatabaseCommand::Prune(args) => prune(db, &args).await,
+        DatabaseCommand::Export(args) => export(&db, &args).await,
+        DatabaseCommand::Import(args) => import(&db, &args).await,
+        DatabaseCommand::Subscriptions(command) => manage_subscriptions(&db, command).await,
+        DatabaseCommand::Chats(ChatsCommand::List { format }) => {
+            let chats = Chats(&mut *db.connection().await?).fetch_all().await?;
+            if matches!(format, OutputFormat::Table) {
+                println!(
+                    "{:<16} {:>13} {:>6} {:>13}",
+                    "CHAT", "SUBSCRIPTIONS", "PAUSED", "MUTED SELLERS"
+                );
+                for chat in &chats {
+                    println!(
+                        "{:<16} {:>13} {:>6} {:>13}",
+                        chat.chat_id,
+                        chat.n_subscriptions,
+                        chat.n_paused_subscriptions,
+                        chat.n_muted_sellers,
+                    );
+                }
+                Ok(())
+            } else {
+                print_json(&chats, format)
+            }
+        }
+    }
+}
+
+/// List, add, or remove subscriptions.
+async fn manage_subscriptions(db: &Db, command: SubscriptionsCommand) -> Result {
+    match command {
+        SubscriptionsCommand::List { chat_id, format } => {
+            let subscriptions = match chat_id {
+                Some(chat_id) => db.subscriptions_of(chat_id).await?,
+                None => db.all_subscriptions().await?,
+            };
+            let subscriptions =
+                subscriptions.into_iter().map(ExportedSubscription::from).collect::<Vec<_>>();
+            if matches!(format, OutputFormat::Table) {
+                println!("{:<16} {:<8} QUERY", "CHAT", "STATUS");
+                for subscription in &subscriptions {
+                    let status = if subscription.is_paused { "paused" } else { "active" };
+                    println!("{:<16} {status:<8} {}", subscription.chat_id, subscription.query);
+                }
+                Ok(())
+            } else {
+                print_json(&subscriptions, format)
+            }
+        }
+        SubscriptionsCommand::Add { chat_id, query } => {
+            let search_query = SearchQuery::from(&query);
+            let mut transaction = db.begin().await?;
+            SearchQueries(&mut transaction).upsert(&search_query).await?;
+            Subscriptions(&mut transaction)
+                .upsert(Subscription::new(search_query.hash, chat_id))
+                .await?;
+            transaction.commit().await.context("failed to commit the subscription")?;
+            info!("➕ Subscribed", chat_id = chat_id, query = search_query.text);
+            Ok(())
+        }
+        SubscriptionsCommand::Remove { chat_id, query } => {
+            let search_query = SearchQuery::from(&query);
+            let mut subscriptions = Subscriptions(&mut *db.connection().await?);
+            match subscriptions.fetch(search_query.hash, chat_id).await? {
+                Some(subscription) => {
+                    subscriptions.delete(subscription).await?;
+                    info!("➖ Unsubscribed", chat_id = chat_id, query = search_query.text);
+                    Ok(())
+                }
+                None => bail!("chat #{chat_id} is not subscribed to `{}`", search_query.text),
+            }
+        }
+    }
+}
+
+/// Print the rows as a single JSON document or as JSON lines.
+fn print_json<T: Serialize>(rows: &[T], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(rows)?);
+        }
+        OutputFormat::Jsonl => {
+            for row in rows {
+                println!("{}", serde_json::to_string(row)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Search once and print the results.
+async fn search(client: &ClientWithMiddleware, trace_requests: bool, args: SearchArgs) -> Result {
+    let mut marktplaats_args = args.marktplaats;
+    if let Some(limit) = args.limit {
+        marktplaats_args.marktplaats_search_limit = limit;
+    }
+    // A one-off search must not report to the bot's monitoring:
+    marktplaats_args.heartbeat_url = None;
+    let marketplaces =
+        new_marketplaces(client, trace_requests, marktplaats_args, &Status::default())?;
+    let results = marketplaces.search_infallible(&SearchQuery::from(&args.query), None).await;
+    match args.format {
+        OutputFormat::Table => {
+            println!("{:<12} {:<20} TITLE", "ID", "PRICE");
+            for item in &results.items {
+                println!("{:<12} {:<20} {}", item.id, item.price.to_string(), item.title);
+            }
+            for DroppedItem { item, reason } in &results.dropped {
+                println!(
+                    "{:<12} {:<20} {} ❌ {reason}",
+                    item.id,
+                    item.price.to_string(),
+                    item.title,
+                );
+            }
+            for SkippedListing { item_id, error } in &results.skipped {
+                println!("{:<12} {:<20} 💥 {error}", item_id.as_deref().unwrap_or("?"), "");
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        OutputFormat::Jsonl => {
+            for item in &results.items {
+                println!("{}", serde_json::json!({ "item": item }));
+            }
+            for dropped in &results.dropped {
+                println!("{}", serde_json::to_string(dropped)?);
+            }
+            for skipped in &results.skipped {
+                println!("{}", serde_json::json!({ "skipped": skipped }));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rerun the parsing and filtering against the recorded responses, and print the outcomes.
+fn replay(args: &ReplayArgs) -> Result {
+    let mut paths = Vec::new();
+    for path in &args.paths {
+        if path.is_dir() {
+            let entries = fs::read_dir(path)
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            for entry in entries {
+                let entry_path = entry?.path();
+                if entry_path.extension().is_some_and(|extension| extension == "json") {
+                    paths.push(entry_path);
+                }
+            }
+        } else {
+            paths.push(path.clone());
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        let recorded = RecordedResponse::load(&path)?;
+        let query_text = args.query.clone().or_else(|| recorded.query()).unwrap_or_default();
+        println!("📼 {} ({})", path.display(), recorded.url);
+        let replayed = match recorded.replay(&SearchQuery::from(query_text.as_str())) {
+            Ok(replayed) => replayed,
+            Err(error) => {
+                println!("💥 {error:#}");
+                continue;
+            }
+        };
+        for ReplayedListing { item_id, outcome } in replayed {
+            match outcome {
+                Ok((item, None)) => {
+                    println!("{item_id:<12} ✅ {}", item.title);
+                    if args.render {
+                        let manage_search_query = ManageSearchQuery::new(&query_text, &[]);
+                        println!(
+                            "{}\n",
+                            Template::default().render(
+                                &item,
+                                &manage_search_query,
+                                Language::default()
+                            )
+                        );
+                    }
+                }
+                Ok((item, Some(mismatch))) => {
+                    println!("{item_id:<12} ❌ {}: {mismatch}", item.title);
+                }
+                Err(error) => println!("{item_id:<12} 💥 {error:#}"),
+            }
+        }
+    }
+    Ok(())
+}
 
-    // Marktplaats connection:
+/// Build the marketplaces, each with its own HTTP client.
+///
+/// The heartbeats go via the default client, so that the proxy does not affect the monitoring.
+fn new_marketplaces(
+    client: &ClientWithMiddleware,
+    trace_requests: bool,
+    args: MarktplaatsArgs,
+    status: &Status,
+) -> Result<Marketplaces> {
+    let marktplaats_client = client::try_new(&args.client_settings(trace_requests))
+        .context("failed to build the Marktplaats client")?;
     let marktplaats = Marktplaats::builder()
-        .client(MarktplaatsClient(client.clone()))
-        .search_limit(args.marktplaats.marktplaats_search_limit)
-        .search_in_title_and_description(args.marktplaats.search_in_title_and_description)
-        .heartbeat(Heartbeat::new(client.clone(), args.marktplaats.heartbeat_url))
+        .client(MarktplaatsClient::new(marktplaats_client)?.with_record_dir(args.record_responses))
+        .search_limit(args.marktplaats_search_limit)
+        .max_pages(args.max_pages)
+        .maybe_min_matches(args.min_matches)
+        .search_in_title_and_description(args.search_in_title_and_description)
+        .fetch_details(args.fetch_details)
+        .heartbeat(Heartbeat::new(client.clone(), args.heartbeat_kind, args.heartbeat_url))
+        .status(status.clone())
         .build();
+    Ok(Marketplaces { marktplaats })
+}
+
+/// Export the database into a file or the standard output.
+async fn export(db: &Db, args: &ExportArgs) -> Result {
+    let export = db.export(args.chat_id).await?;
+    let format = args
+        .format
+        .unwrap_or_else(|| args.output.as_deref().map_or(ExportFormat::Json, ExportFormat::of));
+    let export = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&export)?,
+        ExportFormat::Yaml => serde_norway::to_string(&export)?,
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, export)
+            .with_context(|| format!("failed to write `{}`", path.display()))?,
+        None => println!("{export}"),
+    }
+    Ok(())
+}
+
+/// Import the file into the database.
+async fn import(db: &Db, args: &ImportArgs) -> Result {
+    let export = std::fs::read_to_string(&args.input)
+        .with_context(|| format!("failed to read `{}`", args.input.display()))?;
+    let export = match ExportFormat::of(&args.input) {
+        ExportFormat::Json => {
+            serde_json::from_str(&export).context("failed to parse the export")?
+        }
+        ExportFormat::Yaml => {
+            serde_norway::from_str(&export).context("failed to parse the export")?
+        }
+    };
+    let report = db.import(&export, args.dry_run).await?;
+    info!(
+        "✅ Imported",
+        dry_run = args.dry_run,
+        n_subscriptions_added = report.n_subscriptions_added.cast_signed(),
+        n_subscriptions_updated = report.n_subscriptions_updated.cast_signed(),
+        n_subscriptions_unchanged = report.n_subscriptions_unchanged.cast_signed(),
+        n_muted_sellers_added = report.n_muted_sellers_added.cast_signed(),
+        n_chat_settings_updated = report.n_chat_settings_updated.cast_signed(),
+    );
+    Ok(())
+}
+
+/// Prune and optimize the database once.
+async fn prune(db: Db, args: &PruneArgs) -> Result {
+    Maintenance::builder().db(db).item_retention(args.item_retention()).build().run_once().await
+}
+
+/// Run the bot indefinitely.
+async fn run(db: Db, client: ClientWithMiddleware, trace_requests: bool, args: RunArgs) -> Result {
+    args.validate()?;
+
+    // Status server goes first, so that the probes respond during the initialization:
+    let status = Status::default();
+    if let Some(address) = args.status.address {
+        let status_server = StatusServer::builder()
+            .status(status.clone())
+            .db(db.clone())
+            .address(address)
+            .max_stale(TimeDelta::seconds(args.status.max_stale_secs.cast_signed()))
+            .try_bind()
+            .await?;
+        tokio::spawn(status_server.run());
+    }
+    if let Some(address) = args.status.metrics_address {
+        prometheus::install(address)?;
+    }
 
-    let marketplaces = Marketplaces { marktplaats };
+    let telegram_client = client::try_new(&args.telegram.client_settings(trace_requests))
+        .context("failed to build the Telegram client")?;
+    let telegram = Telegram::new(
+        telegram_client,
+        args.telegram.bot_token.clone().into(),
+        args.telegram.api_url.clone(),
+    )?
+    .with_timeout(args.telegram.timeout());
+    for chat in &args.telegram.authorized_chats {
+        info!("🔑 Authorized chat", chat_id = chat.id, name = chat.name.clone());
+    }
+    let command_builder = telegram.command_builder().await?;
+    let templates = args
+        .telegram
+        .templates
+        .as_deref()
+        .map(Templates::load)
+        .transpose()
+        .context("failed to load the templates")?
+        .unwrap_or_default();
+
+    // Marketplace connections:
+    let marketplaces = new_marketplaces(&client, trace_requests, args.marktplaats, &status)?;
+
+    // Heartbeats:
+    let telegram_heartbeat =
+        Heartbeat::new(client, args.telegram.heartbeat_kind, args.telegram.heartbeat_url);
+    telegram_heartbeat.start().await;
+    marketplaces.start().await;
 
     // Telegram bot:
     let telegram_bot = TelegramBot::builder()
         .telegram(telegram.clone())
-        .authorized_chat_ids(args.telegram.authorized_chat_ids.into_iter().collect())
+        .authorized_chat_ids(args.telegram.authorized_chats.iter().map(|chat| chat.id).collect())
         .db(db.clone())
         .marketplaces(marketplaces.clone())
         .poll_timeout_secs(args.telegram.poll_timeout_secs)
-        .heartbeat(Heartbeat::new(client, args.telegram.heartbeat_url))
+        .heartbeat(telegram_heartbeat.clone())
+        .status(status.clone())
         .command_builder(command_builder.clone())
+        .templates(templates.clone())
         .try_init()
         .await?;
+    status.set_ready();
+
+    // Database maintenance:
+    let maintenance =
+        Maintenance::builder().db(db.clone()).item_retention(args.prune.item_retention()).build();
 
     // Search bot:
     let search_bot = SearchBot::builder()
         .db(db)
         .search_interval(Duration::from_secs(args.search_interval_secs))
-        .marketplaces(marketplaces)
+        .marketplaces(marketplaces.clone())
         .telegram(telegram)
         .command_builder(command_builder)
+        .status(status)
+        .templates(templates)
         .build();
 
-    // Run the bots:
-    tokio::try_join!(tokio::spawn(telegram_bot.run()), tokio::spawn(search_bot.run()))?;
+    // Run the bots, they only stop on a panic:
+    let result = tokio::try_join!(
+        tokio::spawn(telegram_bot.run()),
+        tokio::spawn(search_bot.run()),
+        tokio::spawn(maintenance.run(Duration::from_hours(args.maintenance_interval_hours))),
+    );
+    if result.is_err() {
+        telegram_heartbeat.exit(1).await;
+        marketplaces.exit(1).await;
+    }
+    result?;
     Ok(())
 }
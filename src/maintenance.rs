@@ -0,0 +1,92 @@
+//! Database maintenance, in the background or once.
+
+use std::time::Duration;
+
+use bon::Builder;
+use chrono::{DateTime, TimeDelta, Utc};
+use prost::Message;
+use tokio::time::sleep;
+
+use crate::{
+    db::{Db, KeyValues, KeyedMessage},
+    prelude::*,
+};
+
+/// Last successful maintenance run.
+#[derive(Eq, PartialEq, Message)]
+struct LastRun {
+    /// Unix timestamp, in seconds.
+    #[prost(tag = "1", int64)]
+    timestamp: i64,
+}
+
+impl KeyedMessage for LastRun {
+    const KEY: &'static str = "maintenance.last_run";
+}
+
+/// Prunes and optimizes the database, once or periodically.
+#[derive(Builder)]
+pub struct Maintenance {
+    db: Db,
+
+    /// Items which have not been seen for this long are removed.
+    item_retention: TimeDelta,
+}
+
+impl Maintenance {
+    /// Run the maintenance indefinitely with the interval between the runs.
+    pub async fn run(self, interval: Duration) {
+        info!("🧹 Running the maintenance…", interval_secs = interval.as_secs_f64());
+        loop {
+            sleep(self.delay(interval).await).await;
+            if let Err(error) = self.run_once().await.context("failed to maintain the database") {
+                log::error!("‼️ Error: {error:#}");
+                capture_anyhow(&error);
+            }
+        }
+    }
+
+    /// Delay until the next run, which is immediate if the database has never been maintained.
+    ///
+    /// The last run is persisted, so that frequent restarts do not postpone the maintenance forever.
+    async fn delay(&self, interval: Duration) -> Duration {
+        match self.fetch_last_run().await.context("failed to fetch the last maintenance run") {
+            Ok(Some(last_run_at)) => {
+                let next_run_at = last_run_at + TimeDelta::from_std(interval).unwrap_or_default();
+                (next_run_at - Utc::now()).to_std().unwrap_or_default()
+            }
+            Ok(None) => Duration::ZERO,
+            Err(error) => {
+                log::error!("‼️ Error: {error:#}");
+                capture_anyhow(&error);
+                interval
+            }
+        }
+    }
+
+    async fn fetch_last_run(&self) -> Result<Option<DateTime<Utc>>> {
+        let last_run = KeyValues(&mut *self.db.connection().await?).fetch::<LastRun>().await?;
+        Ok(last_run.and_then(|last_run| DateTime::from_timestamp(last_run.timestamp, 0)))
+    }
+
+    async fn store_last_run(&self, run_at: DateTime<Utc>) -> Result {
+        KeyValues(&mut *self.db.connection().await?)
+            .upsert(&LastRun { timestamp: run_at.timestamp() })
+            .await
+    }
+
+    /// Prune the stale rows, and optimize the database.
+    #[instrument(name = "🧹 Maintaining the database…", skip_all)]
+    pub async fn run_once(&self) -> Result {
+        let report = self.db.prune(Utc::now() - self.item_retention).await?;
+        info!(
+            "🧹 Pruned the database",
+            n_items = report.n_items.cast_signed(),
+            n_notifications = report.n_notifications.cast_signed(),
+            n_search_queries = report.n_search_queries.cast_signed(),
+        );
+        self.db.optimize().await?;
+        info!("✅ Optimized the database");
+        self.store_last_run(Utc::now()).await
+    }
+}
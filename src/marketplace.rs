@@ -5,41 +5,102 @@ mod marktplaats;
 mod search;
 mod search_bot;
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Instant};
 
 use async_trait::async_trait;
+use metrics::{counter, histogram};
 
+#[cfg(test)]
+pub use self::marktplaats::fake_api as fake_marktplaats;
 pub use self::{
-    marktplaats::{Marktplaats, MarktplaatsClient},
-    search::NormalisedQuery,
+    marktplaats::{Marktplaats, MarktplaatsClient, RecordedResponse, ReplayedListing},
+    search::{DroppedItem, NormalisedQuery, SearchResults, SkippedListing},
     search_bot::SearchBot,
 };
-use crate::{db::SearchQuery, marketplace::item::Item, prelude::*};
+use crate::{
+    db::SearchQuery,
+    heartbeat::Heartbeat,
+    marketplace::item::{Details, Item},
+    prelude::*,
+    prometheus::{
+        MARKETPLACE_ITEMS_DROPPED,
+        MARKETPLACE_ITEMS_FETCHED,
+        MARKETPLACE_LISTINGS_SKIPPED,
+        MARKETPLACE_SEARCH_DURATION,
+        MARKETPLACE_SEARCHES,
+    },
+};
 
 #[async_trait]
 pub trait Marketplace: Display {
-    async fn check_in(&self);
+    fn heartbeat(&self) -> &Heartbeat;
+
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults>;
 
-    async fn search(&self, query: &SearchQuery) -> Result<Vec<Item>>;
+    /// Fetch the item details, or `None` – if the marketplace is not configured to.
+    async fn fetch_details(&self, item: &Item) -> Result<Option<Details>>;
 
     #[instrument(
         name = "🔎 Searching on marketplace…",
         skip_all,
         fields(self = %self, query.text = query.text, limit = limit),
     )]
-    async fn search_infallible(&self, query: &SearchQuery, limit: Option<usize>) -> Vec<Item> {
-        match self.search(query).await.with_context(|| format!("failed to search on {self}")) {
-            Ok(mut items) => {
-                self.check_in().await;
+    async fn search_infallible(&self, query: &SearchQuery, limit: Option<usize>) -> SearchResults {
+        let marketplace = self.to_string();
+        let start_time = Instant::now();
+        let result =
+            self.search(query).await.with_context(|| format!("failed to search on {self}"));
+        histogram!(MARKETPLACE_SEARCH_DURATION, "marketplace" => marketplace.clone())
+            .record(start_time.elapsed());
+        let outcome = if result.is_ok() { "ok" } else { "error" };
+        counter!(MARKETPLACE_SEARCHES, "marketplace" => marketplace.clone(), "outcome" => outcome)
+            .increment(1);
+        match result {
+            Ok(mut results) => {
+                counter!(MARKETPLACE_ITEMS_FETCHED, "marketplace" => marketplace.clone())
+                    .increment((results.items.len() + results.dropped.len()) as u64);
+                counter!(MARKETPLACE_ITEMS_DROPPED, "marketplace" => marketplace.clone())
+                    .increment(results.dropped.len() as u64);
+                counter!(MARKETPLACE_LISTINGS_SKIPPED, "marketplace" => marketplace)
+                    .increment(results.skipped.len() as u64);
+                for SkippedListing { item_id, error } in &results.skipped {
+                    warn!(
+                        "⚠️ Skipped malformed listing",
+                        item_id = item_id.clone().unwrap_or_default(),
+                        error = error.clone(),
+                    );
+                }
+                self.heartbeat().check_in().await;
                 if let Some(limit) = limit {
-                    items.truncate(limit);
+                    results.items.truncate(limit);
                 }
-                items
+                results
             }
             Err(error) => {
                 log::error!("‼️ Error: {error:#}");
                 capture_anyhow(&error);
-                Vec::new()
+                self.heartbeat().fail(&error).await;
+                SearchResults::default()
+            }
+        }
+    }
+
+    #[instrument(
+        name = "🔎 Fetching item details…",
+        skip_all,
+        fields(self = %self, item.id = item.id),
+    )]
+    async fn fetch_details_infallible(&self, item: &Item) -> Option<Details> {
+        match self
+            .fetch_details(item)
+            .await
+            .with_context(|| format!("failed to fetch item details from {self}"))
+        {
+            Ok(details) => details,
+            Err(error) => {
+                log::error!("‼️ Error: {error:#}");
+                capture_anyhow(&error);
+                None
             }
         }
     }
@@ -51,15 +112,27 @@ pub struct Marketplaces {
 }
 
 impl Marketplaces {
+    pub async fn start(&self) {
+        self.marktplaats.heartbeat().start().await;
+    }
+
     pub async fn check_in(&self) {
-        self.marktplaats.check_in().await;
+        self.marktplaats.heartbeat().check_in().await;
+    }
+
+    pub async fn exit(&self, status: u8) {
+        self.marktplaats.heartbeat().exit(status).await;
     }
 
     pub async fn search_infallible(
         &self,
         query: &SearchQuery,
         marketplace_limit: Option<usize>,
-    ) -> Vec<Item> {
+    ) -> SearchResults {
         self.marktplaats.search_infallible(query, marketplace_limit).await
     }
+
+    pub async fn fetch_details_infallible(&self, item: &Item) -> Option<Details> {
+        self.marktplaats.fetch_details_infallible(item).await
+    }
 }
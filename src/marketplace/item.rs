@@ -1,10 +1,12 @@
 use bon::Builder;
+use serde::Serialize;
 use url::Url;
 
 pub use self::{
     amount::Amount,
-    condition::{Condition, New, Used},
-    delivery::Delivery,
+    condition::{Condition, ConditionClass, New, Used},
+    delivery::{Delivery, DeliveryMethod},
+    details::{Attribute, Details},
     location::{GeoLocation, Location},
     price::Price,
     seller::Seller,
@@ -13,12 +15,13 @@ pub use self::{
 mod amount;
 mod condition;
 mod delivery;
+mod details;
 mod location;
 mod price;
 mod seller;
 
 /// Marketplace item.
-#[derive(Builder)]
+#[derive(Builder, Serialize)]
 pub struct Item {
     pub id: String,
     pub url: Url,
@@ -30,4 +33,27 @@ pub struct Item {
     pub price: Price,
     pub seller: Seller,
     pub location: Option<Location>,
+
+    /// Whether the seller paid for a better placement of the item.
+    #[builder(default)]
+    pub is_promoted: bool,
+
+    /// Whether the item is being auctioned, usually by an auction house.
+    #[builder(default)]
+    pub is_auction: bool,
+
+    /// Details from the item page, only fetched for the items which are about to be notified about.
+    pub details: Option<Details>,
+}
+
+impl Item {
+    /// All the pictures from the item page, or the search result's one – if the details are missing.
+    pub fn picture_urls(&self) -> Vec<&Url> {
+        match &self.details {
+            Some(details) if !details.picture_urls.is_empty() => {
+                details.picture_urls.iter().collect()
+            }
+            _ => self.picture_url.iter().collect(),
+        }
+    }
 }
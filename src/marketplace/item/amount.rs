@@ -2,12 +2,12 @@ use std::str::FromStr;
 
 use maud::{Markup, Render, html};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::prelude::*;
 
 /// Monetary amount.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct Amount(pub Decimal);
 
 impl Amount {
@@ -1,23 +1,74 @@
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+use serde::Serialize;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub enum Condition {
     New(New),
     Used(Used),
     Refurbished,
+
+    /// Condition, which the marketplace has but we do not know yet, as is.
+    Unknown(String),
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+impl Condition {
+    /// Filterable class, or `None` – for an unknown condition.
+    pub const fn class(&self) -> Option<ConditionClass> {
+        match self {
+            Self::New(New::AsGood) => Some(ConditionClass::AsGoodAsNew),
+            Self::New(_) => Some(ConditionClass::New),
+            Self::Used(Used::NotFullyFunctional) => Some(ConditionClass::NotWorking),
+            Self::Used(_) => Some(ConditionClass::Used),
+            Self::Refurbished => Some(ConditionClass::Refurbished),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
 pub enum New {
     Unspecified,
+
+    #[expect(dead_code)]
     WithoutTags,
+
+    #[expect(dead_code)]
     WithTags,
+
     AsGood,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
 pub enum Used {
     Unspecified,
+
+    #[expect(dead_code)]
     VeryGood,
+
+    #[expect(dead_code)]
     Good,
+
+    #[expect(dead_code)]
     Satisfactory,
+
     NotFullyFunctional,
 }
+
+/// Coarse condition grade, which users can filter on.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConditionClass {
+    New = 0,
+    AsGoodAsNew = 1,
+    Used = 2,
+    NotWorking = 3,
+    Refurbished = 4,
+}
+
+impl ConditionClass {
+    pub const ALL: [Self; 5] =
+        [Self::New, Self::AsGoodAsNew, Self::Used, Self::NotWorking, Self::Refurbished];
+
+    /// Bit in a condition class bitmask.
+    pub const fn bit(self) -> i64 {
+        1 << self as i64
+    }
+}
@@ -1,6 +1,40 @@
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+use serde::Serialize;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub enum Delivery {
     CollectionOnly,
     ShippingOnly,
     Both,
+
+    /// Delivery, which the marketplace has but we do not know yet, as is.
+    Unknown(String),
+}
+
+impl Delivery {
+    /// Check whether the item may be received the way, or `None` – for an unknown delivery.
+    pub const fn supports(&self, method: DeliveryMethod) -> Option<bool> {
+        match (self, method) {
+            (Self::Unknown(_), _) => None,
+            (Self::Both, _)
+            | (Self::CollectionOnly, DeliveryMethod::Collection)
+            | (Self::ShippingOnly, DeliveryMethod::Shipping) => Some(true),
+            _ => Some(false),
+        }
+    }
+}
+
+/// Single way to receive an item, which users can filter on.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DeliveryMethod {
+    Collection = 0,
+    Shipping = 1,
+}
+
+impl DeliveryMethod {
+    pub const ALL: [Self; 2] = [Self::Collection, Self::Shipping];
+
+    /// Bit in a delivery method bitmask.
+    pub const fn bit(self) -> i64 {
+        1 << self as i64
+    }
 }
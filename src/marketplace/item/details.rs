@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Item details which are only available on the item page.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Details {
+    /// Full description, unlike the truncated one from the search results.
+    pub description: Option<String>,
+
+    /// All the item pictures, in the seller's order.
+    #[serde(default)]
+    pub picture_urls: Vec<Url>,
+
+    pub posted_at: Option<DateTime<Utc>>,
+    pub n_views: Option<u32>,
+
+    /// All the item attributes, as they are displayed on the item page.
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub label: String,
+    pub value: String,
+}
@@ -1,12 +1,13 @@
 use bon::Builder;
+use serde::Serialize;
 
-#[derive(Builder)]
+#[derive(Builder, Serialize)]
 pub struct Location {
     pub toponym: String,
     pub geo: Option<GeoLocation>,
 }
 
-#[derive(Copy, Clone, Builder)]
+#[derive(Copy, Clone, Builder, Serialize)]
 pub struct GeoLocation {
     pub latitude: f64,
     pub longitude: f64,
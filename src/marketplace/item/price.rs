@@ -1,14 +1,40 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
 use crate::marketplace::item::amount::Amount;
 
-#[derive(Copy, Clone)]
+#[derive(Clone, Serialize)]
 pub enum Price {
     Fixed(Amount),
     OnRequest,
     MinimalBid(Amount),
+    #[expect(dead_code)]
     MaximalBid(Amount),
     SeeDescription,
     ToBeAgreed,
     Reserved,
     FastBid,
     Exchange,
+
+    /// Price type, which the marketplace has but we do not know yet, as is.
+    Unknown(String),
+}
+
+/// Plain-text representation for the terminal.
+impl Display for Price {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fixed(asking) => write!(f, "€{}", asking.0),
+            Self::OnRequest => f.write_str("on request"),
+            Self::MinimalBid(asking) => write!(f, "€{} (bidding up)", asking.0),
+            Self::MaximalBid(asking) => write!(f, "€{} (bidding down)", asking.0),
+            Self::SeeDescription => f.write_str("see description"),
+            Self::ToBeAgreed => f.write_str("to be agreed"),
+            Self::Reserved => f.write_str("reserved"),
+            Self::FastBid => f.write_str("auction"),
+            Self::Exchange => f.write_str("exchange"),
+            Self::Unknown(price_type) => write!(f, "unknown ({price_type})"),
+        }
+    }
 }
@@ -1,8 +1,16 @@
 use bon::Builder;
+use serde::Serialize;
 use url::Url;
 
-#[derive(Builder)]
+#[derive(Builder, Serialize)]
 pub struct Seller {
+    /// Marketplace-specific seller ID.
+    pub id: i64,
+
     pub username: String,
     pub profile_url: Url,
+
+    /// Whether the seller is a business rather than a private person.
+    #[builder(default)]
+    pub is_business: bool,
 }
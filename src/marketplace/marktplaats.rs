@@ -1,18 +1,40 @@
 mod client;
+mod details;
+#[cfg(test)]
+pub mod fake_api;
 mod listing;
+mod recording;
 
-use std::fmt::{Display, Formatter};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
 
 use async_trait::async_trait;
 use bon::Builder;
 
-use self::client::SearchRequest;
-pub use self::{client::MarktplaatsClient, listing::Listings};
+pub use self::{
+    client::MarktplaatsClient,
+    listing::Listings,
+    recording::{RecordedResponse, ReplayedListing},
+};
+use self::{client::SearchRequest, listing::Listing};
 use crate::{
     db::SearchQuery,
     heartbeat::Heartbeat,
-    marketplace::{Marketplace, item::Item},
+    marketplace::{
+        DroppedItem,
+        Marketplace,
+        NormalisedQuery,
+        SearchResults,
+        SkippedListing,
+        item::{Details, Item},
+        search::Mismatch,
+    },
     prelude::*,
+    status::Status,
 };
 
 #[must_use]
@@ -21,9 +43,36 @@ pub struct Marktplaats {
     client: MarktplaatsClient,
     search_limit: u32,
     heartbeat: Heartbeat,
+    status: Status,
     search_in_title_and_description: bool,
+
+    /// Fetch the item pages for the full descriptions, all pictures and so on.
+    fetch_details: bool,
+
+    /// Maximum number of the result pages per search, `1` disables the pagination.
+    #[builder(default = 1)]
+    max_pages: u32,
+
+    /// Stop paginating once this many items match the query, defaults to the search limit.
+    min_matches: Option<u32>,
+
+    /// Item IDs returned by the latest search of each query, by the query hash.
+    ///
+    /// Once a page consists only of those, the next pages have been seen too.
+    #[builder(skip)]
+    seen_item_ids: Arc<Mutex<HashMap<i64, SeenItemIds>>>,
+}
+
+struct SeenItemIds {
+    searched_at: Instant,
+    item_ids: HashSet<String>,
 }
 
+/// Maximum number of queries to remember the seen item IDs for, the least recently searched ones are forgotten.
+///
+/// Besides the subscriptions, every `/search` command adds a query.
+const MAX_SEEN_QUERIES: usize = 1000;
+
 impl Display for Marktplaats {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str("Marktplaats")
@@ -32,36 +81,258 @@ impl Display for Marktplaats {
 
 #[async_trait]
 impl Marketplace for Marktplaats {
-    async fn check_in(&self) {
-        self.heartbeat.check_in().await;
+    fn heartbeat(&self) -> &Heartbeat {
+        &self.heartbeat
     }
 
     /// Search Marktplaats.
-    async fn search(&self, query: &SearchQuery) -> Result<Vec<Item>> {
-        let query = query.to_normalised_query();
-        let search_text = query.to_search_text();
-        let listings = SearchRequest::builder()
-            .query(&search_text)
-            .limit(self.search_limit)
-            .search_in_title_and_description(self.search_in_title_and_description)
-            .build()
-            .call_on(&self.client)
-            .await?
-            .inner;
-        let n_fetched = listings.len();
-        let items = listings
-            .into_iter()
-            .filter(|listing| {
-                query.matches(listing.title.split_whitespace().chain(listing.brand().into_iter()))
-            })
-            .map(TryInto::<Item>::try_into)
-            .collect::<Result<Vec<Item>>>()?;
+    ///
+    /// With the pagination enabled, keep fetching the pages until enough items match the query,
+    /// the results run out, a page has been fully seen by the previous search, or the page cap is hit.
+    ///
+    /// If a next page fails, the results of the previous pages are returned.
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults> {
+        let normalised_query = query.to_normalised_query();
+        let search_text = normalised_query.to_search_text();
+        let min_matches = self.min_matches.unwrap_or(self.search_limit) as usize;
+        let previously_seen = self
+            .lock_seen_item_ids()
+            .get(&query.hash)
+            .map(|seen| seen.item_ids.clone())
+            .unwrap_or_default();
+        let mut seen_item_ids = HashSet::new();
+        let mut results = SearchResults::default();
+        let mut n_fetched = 0;
+        let mut n_pages = 0;
+        while n_pages < self.max_pages {
+            let Some(offset) = n_pages.checked_mul(self.search_limit) else {
+                warn!("⚠️ The next page is out of range", n_pages = i64::from(n_pages));
+                break;
+            };
+            let result = SearchRequest::builder()
+                .query(&search_text)
+                .maybe_offset((offset != 0).then_some(offset))
+                .limit(self.search_limit)
+                .search_in_title_and_description(self.search_in_title_and_description)
+                .build()
+                .call_on(&self.client)
+                .await;
+            let listings = match result {
+                Ok(listings) => listings,
+                Err(error) if n_pages != 0 => {
+                    warn!(
+                        "⚠️ Failed to fetch the next page",
+                        n_pages = i64::from(n_pages),
+                        error = format!("{error:#}"),
+                    );
+                    break;
+                }
+                Err(error) => return Err(error),
+            };
+            n_pages += 1;
+            let n_listings = listings.inner.len() + listings.broken.len();
+            n_fetched += n_listings;
+            let is_fully_seen = !listings.inner.is_empty()
+                && listings.inner.iter().all(|listing| {
+                    previously_seen.contains(&listing.item_id)
+                        || seen_item_ids.contains(&listing.item_id)
+                });
+            for broken in listings.broken {
+                results.skipped.push(SkippedListing {
+                    item_id: broken.item_id,
+                    error: format!("{:#}", broken.error),
+                });
+            }
+            for listing in listings.inner {
+                if !seen_item_ids.insert(listing.item_id.clone()) {
+                    // New listings shift the pages, so the next page may repeat the previous one.
+                    continue;
+                }
+                let item_id = listing.item_id.clone();
+                match triage(&normalised_query, listing) {
+                    Ok((item, None)) => results.items.push(item),
+                    Ok((item, Some(reason))) => results.dropped.push(DroppedItem { item, reason }),
+                    Err(error) => results.skipped.push(SkippedListing {
+                        item_id: Some(item_id),
+                        error: format!("{error:#}"),
+                    }),
+                }
+            }
+            if n_listings < self.search_limit as usize
+                || results.items.len() >= min_matches
+                || is_fully_seen
+            {
+                break;
+            }
+        }
+        self.remember_seen_item_ids(query.hash, seen_item_ids);
         info!(
             "🛍️ Fetched from Marktplaats",
             search_text = search_text,
+            n_pages = i64::from(n_pages),
             n_fetched = n_fetched,
-            n_filtered = items.len(),
+            n_filtered = results.items.len(),
+            n_skipped = results.skipped.len(),
+        );
+        self.status.on_crawled(&self.to_string());
+        Ok(results)
+    }
+
+    /// Fetch the details from the item page, if enabled.
+    async fn fetch_details(&self, item: &Item) -> Result<Option<Details>> {
+        if !self.fetch_details {
+            return Ok(None);
+        }
+        let details: Details = self.client.fetch_details(&item.url).await?.try_into()?;
+        info!(
+            "🛍️ Fetched item details from Marktplaats",
+            item_id = &item.id,
+            n_pictures = details.picture_urls.len(),
         );
-        Ok(items)
+        Ok(Some(details))
+    }
+}
+
+impl Marktplaats {
+    fn lock_seen_item_ids(&self) -> MutexGuard<'_, HashMap<i64, SeenItemIds>> {
+        self.seen_item_ids.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Store the item IDs of the latest search, and forget the least recently searched query if needed.
+    fn remember_seen_item_ids(&self, query_hash: i64, item_ids: HashSet<String>) {
+        let mut seen_item_ids = self.lock_seen_item_ids();
+        seen_item_ids.insert(query_hash, SeenItemIds { searched_at: Instant::now(), item_ids });
+        if seen_item_ids.len() > MAX_SEEN_QUERIES
+            && let Some(oldest_hash) =
+                seen_item_ids.iter().min_by_key(|(_, seen)| seen.searched_at).map(|(hash, _)| *hash)
+        {
+            seen_item_ids.remove(&oldest_hash);
+        }
+    }
+}
+
+/// Convert the listing into the item, and match it against the query.
+///
+/// # Returns
+///
+/// The item and the mismatch reason – if the item does not match the query.
+fn triage(query: &NormalisedQuery, listing: Listing) -> Result<(Item, Option<Mismatch>)> {
+    let mismatch = query.mismatch(listing.title.split_whitespace().chain(listing.brand()));
+    Ok((listing.try_into()?, mismatch))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        client::{self, ClientSettings},
+        heartbeat::HeartbeatKind,
+        marketplace::fake_marktplaats::{FakeMarktplaats, listing},
+    };
+
+    fn marktplaats(marktplaats_api: &FakeMarktplaats, max_pages: u32) -> Result<Marktplaats> {
+        Ok(Marktplaats::builder()
+            .client(marktplaats_api.client()?)
+            .search_limit(2)
+            .max_pages(max_pages)
+            .heartbeat(Heartbeat::new(
+                client::try_new(&ClientSettings::default())?,
+                HeartbeatKind::Generic,
+                None,
+            ))
+            .status(Status::default())
+            .search_in_title_and_description(false)
+            .fetch_details(false)
+            .build())
+    }
+
+    #[tokio::test]
+    async fn paginate_until_min_matches_ok() -> Result {
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        marktplaats_api.set_listings(vec![
+            listing("m1", "Gazelle bakfiets"),
+            listing("m2", "Gazelle fiets"),
+            listing("m3", "Batavus bakfiets"),
+            listing("m4", "Batavus fiets"),
+            listing("m5", "Sparta fiets"),
+        ]);
+        let marktplaats = marktplaats(&marktplaats_api, 5)?;
+
+        let results = marktplaats.search(&SearchQuery::from("fiets")).await?;
+        let item_ids: Vec<_> = results.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(item_ids, ["m2", "m4"]);
+        assert_eq!(results.dropped.len(), 2);
+        let queries = marktplaats_api.queries();
+        assert_eq!(queries.len(), 2, "{queries:?}");
+        assert!(!queries[0].contains("offset"), "{}", queries[0]);
+        assert!(queries[1].contains("offset=2"), "{}", queries[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn paginate_until_fully_seen_ok() -> Result {
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        let bakfietsen =
+            (1..=6).map(|i| listing(&format!("m{i}"), "Gazelle bakfiets")).collect::<Vec<_>>();
+        marktplaats_api.set_listings(bakfietsen.clone());
+        let marktplaats = marktplaats(&marktplaats_api, 2)?;
+        let query = SearchQuery::from("fiets");
+
+        // Nothing matches, so the page cap applies:
+        let results = marktplaats.search(&query).await?;
+        assert!(results.items.is_empty());
+        assert_eq!(marktplaats_api.queries().len(), 2);
+
+        // The first page has been seen by the previous search:
+        let _ = marktplaats.search(&query).await?;
+        assert_eq!(marktplaats_api.queries().len(), 3);
+
+        // A new listing makes the first page new again:
+        let mut listings = vec![listing("m7", "Gazelle bakfiets")];
+        listings.extend(bakfietsen);
+        marktplaats_api.set_listings(listings);
+        let results = marktplaats.search(&query).await?;
+        assert_eq!(marktplaats_api.queries().len(), 5);
+        assert_eq!(results.dropped.len(), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn paginate_past_broken_page_ok() -> Result {
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        marktplaats_api.set_listings(vec![
+            json!({"itemId": "m1"}),
+            json!({"itemId": "m2"}),
+            listing("m3", "Gazelle fiets"),
+        ]);
+        let marktplaats = marktplaats(&marktplaats_api, 2)?;
+
+        // A page without parseable listings must not count as seen:
+        let results = marktplaats.search(&SearchQuery::from("fiets")).await?;
+        assert_eq!(results.skipped.len(), 2);
+        assert_eq!(results.items.len(), 1);
+        assert_eq!(marktplaats_api.queries().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn paginate_failed_page_ok() -> Result {
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        marktplaats_api
+            .set_listings((1..=4).map(|i| listing(&format!("m{i}"), "Gazelle bakfiets")).collect());
+        marktplaats_api.fail_from_offset(2);
+        let marktplaats = marktplaats(&marktplaats_api, 2)?;
+        let query = SearchQuery::from("fiets");
+
+        let results = marktplaats.search(&query).await?;
+        assert_eq!(results.dropped.len(), 2, "the first page must be kept");
+        assert_eq!(marktplaats_api.queries().len(), 2);
+
+        // The first page has been seen despite the failure:
+        let _ = marktplaats.search(&query).await?;
+        assert_eq!(marktplaats_api.queries().len(), 3);
+        Ok(())
     }
 }
@@ -1,21 +1,51 @@
+use std::path::PathBuf;
+
 use bon::Builder;
 use reqwest_middleware::ClientWithMiddleware;
 use serde::Serialize;
 use url::Url;
 
-use crate::{logging::Breadcrumb, marketplace::marktplaats::Listings, prelude::*};
+use crate::{
+    logging::Breadcrumb,
+    marketplace::marktplaats::{Listings, details::ListingDetails, recording::RecordedResponse},
+    prelude::*,
+};
 
+/// Marktplaats API client.
+///
+/// Clones share the HTTP client with its rate limiter, so that the crawler and the interactive searches are limited together.
 #[must_use]
 #[derive(Clone)]
-pub struct MarktplaatsClient(pub ClientWithMiddleware);
+pub struct MarktplaatsClient {
+    client: ClientWithMiddleware,
+    root_url: Url,
+
+    /// Store the raw search responses in the directory, if set.
+    record_dir: Option<PathBuf>,
+}
 
 impl MarktplaatsClient {
+    pub fn new(client: ClientWithMiddleware) -> Result<Self> {
+        Ok(Self { client, root_url: Url::parse("https://www.marktplaats.nl")?, record_dir: None })
+    }
+
+    /// Store the raw search responses in the directory for `replay`.
+    pub fn with_record_dir(self, record_dir: Option<PathBuf>) -> Self {
+        Self { record_dir, ..self }
+    }
+
+    /// Point the client to a stand-in server.
+    #[cfg(test)]
+    pub fn with_root_url(self, root_url: Url) -> Self {
+        Self { root_url, ..self }
+    }
+
     /// Search Marktplaats.
     pub async fn search(&self, request: &SearchRequest<'_>) -> Result<Listings> {
         let url = {
             let query =
                 serde_qs::to_string(request).context("failed to serialize the search request")?;
-            let mut url = Url::parse("https://www.marktplaats.nl/lrp/api/search")?;
+            let mut url = self.root_url.join("/lrp/api/search")?;
             url.set_query(Some(&query));
             url
         };
@@ -26,8 +56,8 @@ impl MarktplaatsClient {
             .build()
             .add();
         let response = self
-            .0
-            .get(url)
+            .client
+            .get(url.clone())
             .send()
             .await
             .context("failed to search")?
@@ -41,8 +71,36 @@ impl MarktplaatsClient {
             .data("response.body", response.as_str())
             .build()
             .add();
+        if let Some(record_dir) = &self.record_dir {
+            // Recording is a debugging aid, so it must not break the search:
+            match RecordedResponse::new(url, response.clone()).save(record_dir) {
+                Ok(path) => debug!("📼 Recorded the response", path = path.display().to_string()),
+                Err(error) => log::warn!("⚠️ Failed to record the response: {error:#}"),
+            }
+        }
         serde_json::from_str(&response).context("failed to deserialize the response")
     }
+
+    /// Fetch the listing details from the item page.
+    pub async fn fetch_details(&self, url: &Url) -> Result<ListingDetails> {
+        Breadcrumb::debug()
+            .category(module_path!())
+            .message("Fetching the item page…")
+            .data("url", url.as_str())
+            .build()
+            .add();
+        let page = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .context("failed to fetch the item page")?
+            .error_for_status()
+            .context("the item page call failed")?
+            .text()
+            .await?;
+        ListingDetails::from_page(&page)
+    }
 }
 
 #[must_use]
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{marketplace::item::Attribute, prelude::*};
+
+/// The item page embeds the complete listing as a JSON configuration object.
+const CONFIG_PREFIX: &str = "window.__CONFIG__ =";
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    listing: ListingDetails,
+}
+
+/// Listing as it is embedded in the item page.
+#[derive(Debug, Deserialize)]
+pub struct ListingDetails {
+    /// Full description in HTML.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub gallery: Gallery,
+
+    #[serde(default)]
+    pub stats: Option<Stats>,
+
+    #[serde(default)]
+    pub attributes: Vec<DetailsAttribute>,
+}
+
+impl ListingDetails {
+    /// Extract the listing from the item page HTML.
+    pub fn from_page(page: &str) -> Result<Self> {
+        let (_, config) =
+            page.split_once(CONFIG_PREFIX).context("the item page contains no listing config")?;
+
+        // The config is followed by the rest of the script, hence the streaming deserializer:
+        let mut deserializer = serde_json::Deserializer::from_str(config.trim_start());
+        let config = Config::deserialize(&mut deserializer)
+            .context("failed to deserialize the listing config")?;
+        Ok(config.listing)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Gallery {
+    /// Protocol-relative picture URLs, like `//images.marktplaats.com/api/v1/…`.
+    #[serde(rename = "imageUrls", default)]
+    pub image_urls: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Stats {
+    #[serde(rename = "viewCount", default)]
+    pub view_count: Option<u32>,
+
+    /// Posting timestamp.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetailsAttribute {
+    pub label: String,
+    pub value: String,
+}
+
+impl TryFrom<ListingDetails> for crate::marketplace::item::Details {
+    type Error = Error;
+
+    fn try_from(details: ListingDetails) -> Result<Self> {
+        let picture_urls = details
+            .gallery
+            .image_urls
+            .iter()
+            .map(|url| {
+                let url = url
+                    .strip_prefix("//")
+                    .map_or_else(|| url.clone(), |url| format!("https://{url}"));
+                Url::parse(&url).context("failed to parse the picture URL")
+            })
+            .collect::<Result<Vec<Url>>>()?;
+        Ok(Self {
+            description: details.description.as_deref().map(html_to_text),
+            picture_urls,
+            posted_at: details.stats.as_ref().and_then(|stats| stats.since),
+            n_views: details.stats.as_ref().and_then(|stats| stats.view_count),
+            attributes: details
+                .attributes
+                .into_iter()
+                .map(|attribute| Attribute { label: attribute.label, value: attribute.value })
+                .collect(),
+        })
+    }
+}
+
+/// Convert the description HTML into plain text: keep the line breaks, drop the other tags.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/p" || tag == "/div" || tag == "/li" {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::item::Details;
+
+    #[test]
+    fn parse_item_page_ok() -> Result {
+        // language=html
+        const PAGE: &str = r#"<html><head><script>window.__CONFIG__ = {"listing":{"itemId":"m2153817200","title":"Ubiquiti UniFi Cloud Gateway Ultra","description":"Gekocht op 25-07-2024 bij ubiquiti store.<br />Inclusief 3d-geprinte wandmontagebeugel &amp; kabels.","gallery":{"imageUrls":["//images.marktplaats.com/api/v1/listing-mp-p/images/ba/baaee2ea?rule=ecg_mp_eps$_85.jpg","//images.marktplaats.com/api/v1/listing-mp-p/images/cc/cc3fe1a2?rule=ecg_mp_eps$_85.jpg"]},"stats":{"viewCount":42,"favoritedCount":3,"since":"2024-09-02T22:08:20Z"},"attributes":[{"key":"condition","label":"Conditie","value":"Zo goed als nieuw"},{"key":"type","label":"Type","value":"Router"}]}};
+        window.__OTHER__ = {};</script></head></html>"#;
+
+        let details: Details = ListingDetails::from_page(PAGE)?.try_into()?;
+        assert_eq!(
+            details.description.as_deref(),
+            Some(
+                "Gekocht op 25-07-2024 bij ubiquiti store.\nInclusief 3d-geprinte wandmontagebeugel & kabels."
+            ),
+        );
+        assert_eq!(details.picture_urls.len(), 2);
+        assert_eq!(
+            details.picture_urls[0].as_str(),
+            "https://images.marktplaats.com/api/v1/listing-mp-p/images/ba/baaee2ea?rule=ecg_mp_eps$_85.jpg",
+        );
+        assert_eq!(details.n_views, Some(42));
+        assert_eq!(details.posted_at, Some("2024-09-02T22:08:20Z".parse()?));
+        assert_eq!(
+            details.attributes[1],
+            Attribute { label: "Type".to_string(), value: "Router".to_string() },
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_item_page_without_config_err() {
+        assert!(ListingDetails::from_page("<html></html>").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! In-process stand-in for the Marktplaats search API in the tests.
+
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bytes::Bytes;
+use http::{Request, Response, StatusCode, header};
+use http_body_util::Full;
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::Url;
+
+use crate::{
+    client::{self, ClientSettings},
+    marketplace::marktplaats::MarktplaatsClient,
+    prelude::*,
+};
+
+/// The server stops when dropped.
+pub struct FakeMarktplaats {
+    root_url: Url,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Listings returned by every search, paginated with `offset` and `limit`.
+    listings: Vec<Value>,
+
+    /// Queries of the received searches.
+    queries: Vec<String>,
+
+    /// Searches from this offset fail.
+    fail_from_offset: Option<usize>,
+}
+
+impl Drop for FakeMarktplaats {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl FakeMarktplaats {
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let root_url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let server = tokio::spawn(serve(listener, state.clone()));
+        Ok(Self { root_url, state, server })
+    }
+
+    /// Client to the fake server, which is not rate-limited.
+    pub fn client(&self) -> Result<MarktplaatsClient> {
+        let client = client::try_new(&ClientSettings::default())?;
+        Ok(MarktplaatsClient::new(client)?.with_root_url(self.root_url.clone()))
+    }
+
+    /// Replace the listings, which the searches return.
+    pub fn set_listings(&self, listings: Vec<Value>) {
+        self.lock().listings = listings;
+    }
+
+    /// Make the searches from the offset fail.
+    pub fn fail_from_offset(&self, offset: usize) {
+        self.lock().fail_from_offset = Some(offset);
+    }
+
+    /// Query strings of the searches received so far.
+    pub fn queries(&self) -> Vec<String> {
+        self.lock().queries.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+/// Minimal fixed-price listing.
+pub fn listing(item_id: &str, title: &str) -> Value {
+    // language=json
+    json!({
+        "itemId": item_id,
+        "title": title,
+        "description": format!("{title} in good condition"),
+        "priceInfo": {"priceType": "FIXED", "priceCents": 4200},
+        "vipUrl": format!("/v/fietsen/{item_id}-{}", title.to_lowercase().replace(' ', "-")),
+        "sellerInformation": {"sellerId": 1, "sellerName": "Seller"},
+        "location": {"cityName": "Amsterdam"},
+    })
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(|request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle(&state, &request)) }
+            });
+            let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+        });
+    }
+}
+
+fn handle(state: &Mutex<State>, request: &Request<Incoming>) -> Response<Full<Bytes>> {
+    if request.uri().path() != "/lrp/api/search" {
+        let mut response = Response::new(Full::new(Bytes::from_static(b"not found")));
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    }
+    let query = request.uri().query().unwrap_or_default().to_string();
+    let parameter = |name: &str| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+    };
+    let offset = parameter("offset").unwrap_or(0);
+    let limit = parameter("limit").unwrap_or(usize::MAX);
+    let mut state = lock(state);
+    if state.fail_from_offset.is_some_and(|fail_from_offset| offset >= fail_from_offset) {
+        state.queries.push(query);
+        let mut response = Response::new(Full::new(Bytes::from_static(b"bad request")));
+        *response.status_mut() = StatusCode::BAD_REQUEST;
+        return response;
+    }
+    let page: Vec<_> = state.listings.iter().skip(offset).take(limit).cloned().collect();
+    state.queries.push(query);
+    let body = json!({"listings": page});
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    response
+}
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use serde_json::Value;
 use url::Url;
 
 use crate::{
@@ -6,10 +7,45 @@ use crate::{
     prelude::*,
 };
 
+/// Search response, where each listing is parsed on its own.
+///
+/// A malformed listing is put aside into [`Listings::broken`] instead of failing the whole response.
 #[derive(Debug, Deserialize)]
+#[serde(from = "RawListings")]
 pub struct Listings {
-    #[serde(rename = "listings")]
     pub inner: Vec<Listing>,
+    pub broken: Vec<BrokenListing>,
+}
+
+#[derive(Deserialize)]
+struct RawListings {
+    listings: Vec<Value>,
+}
+
+impl From<RawListings> for Listings {
+    fn from(raw: RawListings) -> Self {
+        let mut this = Self { inner: Vec::with_capacity(raw.listings.len()), broken: Vec::new() };
+        for value in raw.listings {
+            let item_id = value.get("itemId").and_then(Value::as_str).map(ToString::to_string);
+            match serde_json::from_value(value) {
+                Ok(listing) => this.inner.push(listing),
+                Err(error) => this.broken.push(BrokenListing {
+                    item_id,
+                    error: Error::new(error).context("failed to deserialize the listing"),
+                }),
+            }
+        }
+        this
+    }
+}
+
+/// Listing, which could not be deserialized.
+#[derive(Debug)]
+pub struct BrokenListing {
+    /// Item ID, if the listing has one at all.
+    pub item_id: Option<String>,
+
+    pub error: Error,
 }
 
 impl IntoIterator for Listings {
@@ -54,12 +90,38 @@ pub struct Listing {
 
     #[serde(rename = "extendedAttributes", default)]
     pub extended_attributes: Vec<ExtendedAttribute>,
+
+    /// Paid placement of the listing.
+    #[serde(rename = "priorityProduct", default)]
+    pub priority_product: PriorityProduct,
 }
 
 impl Listing {
     pub fn brand(&self) -> Option<&str> {
         self.extended_attributes.iter().find_map(ExtendedAttribute::as_brand)
     }
+
+    /// Admarkt listings are posted by businesses via the paid advertising platform,
+    /// their IDs look like `a1506355752`.
+    pub fn is_admarkt(&self) -> bool {
+        self.item_id.starts_with('a')
+    }
+
+    /// Auction houses prefix their listing titles with «Veiling».
+    pub fn is_auction(&self) -> bool {
+        self.title.get(..7).is_some_and(|prefix| prefix.eq_ignore_ascii_case("veiling"))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub enum PriorityProduct {
+    #[default]
+    #[serde(rename = "NONE")]
+    None,
+
+    /// «Topadvertentie», «Dagtopper», and whatever else one can pay for.
+    #[serde(other)]
+    Promoted,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,6 +131,10 @@ pub struct Seller {
 
     #[serde(rename = "sellerName")]
     pub name: String,
+
+    /// Business sellers may link their website.
+    #[serde(rename = "sellerWebsiteUrl", default)]
+    pub website_url: Option<String>,
 }
 
 impl TryFrom<Seller> for crate::marketplace::item::Seller {
@@ -77,7 +143,12 @@ impl TryFrom<Seller> for crate::marketplace::item::Seller {
     fn try_from(seller: Seller) -> Result<Self> {
         let profile_url =
             Url::parse(&format!("https://www.marktplaats.nl/u/{}/{}/", seller.name, seller.id))?;
-        Ok(Self::builder().username(seller.name).profile_url(profile_url).build())
+        Ok(Self::builder()
+            .id(seller.id.into())
+            .username(seller.name)
+            .profile_url(profile_url)
+            .is_business(seller.website_url.is_some())
+            .build())
     }
 }
 
@@ -120,6 +191,13 @@ pub enum Price {
 
     #[serde(rename = "EXCHANGE")]
     Exchange,
+
+    /// Price type, which we do not know yet.
+    #[serde(untagged)]
+    Unknown {
+        #[serde(rename = "priceType")]
+        price_type: String,
+    },
 }
 
 impl From<Price> for crate::marketplace::item::Price {
@@ -134,6 +212,7 @@ impl From<Price> for crate::marketplace::item::Price {
             Price::FastBid => Self::FastBid,
             Price::Free => Self::Fixed(Amount::ZERO),
             Price::Exchange => Self::Exchange,
+            Price::Unknown { price_type } => Self::Unknown(price_type),
         }
     }
 }
@@ -218,16 +297,16 @@ pub enum ExtendedAttribute {
 }
 
 impl ExtendedAttribute {
-    pub const fn as_condition(&self) -> Option<Condition> {
+    pub fn as_condition(&self) -> Option<Condition> {
         match self {
-            Self::Condition(condition) => Some(*condition),
+            Self::Condition(condition) => Some(condition.clone()),
             _ => None,
         }
     }
 
-    pub const fn as_delivery(&self) -> Option<Delivery> {
+    pub fn as_delivery(&self) -> Option<Delivery> {
         match self {
-            Self::Delivery(delivery) => Some(*delivery),
+            Self::Delivery(delivery) => Some(delivery.clone()),
             _ => None,
         }
     }
@@ -240,7 +319,7 @@ impl ExtendedAttribute {
     }
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum Condition {
     #[serde(rename = "Nieuw")]
     New,
@@ -255,6 +334,10 @@ pub enum Condition {
     NotWorking,
 
     Refurbished,
+
+    /// Condition, which we do not know yet.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl From<Condition> for crate::marketplace::item::Condition {
@@ -265,11 +348,12 @@ impl From<Condition> for crate::marketplace::item::Condition {
             Condition::Used => Self::Used(crate::marketplace::item::Used::Unspecified),
             Condition::NotWorking => Self::Used(crate::marketplace::item::Used::NotFullyFunctional),
             Condition::Refurbished => Self::Refurbished,
+            Condition::Unknown(condition) => Self::Unknown(condition),
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub enum Delivery {
     #[serde(rename = "Ophalen")]
     CollectionOnly,
@@ -279,6 +363,10 @@ pub enum Delivery {
 
     #[serde(rename = "Ophalen of Verzenden")]
     CollectionOrShipping,
+
+    /// Delivery, which we do not know yet.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl From<Delivery> for crate::marketplace::item::Delivery {
@@ -287,6 +375,7 @@ impl From<Delivery> for crate::marketplace::item::Delivery {
             Delivery::CollectionOnly => Self::CollectionOnly,
             Delivery::ShippingOnly => Self::ShippingOnly,
             Delivery::CollectionOrShipping => Self::Both,
+            Delivery::Unknown(delivery) => Self::Unknown(delivery),
         }
     }
 }
@@ -310,6 +399,10 @@ impl TryFrom<Listing> for crate::marketplace::item::Item {
         } else {
             None
         };
+        let is_admarkt = listing.is_admarkt();
+        let is_auction = listing.is_auction();
+        let mut seller: crate::marketplace::item::Seller = listing.seller.try_into()?;
+        seller.is_business |= is_admarkt;
         Ok(Self::builder()
             .id(listing.item_id)
             .url(Url::parse(&format!("https://www.marktplaats.nl{}", listing.url_path))?)
@@ -318,9 +411,11 @@ impl TryFrom<Listing> for crate::marketplace::item::Item {
             .maybe_condition(condition.map(Into::into))
             .maybe_delivery(delivery.map(Into::into))
             .price(listing.price.into())
-            .seller(listing.seller.try_into()?)
+            .seller(seller)
             .maybe_location(listing.location.into())
             .maybe_picture_url(picture_url)
+            .is_promoted(listing.priority_product == PriorityProduct::Promoted)
+            .is_auction(is_auction)
             .build())
     }
 }
@@ -342,6 +437,8 @@ mod tests {
             Some(crate::marketplace::item::Condition::New(crate::marketplace::item::New::AsGood))
         );
         assert_eq!(item.delivery, Some(crate::marketplace::item::Delivery::Both));
+        assert!(!item.seller.is_business);
+        assert!(!item.is_auction);
         Ok(())
     }
 
@@ -392,10 +489,50 @@ mod tests {
 
     #[test]
     fn parse_listings_a1506355752() -> Result {
-        serde_json::from_str::<Listings>(
+        let mut listings = serde_json::from_str::<Listings>(
             // language=json
             r#"{"listings":[{"itemId":"a1506355752","title":"Veiling - TELESTAR EC 311 S Touch panel wifi Bluetooth and a","description":"De telestar ec 311 s is een driefasige wallbox en is geschikt voor het opladen van een elektrisch/hybride voertuig. Met een maxima","categorySpecificDescription":"De telestar ec 311 s is een driefasige wallbox en is geschikt voor het opladen van een elektrisch/hybride voertuig. Met een maximaal vermogen van 11 kw bij 16 a. De laadstatussen worden weergegeven op een 8,9 cm (3,5 inch) lcd-kleurendisplay (touchsc...","thinContent":false,"priceInfo":{"priceCents":4000,"priceType":"FIXED"},"location":{"distanceMeters":-1000,"isBuyerLocation":false,"onCountryLevel":true,"abroad":false,"latitude":0,"longitude":0},"date":"Vandaag","imageUrls":["//mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_82.JPG"],"sellerInformation":{"sellerId":49797653,"sellerName":"FlimDeal","showSoiUrl":true,"showWebsiteUrl":true,"sellerWebsiteUrl":"https://admarkt.marktplaats.nl/bside/url/Xdktr22Y0uHWmrqiFAkc4QCts98ouPSVwEP3p-SyO-ohtqDp6o9G9Ws-cE3x4H_rTiDOLkkw5LSFIoD-dV_dsoai6R8KHgSpvJAntXXBVCch6k7HxMQn4r6XXbKkAZ7KRdnLtlY46CwPVJslEq8mb9z3_yOnm0dTKq-EyT8Er6VIduvcDpfNl86_yHiSSkZ3-Gq6nctSe9c4Jrk3TpuKYk8EmlCLs8LIM6wxSPu7NNcn5ypmbA8N8DefSynjiONn0cAaFbzcxWhelD00heBkW5XE15qU5Dm3a0_a-0Vyx5hjg1NTv_jFohLXAfDldpEBSymvVvLL38uZfYyCdw5S3c1MpkepsO1IIk0U5FD5ZpnPjb-JwG1oAFy1Sk2KZRwG8cn3QLa3jVnwTidyimevYi0bQtguVP7A_7KcJdgBRcJ5w7cpQeYvGJypXxp91tkcvJ1nnESHlZ8bmN8dRgsaZ6KHac7_g70dcdMpkgRPz-sJ5NPE4C06tTqFBPW_jlTkwKPOndiO2jvmhauhymMK9P4liLA8DdnXOY-xMKZgLthcFcodxoTH71uVDEBE8BC1VlqU-hgn4GY3Z-QaIRnuCEUEG4VtsnHMbbcIVAliKLVohPtcfnafKfD4ofhQLFLL07PjChhcMVutoyc4yVVYJj3mce5vtb_Z-5rRan2lIIJcp6r3wCnfh6mDB9mIhunxngNpK9D9D88o7QBIELSz4P_SkcNf04X4uMRrlq57ueU-geJcVO-9QCaq1vKXp_ZvDDbFFxumq7tafVhI-mY_f85WBBqLX4mE8-sn0NASReTjcpzNKaxzLmmBpe0GL0Z7ho6p03JGg7O_A3mCFvbORFfANqi1RSaZbjunW5jl2BEM0_mM1o5bV2PO_B1xPvcI0GSNvsyHvuAFDBuO1ePOusz7fAFe3RYC_0yyolotH8on_FziRRS2IYw6wlhyBwme","isVerified":false},"categoryId":3071,"trackingData":"5rhgoHesYogOfMniKGDpPmroOLHD5_TrGD_zHj0OBdXv8noAjUf7oEjaevcxG8QOEt9HeP26cSlAstGZEns1iKlIOLkNsmPVW-ZWHxr3C5t3fR3j7PfHTM5zikU3zXWhC9bSEGU6R8SlGvUQ3If7I_tr0Kgl2g9qMbHqzrmvCN9C4RQQmT7eJfGvBlzw6LEGgGQMLmBBqfkHh6pXPKwjLSmbFRaTDohDM1C_krUUsjpgA-a_pC4E8OX83_KPD3k78z9mxzigL7b-08vkhx9QyEKuaSYpirIDORkqZXGt1fNyqKFFUapjLgSAQp3CWZlWDnOXuDTPjQ9R3dfR8j_MzzBvtpabAkemGwlTdbTNjuqqJLzp0bMbTSMiZA4VIchK2yHnBYnLoOAtdDgMR7U6_y1GSMTiEokJBp-NldNpa2ztvgPBPZVi9a10a-UJxw6jA0fqzWVCHQgHSsOOq91UqPiFO4PKGMF5VKpFJSJvm2DZcsTZF3VnYmAqane-b5IqaiDjXMdhUa_qITHZVTvjAd6T_dFNdtHtc-BCray0ZBA","pageLocation":"L0_SEARCH","priorityProduct":"NONE","videoOnVip":false,"urgencyFeatureActive":false,"napAvailable":false,"attributes":[{"key":"condition","value":"Nieuw","values":["Nieuw"]}],"extendedAttributes":[{"key":"condition","value":"Nieuw","values":["Nieuw"]}],"traits":["ADMARKT_CONSOLE","PROFILE"],"verticals":["charging_stations","car_various"],"pictures":[{"id":0,"mediaId":"00000000-0000-0000-0000-000000000000","url":"https://mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_#.JPG","sizes":{"XL":"84","M":"82","XXXL":"86","L":"83","XXL":"85","XXS":"14","XS":"14","S":"82"},"extraSmallUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_14.JPG","mediumUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_82.JPG","largeUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_83.JPG","extraExtraLargeUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/af/af2489db-337d-4497-98c8-9368adde100c?rule=eps_85.JPG","aspectRatio":{"width":4,"height":3}}],"vipUrl":"/v/auto-diversen/laadpalen/a1506355752-veiling-telestar-ec-311-s-touch-panel-wifi-bluetooth-and-a"}],"topBlock":[{"itemId":"a1487129509","title":"Spy camera HD Klok WiFi","description":"Hd 1080p klok wifi camera mini home security ip p2p bewakingscamera infrarood nachtzicht motion remote camera verborgen camera een","categorySpecificDescription":"Hd 1080p klok wifi camera mini home security ip p2p bewakingscamera infrarood nachtzicht motion remote camera verborgen camera een spycam die er uitziet als een doodnormale wekker. Zoals je die in elke nederlandse slaapkamer ziet. In de praktijk is h...","thinContent":false,"priceInfo":{"priceCents":9900,"priceType":"FIXED"},"location":{"cityName":"Zevenhuizen","distanceMeters":-1000,"isBuyerLocation":false,"onCountryLevel":false,"abroad":false,"latitude":51.973262786865234,"longitude":4.556851863861084},"date":"Vandaag","imageUrls":["//mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_82.JPG"],"sellerInformation":{"sellerId":9208018,"sellerName":"megaspullen.nl","showSoiUrl":true,"showWebsiteUrl":true,"sellerWebsiteUrl":"https://admarkt.marktplaats.nl/bside/url/z2gV2JJ_W4NpMqR72h29HY2v7nNDjyoz1LVWNAzBfjtvu0cxP5ia23ytq-KEfjZRly9s70xA6wlnt1gG2GMEofH94BsczlW3x5AThynBKgRhxfFLMlT_j1hszIS3gp7UNjLAgyAwRyCMZKWhNbDsVhjmxjEADPq6qUGQgA_AhuExzj_r2IMTK2fx_OyhOYoDUR5R-aW7tE7uyfeEHmC-7KqHQhYoylKrB8LE9KUomb3JQRuv2139dPK6jYXgT7l8mI9NvGEk2lTbNv4cZ1dh3O6nMfhVfMIhcduHOd2C0ztQ1CjVWJL4Nre6zmFRtx1eUZFAk5u9N0aM8hwIjegP9iTW5Xcdl0brmTsg-noPexZQVWwMUUsd5CUJPChOJxoCBqzhOpPgoCRSzXYUrk8BOV8pKCcYtQyh9Mdpdx1InSCFKNIdxAYt9gp42l8l9nHl3T0hGZ8I9d-NS_nl6wBbLonsVjogfIhP6fNIwV_ER9OQtsFyGGN2gaJZs2wIlu1t9mIrG47jUG0a7f7Lqz0bGrIm78fCpacQxcjRSQ4nUE8yMzSDWlC8nb2Qs68CYxFFfR8nfHtLfKtKSWgxsv66GMr5r6tbsDu1b-L9IKDGIM_IOLFLwJGCzaB-SswizdZuWnZZF1KM6C04KIZmHtcJ55XuAhYDyaKmVHSyjwrEYgm-UxjWrIcX5cc6T02TbEH6W2YapxQ2Fi1dFi_Fyfk1qVlsCC-T4XzgAV0Tr_cUzvD2t8Nm45qmNtGPeTqGkaVd9pgDi9f09M3_8cSNDWvQi2Eh_LCpTQCkh_enc2kby-zz6kTgDIqDN4a5ruCJg37mpFTx2Akm5xBFMtK3X39q_d3pOZIjseiKx3ugOia2lPBFrl0bJnSO5rUCJeYfs0v56UEbsQvukeIORHnyKOJ_8DsV2gGMlKq3aaj3YiggekQfeE3lULdsLCQoQJZtlGKN","isVerified":false},"categoryId":1129,"trackingData":"Jncntp7C_HgkL_worUaP27Y16aWaas7hcwrowUnPF_BWfgN-lbU9EPP_6VwjIJjBQkvxRo_uq9aeGfUah-krVovzY6Pq5D4IwhpupBhH1mIT6Riq_SuMxhBhAKcb_6LhBL5Giyy-cic0YJ8pZxL3aqSVWlvBWaXuUpfPy-Km4Sgdz3lHg9HoRn57ja_uZf7QhgRXkqnm4IrsYzcEB4O-ux9v1mgBIX4FV6Ed5JaZkE8Mr4Q41e6xsudvPreQne-_ywhMgYGw1DEZRh0b-sjTurDoRRZa9pTapRIqzd3SG3LX5_zk81ghjIBwSWudZNmWxrFcv8Mph4bQARXkpi38rx93PWAQm9Y_PyMglu9_xd8F97EgywlNUVckQIki2KImwWKF87dPAbNTBOBNmei0TXP9hxZvQh1MwCYeOOCHTv-rv-k4QSOgZpYT2uS0kXXHNqX2vFkPAKsaf-8u2Vo5K4oXg0SkMqG0eTQWt8e5Fz1sTsdlhbf2vxIWWJ7EvLQAVDDkaGr3DXbpSvtzNwM94LUIF9gTzW0K2sva4t2Xq4k","pageLocation":"L0_SEARCH_TOP","priorityProduct":"TOPADVERTENTIE","videoOnVip":false,"urgencyFeatureActive":false,"napAvailable":false,"attributes":[{"key":"condition","value":"Nieuw","values":["Nieuw"]},{"key":"delivery","value":"Ophalen of Verzenden","values":["Ophalen of Verzenden"]}],"extendedAttributes":[{"key":"delivery","value":"Ophalen of Verzenden","values":["Ophalen of Verzenden"]},{"key":"condition","value":"Nieuw","values":["Nieuw"]}],"traits":["PROFILE","ADMARKT_CONSOLE","NO_MARKETING"],"verticals":["video_surveillance_equipment","barcode-supported","audio_tv_photo"],"pictures":[{"id":0,"mediaId":"00000000-0000-0000-0000-000000000000","url":"https://mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_#.JPG","sizes":{"XL":"84","M":"82","XXXL":"86","L":"83","XXL":"85","XXS":"14","XS":"14","S":"82"},"extraSmallUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_14.JPG","mediumUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_82.JPG","largeUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_83.JPG","extraExtraLargeUrl":"https://mp.images.icas.io/api/v1/a6519ad0/images/03/03790313-ab9a-4fda-99bf-fef8dcc97f4e?rule=eps_85.JPG","aspectRatio":{"width":1,"height":1}}],"vipUrl":"/v/audio-tv-en-foto/videobewaking/a1487129509-spy-camera-hd-klok-wifi"}],"facets":[{"key":"PriceCents","type":"AttributeRangeFacet"},{"key":"RelevantCategories","type":"CategoryTreeFacet","categories":[{"id":322,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Computers en Software","key":"computers-en-software","parentId":null,"parentKey":false},{"id":3022,"histogramCount":166,"selected":false,"isValuableForSeo":true,"dominant":true,"label":"Accesspoints","key":"accesspoints","parentId":322,"parentKey":"computers-en-software"},{"id":338,"histogramCount":199,"selected":false,"isValuableForSeo":true,"dominant":true,"label":"Netwerk switches","key":"netwerk-switches","parentId":322,"parentKey":"computers-en-software"},{"id":334,"histogramCount":234,"selected":false,"isValuableForSeo":true,"dominant":true,"label":"Routers en Modems","key":"routers-en-modems","parentId":322,"parentKey":"computers-en-software"},{"id":504,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Huis en Inrichting","key":"huis-en-inrichting","parentId":null,"parentKey":false},{"id":2762,"histogramCount":285,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Spots","key":"lampen-spots","parentId":504,"parentKey":"huis-en-inrichting"},{"id":239,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Doe-het-zelf en Verbouw","key":"doe-het-zelf-en-verbouw","parentId":null,"parentKey":false},{"id":266,"histogramCount":195,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Sanitair","key":"sanitair","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":31,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Audio, Tv en Foto","key":"audio-tv-en-foto","parentId":null,"parentKey":false},{"id":1129,"histogramCount":184,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Videobewaking","key":"videobewaking","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1622,"histogramCount":167,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Wandlampen","key":"lampen-wandlampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":1847,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Tuin en Terras","key":"tuin-en-terras","parentId":null,"parentKey":false},{"id":281,"histogramCount":167,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Buitenverlichting","key":"buitenverlichting","parentId":1847,"parentKey":"tuin-en-terras"},{"id":1258,"histogramCount":154,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Hanglampen","key":"lampen-hanglampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":1867,"histogramCount":153,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Elektra en Kabels","key":"elektra-en-kabels","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":1259,"histogramCount":140,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Vloerlampen","key":"lampen-vloerlampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":48,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Auto diversen","key":"auto-diversen","parentId":null,"parentKey":false},{"id":60,"histogramCount":96,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Autogereedschap","key":"autogereedschap","parentId":48,"parentKey":"auto-diversen"},{"id":1265,"histogramCount":96,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Overige","key":"lampen-overige","parentId":504,"parentKey":"huis-en-inrichting"},{"id":328,"histogramCount":90,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Desktop Pc's","key":"desktop-pc-s","parentId":322,"parentKey":"computers-en-software"},{"id":2760,"histogramCount":88,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Losse lampen","key":"lampen-losse-lampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":1099,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Hobby en Vrije tijd","key":"hobby-en-vrije-tijd","parentId":null,"parentKey":false},{"id":1398,"histogramCount":81,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Elektronica-componenten","key":"elektronica-componenten","parentId":1099,"parentKey":"hobby-en-vrije-tijd"},{"id":3068,"histogramCount":75,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Dashcams","key":"dashcams","parentId":48,"parentKey":"auto-diversen"},{"id":335,"histogramCount":73,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Moederborden","key":"moederborden","parentId":322,"parentKey":"computers-en-software"},{"id":1098,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Diensten en Vakmensen","key":"diensten-en-vakmensen","parentId":null,"parentKey":false},{"id":1195,"histogramCount":67,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Computer en Internet experts","key":"computer-en-internet-experts","parentId":1098,"parentKey":"diensten-en-vakmensen"},{"id":2761,"histogramCount":66,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Plafondlampen","key":"lampen-plafondlampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":395,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Dieren en Toebehoren","key":"dieren-en-toebehoren","parentId":null,"parentKey":false},{"id":396,"histogramCount":59,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Vissen | Aquaria en Toebehoren","key":"vissen-aquaria-en-toebehoren","parentId":395,"parentKey":"dieren-en-toebehoren"},{"id":2722,"histogramCount":51,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Apple iPads","key":"apple-ipads","parentId":322,"parentKey":"computers-en-software"},{"id":537,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Witgoed en Apparatuur","key":"witgoed-en-apparatuur","parentId":null,"parentKey":false},{"id":561,"histogramCount":45,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Airco's","key":"airco-s","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":2844,"histogramCount":41,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Android Tablets","key":"android-tablets","parentId":322,"parentKey":"computers-en-software"},{"id":282,"histogramCount":41,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Verwarming en Radiatoren","key":"verwarming-en-radiatoren","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":342,"histogramCount":36,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Printers","key":"printers","parentId":322,"parentKey":"computers-en-software"},{"id":2723,"histogramCount":35,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Windows Tablets","key":"windows-tablets","parentId":322,"parentKey":"computers-en-software"},{"id":341,"histogramCount":33,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Computers en Software","key":"overige-computers-en-software","parentId":322,"parentKey":"computers-en-software"},{"id":1658,"histogramCount":31,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Pc- en Netwerkkabels","key":"pc-en-netwerkkabels","parentId":322,"parentKey":"computers-en-software"},{"id":487,"histogramCount":20,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Fotocamera's Digitaal","key":"fotocamera-s-digitaal","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":3127,"histogramCount":19,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Deurbellen","key":"deurbellen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":3057,"histogramCount":18,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Drones","key":"drones","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1260,"histogramCount":17,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Tafellampen","key":"lampen-tafellampen","parentId":504,"parentKey":"huis-en-inrichting"},{"id":339,"histogramCount":17,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Windows Laptops","key":"windows-laptops","parentId":322,"parentKey":"computers-en-software"},{"id":43,"histogramCount":17,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Radio's","key":"radio-s","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":262,"histogramCount":17,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Doe-het-zelf en Verbouw","key":"overige-doe-het-zelf-en-verbouw","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":38,"histogramCount":16,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Luidsprekers","key":"luidsprekers","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":3024,"histogramCount":13,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"WiFi-versterkers","key":"wifi-versterkers","parentId":322,"parentKey":"computers-en-software"},{"id":3015,"histogramCount":13,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Netwerkkaarten","key":"netwerkkaarten","parentId":322,"parentKey":"computers-en-software"},{"id":1866,"histogramCount":13,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Alarmsystemen","key":"alarmsystemen","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":544,"histogramCount":11,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Koelkasten en IJskasten","key":"koelkasten-en-ijskasten","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":1131,"histogramCount":10,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Videocamera's Digitaal","key":"videocamera-s-digitaal","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":565,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kinderen en Baby's","key":"kinderen-en-baby-s","parentId":null,"parentKey":false},{"id":567,"histogramCount":10,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Babyfoons","key":"babyfoons","parentId":565,"parentKey":"kinderen-en-baby-s"},{"id":3105,"histogramCount":9,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Thermostaten","key":"thermostaten","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":976,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Watersport en Boten","key":"watersport-en-boten","parentId":null,"parentKey":false},{"id":978,"histogramCount":9,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Bootonderdelen","key":"bootonderdelen","parentId":976,"parentKey":"watersport-en-boten"},{"id":3053,"histogramCount":8,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Soundbars","key":"soundbars","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":336,"histogramCount":7,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Monitoren","key":"monitoren","parentId":322,"parentKey":"computers-en-software"},{"id":2834,"histogramCount":7,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Actiecamera's","key":"actiecamera-s","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1418,"histogramCount":7,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"USB Sticks","key":"usb-sticks","parentId":322,"parentKey":"computers-en-software"},{"id":41,"histogramCount":7,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Audio, Tv en Foto","key":"overige-audio-tv-en-foto","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":556,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Stofzuigers","key":"stofzuigers","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":728,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Muziek en Instrumenten","key":"muziek-en-instrumenten","parentId":null,"parentKey":false},{"id":2135,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kabels en Stekkers","key":"kabels-en-stekkers","parentId":728,"parentKey":"muziek-en-instrumenten"},{"id":289,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Caravans en Kamperen","key":"caravans-en-kamperen","parentId":null,"parentKey":false},{"id":316,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Caravans en Kamperen","key":"overige-caravans-en-kamperen","parentId":289,"parentKey":"caravans-en-kamperen"},{"id":1,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Antiek en Kunst","key":"antiek-en-kunst","parentId":null,"parentKey":false},{"id":7,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Antiek | Lampen","key":"antiek-lampen","parentId":1,"parentKey":"antiek-en-kunst"},{"id":1400,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Fotografie | Fotostudio en Toebehoren","key":"fotografie-fotostudio-en-toebehoren","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1085,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Zakelijke goederen","key":"zakelijke-goederen","parentId":null,"parentKey":false},{"id":2603,"histogramCount":6,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kantoor en Winkelinrichting | Kassa's en Betaalsystemen","key":"kantoor-en-winkelinrichting-kassa-s-en-betaalsystemen","parentId":1085,"parentKey":"zakelijke-goederen"},{"id":333,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Harde schijven","key":"harde-schijven","parentId":322,"parentKey":"computers-en-software"},{"id":354,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Webcams","key":"webcams","parentId":322,"parentKey":"computers-en-software"},{"id":3169,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Waterontharders","key":"waterontharders","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":2622,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Zonnepanelen en Toebehoren","key":"zonnepanelen-en-toebehoren","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":1826,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Sieraden, Tassen en Uiterlijk","key":"sieraden-tassen-en-uiterlijk","parentId":null,"parentKey":false},{"id":3041,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Smartwatches","key":"smartwatches","parentId":1826,"parentKey":"sieraden-tassen-en-uiterlijk"},{"id":247,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Gereedschap | Handgereedschap","key":"gereedschap-handgereedschap","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":988,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Navigatiemiddelen en Scheepselektronica","key":"navigatiemiddelen-en-scheepselektronica","parentId":976,"parentKey":"watersport-en-boten"},{"id":356,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Spelcomputers en Games","key":"spelcomputers-en-games","parentId":null,"parentKey":false},{"id":1655,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Spelcomputers | Nintendo DS","key":"spelcomputers-nintendo-ds","parentId":356,"parentKey":"spelcomputers-en-games"},{"id":621,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kleding | Dames","key":"kleding-dames","parentId":null,"parentKey":false},{"id":625,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Schoenen","key":"schoenen","parentId":621,"parentKey":"kleding-dames"},{"id":54,"histogramCount":5,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Auto-accessoires","key":"auto-accessoires","parentId":48,"parentKey":"auto-diversen"},{"id":1944,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Lampen | Kroonluchters","key":"lampen-kroonluchters","parentId":504,"parentKey":"huis-en-inrichting"},{"id":372,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kantoor en Winkelinrichting | Computer en IT","key":"kantoor-en-winkelinrichting-computer-en-it","parentId":1085,"parentKey":"zakelijke-goederen"},{"id":513,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kachels","key":"kachels","parentId":504,"parentKey":"huis-en-inrichting"},{"id":598,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kinderkleding | Schoenen en Sokken","key":"kinderkleding-schoenen-en-sokken","parentId":565,"parentKey":"kinderen-en-baby-s"},{"id":553,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Witgoed en Apparatuur","key":"overige-witgoed-en-apparatuur","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":2600,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Auto-onderdelen","key":"auto-onderdelen","parentId":null,"parentKey":false},{"id":2905,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Accu's en Toebehoren","key":"accu-s-en-toebehoren","parentId":2600,"parentKey":"auto-onderdelen"},{"id":2974,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Tuinsproeiers","key":"tuinsproeiers","parentId":1847,"parentKey":"tuin-en-terras"},{"id":3071,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Laadpalen","key":"laadpalen","parentId":48,"parentKey":"auto-diversen"},{"id":1090,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Horeca | Keukenapparatuur","key":"horeca-keukenapparatuur","parentId":1085,"parentKey":"zakelijke-goederen"},{"id":251,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Schuttingen","key":"schuttingen","parentId":1847,"parentKey":"tuin-en-terras"},{"id":274,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Tuin en Terras","key":"overige-tuin-en-terras","parentId":1847,"parentKey":"tuin-en-terras"},{"id":895,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Verzamelen","key":"verzamelen","parentId":null,"parentKey":false},{"id":927,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Verzamelen","key":"overige-verzamelen","parentId":895,"parentKey":"verzamelen"},{"id":1118,"histogramCount":4,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Schotelantennes","key":"schotelantennes","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1417,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Servers","key":"servers","parentId":322,"parentKey":"computers-en-software"},{"id":820,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Telecommunicatie","key":"telecommunicatie","parentId":null,"parentKey":false},{"id":1454,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Datacommunicatie en VoIP","key":"datacommunicatie-en-voip","parentId":820,"parentKey":"telecommunicatie"},{"id":2617,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Afstandsbedieningen","key":"afstandsbedieningen","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":246,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Geisers en Boilers","key":"geisers-en-boilers","parentId":239,"parentKey":"doe-het-zelf-en-verbouw"},{"id":1725,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Weerstations en Barometers","key":"weerstations-en-barometers","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":2667,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Fotografie | Digitale fotolijsten","key":"fotografie-digitale-fotolijsten","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":315,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Kampeeraccessoires","key":"kampeeraccessoires","parentId":289,"parentKey":"caravans-en-kamperen"},{"id":564,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Weegschalen","key":"weegschalen","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":2668,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Mediaspelers","key":"mediaspelers","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":428,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Diversen","key":"diversen","parentId":null,"parentKey":false},{"id":440,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Diversen","key":"overige-diversen","parentId":428,"parentKey":"diversen"},{"id":2875,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Woonaccessoires | Wanddecoraties","key":"woonaccessoires-wanddecoraties","parentId":504,"parentKey":"huis-en-inrichting"},{"id":1629,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Spelcomputers | Xbox 360","key":"spelcomputers-xbox-360","parentId":356,"parentKey":"spelcomputers-en-games"},{"id":526,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Overige Huis en Inrichting","key":"overige-huis-en-inrichting","parentId":504,"parentKey":"huis-en-inrichting"},{"id":1132,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Beamers","key":"beamers","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":53,"histogramCount":3,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Autonavigatie","key":"autonavigatie","parentId":48,"parentKey":"auto-diversen"},{"id":3038,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Dockingstations","key":"dockingstations","parentId":322,"parentKey":"computers-en-software"},{"id":850,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Telefooncentrales","key":"telefooncentrales","parentId":820,"parentKey":"telecommunicatie"},{"id":46,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Versterkers en Receivers","key":"versterkers-en-receivers","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":1453,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Televisiebeugels","key":"televisiebeugels","parentId":31,"parentKey":"audio-tv-en-foto"},{"id":552,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Ovens","key":"ovens","parentId":537,"parentKey":"witgoed-en-apparatuur"},{"id":754,"histogramCount":2,"selected":false,"isValuableForSeo":true,"dominant":false,"label":"Licht en Laser","key":"licht-en-laser","parentId":728,"parentKey":"muziek-en-instrumenten"}]},{"id":1627,"key":"condition","type":"AttributeGroupFacet","label":"Conditie","attributeGroup":[{"attributeValueKey":"Nieuw","attributeValueId":30,"attributeValueLabel":"Nieuw","histogramCount":2936,"selected":false,"isValuableForSeo":false},{"attributeValueKey":"Refurbished","attributeValueId":14050,"attributeValueLabel":"Refurbished","histogramCount":14,"selected":false,"isValuableForSeo":false},{"attributeValueKey":"Zo goed als nieuw","attributeValueId":31,"attributeValueLabel":"Zo goed als nieuw","histogramCount":405,"selected":false,"isValuableForSeo":false},{"attributeValueKey":"Gebruikt","attributeValueId":32,"attributeValueLabel":"Gebruikt","histogramCount":230,"selected":false,"isValuableForSeo":false},{"attributeValueKey":"Niet werkend","attributeValueId":13940,"attributeValueLabel":"Niet werkend","selected":false,"isValuableForSeo":false}],"singleSelect":false,"categoryId":0},{"id":2947,"key":"buyitnow","type":"AttributeGroupFacet","label":"Direct Kopen","attributeGroup":[{"attributeValueKey":"Direct Kopen","attributeValueId":14055,"attributeValueLabel":"Direct Kopen","histogramCount":57,"selected":false,"isValuableForSeo":false}],"singleSelect":false,"categoryId":0},{"id":8,"key":"delivery","type":"AttributeGroupFacet","label":"Levering","attributeGroup":[{"attributeValueKey":"Ophalen","attributeValueId":33,"attributeValueLabel":"Ophalen","histogramCount":549,"selected":false,"isValuableForSeo":false},{"attributeValueKey":"Verzenden","attributeValueId":34,"attributeValueLabel":"Verzenden","histogramCount":521,"selected":false,"isValuableForSeo":false}],"singleSelect":false,"categoryId":0},{"id":987654321,"key":"offeredSince","type":"AttributeGroupFacet","label":"Aangeboden sinds","attributeGroup":[{"attributeValueKey":"Vandaag","selected":false,"isValuableForSeo":false,"default":false},{"attributeValueKey":"Gisteren","histogramCount":27,"selected":false,"isValuableForSeo":false,"default":false},{"attributeValueKey":"Een week","histogramCount":137,"selected":false,"isValuableForSeo":false,"default":false},{"attributeValueKey":"Altijd","histogramCount":615,"selected":true,"isValuableForSeo":false,"default":true}],"singleSelect":true,"categoryId":0}],"totalResultCount":3710,"maxAllowedPageNumber":3711,"correlationId":"1e1eb6a1-f1f6-4fd9-88e9-ec95ff3823bc","originalQuery":"unifi","suggestedSearches":[{"filters":{"query":{"text":"ubiquiti","displayText":"ubiquiti"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi switch","displayText":"unifi switch"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"ubiquiti unifi","displayText":"ubiquiti unifi"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi u6","displayText":"unifi u6"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi cloud key","displayText":"unifi cloud key"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi camera","displayText":"unifi camera"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi dream machine","displayText":"unifi dream machine"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi nanohd","displayText":"unifi nanohd"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi gateway","displayText":"unifi gateway"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi ap","displayText":"unifi ap"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi g4","displayText":"unifi g4"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi doorbell","displayText":"unifi doorbell"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"hikvision","displayText":"hikvision"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"dream machine","displayText":"dream machine"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi ac lite","displayText":"unifi ac lite"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"udm","displayText":"udm"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"dream machine pro","displayText":"dream machine pro"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi poe switch","displayText":"unifi poe switch"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"unifi express","displayText":"unifi express"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}},{"filters":{"query":{"text":"u6 pro","displayText":"u6 pro"},"categories":[{"id":0,"categoryName":"","parentId":0,"parentName":""}]}}],"sortOptions":[{"sortBy":"OPTIMIZED","sortOrder":"DECREASING"},{"sortBy":"SORT_INDEX","sortOrder":"DECREASING"},{"sortBy":"SORT_INDEX","sortOrder":"INCREASING"},{"sortBy":"PRICE","sortOrder":"INCREASING"},{"sortBy":"PRICE","sortOrder":"DECREASING"}],"isSearchSaved":false,"hasErrors":false,"alternativeLocales":[],"searchRequest":{"originalRequest":{"categories":{},"searchQuery":"unifi","attributes":{},"attributesById":[],"attributesByKey":[],"attributeRanges":[],"attributeLabels":[],"sortOptions":{"sortBy":"","sortOrder":"","sortAttribute":""},"pagination":{"offset":0,"limit":1},"distance":{"postcode":""},"viewOptions":{"kind":"list-view"},"bypassSpellingSuggestion":false},"categories":{},"searchQuery":"unifi","attributes":{},"attributesById":[],"attributesByKey":[],"attributeRanges":[],"attributeLabels":[],"sortOptions":{"sortBy":"","sortOrder":"","sortAttribute":""},"pagination":{"offset":0,"limit":1},"distance":{"postcode":""},"viewOptions":{"kind":"list-view"},"bypassSpellingSuggestion":false},"searchCategory":0,"searchCategoryOptions":[{"fullName":"Antiek en Kunst","id":1,"key":"antiek-en-kunst","name":"Antiek en Kunst"},{"fullName":"Audio, Tv en Foto","id":31,"key":"audio-tv-en-foto","name":"Audio, Tv en Foto"},{"fullName":"Auto's","id":91,"key":"auto-s","name":"Auto's"},{"fullName":"Auto-onderdelen","id":2600,"key":"auto-onderdelen","name":"Auto-onderdelen"},{"fullName":"Auto diversen","id":48,"key":"auto-diversen","name":"Auto diversen"},{"fullName":"Boeken","id":201,"key":"boeken","name":"Boeken"},{"fullName":"Caravans en Kamperen","id":289,"key":"caravans-en-kamperen","name":"Caravans en Kamperen"},{"fullName":"Cd's en Dvd's","id":1744,"key":"cd-s-en-dvd-s","name":"Cd's en Dvd's"},{"fullName":"Computers en Software","id":322,"key":"computers-en-software","name":"Computers en Software"},{"fullName":"Contacten en Berichten","id":378,"key":"contacten-en-berichten","name":"Contacten en Berichten"},{"fullName":"Diensten en Vakmensen","id":1098,"key":"diensten-en-vakmensen","name":"Diensten en Vakmensen"},{"fullName":"Dieren en Toebehoren","id":395,"key":"dieren-en-toebehoren","name":"Dieren en Toebehoren"},{"fullName":"Doe-het-zelf en Verbouw","id":239,"key":"doe-het-zelf-en-verbouw","name":"Doe-het-zelf en Verbouw"},{"fullName":"Fietsen en Brommers","id":445,"key":"fietsen-en-brommers","name":"Fietsen en Brommers"},{"fullName":"Hobby en Vrije tijd","id":1099,"key":"hobby-en-vrije-tijd","name":"Hobby en Vrije tijd"},{"fullName":"Huis en Inrichting","id":504,"key":"huis-en-inrichting","name":"Huis en Inrichting"},{"fullName":"Huizen en Kamers","id":1032,"key":"huizen-en-kamers","name":"Huizen en Kamers"},{"fullName":"Kinderen en Baby's","id":565,"key":"kinderen-en-baby-s","name":"Kinderen en Baby's"},{"fullName":"Kleding | Dames","id":621,"key":"kleding-dames","name":"Kleding | Dames"},{"fullName":"Kleding | Heren","id":1776,"key":"kleding-heren","name":"Kleding | Heren"},{"fullName":"Motoren","id":678,"key":"motoren","name":"Motoren"},{"fullName":"Muziek en Instrumenten","id":728,"key":"muziek-en-instrumenten","name":"Muziek en Instrumenten"},{"fullName":"Postzegels en Munten","id":1784,"key":"postzegels-en-munten","name":"Postzegels en Munten"},{"fullName":"Sieraden, Tassen en Uiterlijk","id":1826,"key":"sieraden-tassen-en-uiterlijk","name":"Sieraden en Tassen"},{"fullName":"Spelcomputers en Games","id":356,"key":"spelcomputers-en-games","name":"Spelcomputers, Games"},{"fullName":"Sport en Fitness","id":784,"key":"sport-en-fitness","name":"Sport en Fitness"},{"fullName":"Telecommunicatie","id":820,"key":"telecommunicatie","name":"Telecommunicatie"},{"fullName":"Tickets en Kaartjes","id":1984,"key":"tickets-en-kaartjes","name":"Tickets en Kaartjes"},{"fullName":"Tuin en Terras","id":1847,"key":"tuin-en-terras","name":"Tuin en Terras"},{"fullName":"Vacatures","id":167,"key":"vacatures","name":"Vacatures"},{"fullName":"Vakantie","id":856,"key":"vakantie","name":"Vakantie"},{"fullName":"Verzamelen","id":895,"key":"verzamelen","name":"Verzamelen"},{"fullName":"Watersport en Boten","id":976,"key":"watersport-en-boten","name":"Watersport en Boten"},{"fullName":"Witgoed en Apparatuur","id":537,"key":"witgoed-en-apparatuur","name":"Witgoed en Apparatuur"},{"fullName":"Zakelijke goederen","id":1085,"key":"zakelijke-goederen","name":"Zakelijke goederen"},{"fullName":"Diversen","id":428,"key":"diversen","name":"Diversen"}],"seoFriendlyAttributes":[],"seoFriendlyTextAttributes":{},"attributeHierarchy":{"offeredSince":[{"attributeValueId":null,"attributeValueLabel":null,"attributeValueKey":"Altijd","attributeLabel":"Aangeboden sinds","isDefault":true}]},"categoriesById":{},"metaTags":{"metaTitle":"≥ Vind unifi op Marktplaats - oktober 2024","metaDescription":"3.710 aanbiedingen in oktober - Koop en verkoop unifi eenvoudig op Marktplaats ✅ Lokale aanbiedingen - Ga ervoor!","pageTitleH1":"<span>Je hebt gezocht op </span><h1>unifi</h1>."}}"#,
         )?;
+        let item: Item = listings.inner.pop().unwrap().try_into()?;
+        assert!(item.seller.is_business);
+        assert!(item.is_auction);
+        assert!(!item.is_promoted);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_unknown_variants_ok() -> Result {
+        let listings = serde_json::from_str::<Listings>(
+            // language=json
+            r#"{"listings":[{"itemId":"m1","title":"Fiets","description":"Fiets","priceInfo":{"priceType":"SWAP_SHOP","priceCents":0},"vipUrl":"/v/fietsen/m1-fiets","sellerInformation":{"sellerId":1,"sellerName":"Seller"},"location":{"cityName":"Amsterdam"},"extendedAttributes":[{"key":"condition","value":"Als een ruïne"},{"key":"delivery","value":"Per drone"}]}]}"#,
+        )?;
+        assert!(listings.broken.is_empty(), "{:?}", listings.broken);
+        let item: Item = listings.into_iter().next().unwrap().try_into()?;
+        assert!(
+            matches!(&item.price, crate::marketplace::item::Price::Unknown(price_type) if price_type == "SWAP_SHOP")
+        );
+        assert_eq!(
+            item.condition,
+            Some(crate::marketplace::item::Condition::Unknown("Als een ruïne".to_string()))
+        );
+        assert_eq!(
+            item.delivery,
+            Some(crate::marketplace::item::Delivery::Unknown("Per drone".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_broken_listing_ok() -> Result {
+        let listings = serde_json::from_str::<Listings>(
+            // language=json
+            r#"{"listings":[{"itemId":"m1"},{"title":"No ID"},{"itemId":"m3","title":"Fiets","description":"Fiets","priceInfo":{"priceType":"FREE"},"vipUrl":"/v/fietsen/m3-fiets","sellerInformation":{"sellerId":1,"sellerName":"Seller"},"location":{"cityName":"Amsterdam"}}]}"#,
+        )?;
+        assert_eq!(listings.inner.len(), 1);
+        assert_eq!(listings.inner[0].item_id, "m3");
+        assert_eq!(listings.broken.len(), 2);
+        assert_eq!(listings.broken[0].item_id.as_deref(), Some("m1"));
+        assert_eq!(listings.broken[1].item_id, None);
         Ok(())
     }
 }
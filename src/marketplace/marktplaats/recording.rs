@@ -0,0 +1,131 @@
+//! Raw search responses, stored for reproducing the parsing failures.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    db::SearchQuery,
+    marketplace::{
+        item::Item,
+        marktplaats::{Listings, triage},
+        search::Mismatch,
+    },
+    prelude::*,
+};
+
+/// Raw search response along with the request URL.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub url: Url,
+    pub recorded_at: DateTime<Utc>,
+
+    /// Body as is, so that even an invalid response is kept.
+    pub body: String,
+}
+
+/// Outcome of a single replayed listing.
+pub struct ReplayedListing {
+    pub item_id: String,
+
+    /// The item and the mismatch reason – if it does not match the query.
+    pub outcome: Result<(Item, Option<Mismatch>)>,
+}
+
+impl RecordedResponse {
+    pub fn new(url: Url, body: String) -> Self {
+        Self { url, recorded_at: Utc::now(), body }
+    }
+
+    /// Save the response into the directory under a unique name.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create `{}`", dir.display()))?;
+        let path = dir.join(format!(
+            "{}-{:016x}.json",
+            self.recorded_at.format("%Y%m%dT%H%M%S%.3fZ"),
+            seahash::hash(self.body.as_bytes()),
+        ));
+        fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents =
+            fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("invalid recorded response `{}`", path.display()))
+    }
+
+    /// Search query text from the request URL.
+    pub fn query(&self) -> Option<String> {
+        self.url.query_pairs().find(|(key, _)| key == "query").map(|(_, value)| value.into_owned())
+    }
+
+    /// Rerun the parsing and filtering, listing by listing.
+    ///
+    /// The malformed listings are replayed as errors, and come first.
+    pub fn replay(&self, query: &SearchQuery) -> Result<Vec<ReplayedListing>> {
+        let query = query.to_normalised_query();
+        let listings: Listings =
+            serde_json::from_str(&self.body).context("failed to deserialize the response")?;
+        let broken = listings.broken.into_iter().map(|broken| ReplayedListing {
+            item_id: broken.item_id.unwrap_or_else(|| "?".to_string()),
+            outcome: Err(broken.error),
+        });
+        let parsed = listings.inner.into_iter().map(|listing| ReplayedListing {
+            item_id: listing.item_id.clone(),
+            outcome: triage(&query, listing),
+        });
+        Ok(broken.chain(parsed).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::marketplace::{
+        fake_marktplaats::{FakeMarktplaats, listing},
+        marktplaats::client::SearchRequest,
+    };
+
+    #[tokio::test]
+    async fn record_and_replay_ok() -> Result {
+        let dir =
+            std::env::temp_dir().join(format!("mrktpltsbot-recording-{}", std::process::id()));
+        let marktplaats_api = FakeMarktplaats::start().await?;
+        let mut broken = listing("m3", "Batavus fiets");
+        broken["pictures"] = json!([{"largeUrl": "not a URL"}]);
+        marktplaats_api.set_listings(vec![
+            listing("m1", "Gazelle fiets"),
+            listing("m2", "Gazelle bakfiets"),
+            broken,
+        ]);
+        let client = marktplaats_api.client()?.with_record_dir(Some(dir.clone()));
+        let _ = client.search(&SearchRequest::builder().query("fiets").build()).await?;
+
+        let paths: Vec<_> = fs::read_dir(&dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(paths.len(), 1);
+        let recorded = RecordedResponse::load(&paths[0]);
+        fs::remove_dir_all(&dir)?;
+        let recorded = recorded?;
+        assert_eq!(recorded.query().as_deref(), Some("fiets"));
+
+        let replayed = recorded.replay(&SearchQuery::from("fiets"))?;
+        assert_eq!(replayed.len(), 3);
+        assert!(matches!(&replayed[0].outcome, Ok((item, None)) if item.id == "m1"));
+        assert!(matches!(&replayed[1].outcome, Ok((_, Some(_)))), "`bakfiets` is not `fiets`");
+        assert_eq!(replayed[2].item_id, "m3");
+        assert!(replayed[2].outcome.is_err());
+        Ok(())
+    }
+}
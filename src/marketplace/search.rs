@@ -1,7 +1,14 @@
-use std::{borrow::Cow, collections::BTreeSet};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+};
 
 use deunicode::deunicode;
 use itertools::Itertools;
+use serde::Serialize;
+
+use crate::marketplace::item::Item;
 
 #[derive(Clone, Debug)]
 pub struct NormalisedQuery {
@@ -37,12 +44,71 @@ impl NormalisedQuery {
         positive.chain(negative).join(" ")
     }
 
-    pub fn matches<'a>(&self, terms: impl IntoIterator<Item = &'a str>) -> bool {
+    /// Explain why the terms do not match the query, or return [`None`] – if they do match.
+    pub fn mismatch<'a>(&self, terms: impl IntoIterator<Item = &'a str>) -> Option<Mismatch> {
         let terms: BTreeSet<_> = terms.into_iter().map(Self::normalise_token).collect();
-        self.include.is_subset(&terms) && self.exclude.is_disjoint(&terms)
+        let mismatch = Mismatch {
+            missing: self.include.difference(&terms).cloned().collect(),
+            excluded: self.exclude.intersection(&terms).cloned().collect(),
+        };
+        if mismatch.missing.is_empty() && mismatch.excluded.is_empty() {
+            None
+        } else {
+            Some(mismatch)
+        }
+    }
+}
+
+/// Reason why an item does not match the search query.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Mismatch {
+    /// Included tokens, which the item is missing.
+    pub missing: Vec<String>,
+
+    /// Excluded tokens, which the item contains.
+    pub excluded: Vec<String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let missing = (!self.missing.is_empty()).then(|| {
+            format!("missing {}", self.missing.iter().map(|token| format!("`{token}`")).join(", "))
+        });
+        let excluded = (!self.excluded.is_empty()).then(|| {
+            format!(
+                "excluded {}",
+                self.excluded.iter().map(|token| format!("`{token}`")).join(", ")
+            )
+        });
+        f.write_str(&missing.into_iter().chain(excluded).join("; "))
     }
 }
 
+/// Search results together with the items dropped by the local query filter.
+#[derive(Default, Serialize)]
+pub struct SearchResults {
+    pub items: Vec<Item>,
+    pub dropped: Vec<DroppedItem>,
+
+    /// Malformed listings, which did not make it into the items.
+    pub skipped: Vec<SkippedListing>,
+}
+
+#[derive(Serialize)]
+pub struct DroppedItem {
+    pub item: Item,
+    pub reason: Mismatch,
+}
+
+/// Listing, which could not be parsed or converted into an item.
+#[derive(Serialize)]
+pub struct SkippedListing {
+    /// Marketplace item ID, if the listing has one at all.
+    pub item_id: Option<String>,
+
+    pub error: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,16 +142,25 @@ mod tests {
     fn matches_ok() {
         let query = NormalisedQuery::parse("-samsung foldable smartphone");
         assert!(
-            query.matches("Xiaomi Foldable Smartphone".split_whitespace()),
+            query.mismatch("Xiaomi Foldable Smartphone".split_whitespace()).is_none(),
             "contains all the positives and no negatives"
         );
         assert!(
-            !query.matches("Samsung Foldable Smartphone".split_whitespace()),
+            query.mismatch("Samsung Foldable Smartphone".split_whitespace()).is_some(),
             "contains all the positives but also the negative"
         );
         assert!(
-            !query.matches("xiaomi smartphone".split_whitespace()),
+            query.mismatch("xiaomi smartphone".split_whitespace()).is_some(),
             "does not contain all the positives"
         );
     }
+
+    #[test]
+    fn mismatch_ok() {
+        let query = NormalisedQuery::parse("-samsung foldable smartphone");
+        let mismatch = query.mismatch("Samsung Smartphone".split_whitespace()).unwrap();
+        assert_eq!(mismatch.missing, ["foldable"]);
+        assert_eq!(mismatch.excluded, ["samsung"]);
+        assert_eq!(mismatch.to_string(), "missing `foldable`; excluded `samsung`");
+    }
 }
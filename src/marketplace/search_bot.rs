@@ -1,24 +1,65 @@
-use std::{borrow::Cow, time::Duration};
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 
 use bon::Builder;
 use chrono::Utc;
+use metrics::{counter, histogram};
+use prost::Message;
 use tokio::time::sleep;
 
 use crate::{
     db,
-    db::{Db, Item, Items, Notifications, SearchQuery, Subscription},
-    marketplace::Marketplaces,
+    db::{
+        Chats,
+        Db,
+        Item,
+        ItemDetails,
+        Items,
+        KeyValues,
+        KeyedMessage,
+        MutedSellers,
+        Notifications,
+        SearchQuery,
+        Subscription,
+        Subscriptions,
+    },
+    marketplace::{Marketplaces, item::Details},
     prelude::*,
+    prometheus::{SEARCH_BOT_ITEMS, SEARCH_BOT_SUBSCRIPTION_DURATION},
+    status::Status,
     telegram::{
         Telegram,
         TelegramNotification,
         commands::CommandBuilder,
+        i18n::Language,
         objects::ParseMode,
-        render,
         render::ManageSearchQuery,
+        template::Templates,
     },
 };
 
+/// Last handled subscription, persisted so that a restart does not start the round over.
+#[derive(Eq, PartialEq, Message)]
+struct Cursor {
+    #[prost(tag = "1", int64)]
+    chat_id: i64,
+
+    #[prost(tag = "2", sfixed64)]
+    query_hash: i64,
+}
+
+impl KeyedMessage for Cursor {
+    const KEY: &'static str = "search_bot.cursor";
+}
+
+impl From<Subscription> for Cursor {
+    fn from(subscription: Subscription) -> Self {
+        Self { chat_id: subscription.chat_id, query_hash: subscription.query_hash }
+    }
+}
+
 /// Core logic of the search bot.
 #[derive(Builder)]
 pub struct SearchBot {
@@ -33,6 +74,12 @@ pub struct SearchBot {
     telegram: Telegram,
 
     marketplaces: Marketplaces,
+
+    status: Status,
+
+    /// Notification templates, in addition to the built-in ones.
+    #[builder(default)]
+    templates: Templates,
 }
 
 impl SearchBot {
@@ -42,7 +89,14 @@ impl SearchBot {
             "🔄 Running the search bot…",
             search_interval_secs = self.search_interval.as_secs_f64(),
         );
-        let mut previous = None;
+        let mut previous = match self.fetch_cursor().await.context("failed to fetch the cursor") {
+            Ok(cursor) => cursor,
+            Err(error) => {
+                log::error!("‼️ Error: {error:#}");
+                capture_anyhow(&error);
+                None
+            }
+        };
         loop {
             sleep(self.search_interval).await;
             match self
@@ -58,6 +112,7 @@ impl SearchBot {
                     capture_anyhow(&error);
                 }
             }
+            self.status.on_search_bot_iterated();
         }
     }
 
@@ -67,20 +122,31 @@ impl SearchBot {
     ///
     /// Handled subscription entry as a next pointer.
     #[instrument(name = "⏩ Handling next subscription…", skip_all)]
-    async fn advance_and_handle(
+    pub async fn advance_and_handle(
         &self,
-        previous: Option<&(Subscription, SearchQuery)>,
-    ) -> Result<Option<(Subscription, SearchQuery)>> {
+        previous: Option<&Subscription>,
+    ) -> Result<Option<Subscription>> {
+        let n_resumed =
+            Subscriptions(&mut *self.db.connection().await?).resume_expired(Utc::now()).await?;
+        if n_resumed != 0 {
+            info!(
+                "▶️ Resumed subscriptions with expired pauses",
+                n_resumed = n_resumed.cast_signed()
+            );
+        }
         let current = match previous {
-            Some((previous, _)) => match self.db.next_subscription(previous).await? {
+            Some(previous) => match self.db.next_subscription(previous).await? {
                 Some(next) => Some(next),
                 None => self.db.first_subscription().await?, // reached the end, restart
             },
             None => self.db.first_subscription().await?, // fresh start or no subscriptions
         };
-        if let Some((subscription, search_query)) = &current {
-            self.handle_subscription(subscription, search_query).await?;
-            Ok(current)
+        if let Some((subscription, search_query)) = current {
+            self.handle_subscription(&subscription, &search_query).await?;
+            KeyValues(&mut *self.db.connection().await?)
+                .upsert(&Cursor::from(subscription))
+                .await?;
+            Ok(Some(subscription))
         } else {
             info!("📭 No active subscriptions");
             self.marketplaces.check_in().await;
@@ -88,6 +154,12 @@ impl SearchBot {
         }
     }
 
+    /// Fetch the last handled subscription, so that the crawling continues where it stopped.
+    async fn fetch_cursor(&self) -> Result<Option<Subscription>> {
+        let cursor = KeyValues(&mut *self.db.connection().await?).fetch::<Cursor>().await?;
+        Ok(cursor.map(|cursor| Subscription::new(cursor.query_hash, cursor.chat_id)))
+    }
+
     /// Handle the specified subscription.
     #[instrument(
         name = "🏭 Handling subscription…",
@@ -103,33 +175,84 @@ impl SearchBot {
         subscription: &Subscription,
         search_query: &SearchQuery,
     ) -> Result {
-        let unsubscribe_link = self.command_builder.unsubscribe_link(search_query.hash);
+        let start_time = Instant::now();
+        let (language, template) = {
+            let mut connection = self.db.connection().await?;
+            let language = Chats(&mut connection)
+                .fetch_language(subscription.chat_id)
+                .await?
+                .as_deref()
+                .and_then(Language::from_code)
+                .unwrap_or_default();
+            (language, self.templates.fetch_for(&mut connection, subscription).await?.effective)
+        };
+        let unsubscribe_link = self.command_builder.unsubscribe_link(search_query.hash, language);
 
-        let items = self.marketplaces.search_infallible(search_query, None).await;
+        let items = self.marketplaces.search_infallible(search_query, None).await.items;
         info!("🛍️ Fetched items from all marketplaces", n_items = items.len());
 
-        for item in items {
-            let mut connection = self.db.connection().await;
-            Items(&mut connection).upsert(Item { id: &item.id, updated_at: Utc::now() }).await?;
+        let muted_seller_ids =
+            MutedSellers(&mut *self.db.connection().await?).fetch_ids(subscription.chat_id).await?;
+
+        for mut item in items {
             let notification =
                 db::Notification { item_id: item.id.clone(), chat_id: subscription.chat_id };
-            if Notifications(&mut connection).exists(&notification).await? {
+            let is_notified = {
+                let mut transaction = self.db.begin().await?;
+                Items(&mut transaction)
+                    .upsert(Item { id: &item.id, updated_at: Utc::now() })
+                    .await?;
+                let is_notified = Notifications(&mut transaction).exists(&notification).await?;
+                transaction.commit().await.context("failed to commit the item")?;
+                is_notified
+            };
+            if !subscription.filters.matches(&item) {
+                debug!(
+                    "🫥 The item is filtered out",
+                    chat_id = subscription.chat_id,
+                    item_id = item.id,
+                );
+                counter!(SEARCH_BOT_ITEMS, "outcome" => "filtered").increment(1);
+                continue;
+            }
+            if muted_seller_ids.contains(&item.seller.id) {
+                debug!(
+                    "🔇 The seller is muted",
+                    chat_id = subscription.chat_id,
+                    item_id = item.id,
+                    seller_id = item.seller.id,
+                );
+                counter!(SEARCH_BOT_ITEMS, "outcome" => "muted").increment(1);
+                continue;
+            }
+            if is_notified {
                 debug!(
                     "✅ Notification was already sent",
                     chat_id = subscription.chat_id,
                     item_id = item.id,
                 );
+                counter!(SEARCH_BOT_ITEMS, "outcome" => "already_notified").increment(1);
                 continue;
             }
+            item.details = self.fetch_details(&item).await?;
             info!("✉️ Notifying…", chat_id = subscription.chat_id, item_id = &notification.item_id);
-            let description = render::item_description(
+            let mute_seller_link = self.command_builder.mute_seller_link(
+                item.seller.id,
+                &item.seller.username,
+                language,
+            );
+            let description = template.render(
                 &item,
-                &ManageSearchQuery::new(&search_query.text, &[&unsubscribe_link]),
+                &ManageSearchQuery::new(
+                    &search_query.text,
+                    &[&unsubscribe_link, &mute_seller_link],
+                ),
+                language,
             );
             let telegram_notification = TelegramNotification::builder()
                 .chat_id(Cow::Owned(subscription.chat_id.into()))
                 .text(description.into())
-                .maybe_picture_url(item.picture_url.as_ref())
+                .picture_urls(&item.picture_urls())
                 .parse_mode(ParseMode::Html)
                 .build();
             match telegram_notification
@@ -138,16 +261,39 @@ impl SearchBot {
                 .context("failed to send the notification")
             {
                 Ok(()) => {
-                    Notifications(&mut connection).upsert(&notification).await?;
+                    Notifications(&mut *self.db.connection().await?).upsert(&notification).await?;
+                    counter!(SEARCH_BOT_ITEMS, "outcome" => "notified").increment(1);
                 }
                 Err(error) => {
                     log::error!("‼️ Error: {error:#}");
                     capture_anyhow(&error);
+                    counter!(SEARCH_BOT_ITEMS, "outcome" => "failed").increment(1);
                 }
             }
         }
 
+        histogram!(SEARCH_BOT_SUBSCRIPTION_DURATION).record(start_time.elapsed());
         info!("✅ Done", chat_id = subscription.chat_id, text = &search_query.text);
         Ok(())
     }
+
+    /// Fetch the item details from the cache, or from the marketplace – if they are not cached yet.
+    async fn fetch_details(
+        &self,
+        item: &crate::marketplace::item::Item,
+    ) -> Result<Option<Details>> {
+        if let Some(details) =
+            ItemDetails(&mut *self.db.connection().await?).fetch(&item.id).await?
+        {
+            debug!("✅ Item details are cached", item_id = &item.id);
+            return Ok(Some(details));
+        }
+        let details = self.marketplaces.fetch_details_infallible(item).await;
+        if let Some(details) = &details {
+            ItemDetails(&mut *self.db.connection().await?)
+                .upsert(&item.id, details, Utc::now())
+                .await?;
+        }
+        Ok(details)
+    }
 }
@@ -0,0 +1,70 @@
+//! Prometheus metrics: the names and the exporter.
+//!
+//! The metrics are recorded with the [`metrics`] macros, which are no-ops until the exporter is installed.
+
+use std::net::SocketAddr;
+
+use metrics::{Unit, describe_counter, describe_gauge, describe_histogram};
+use metrics_exporter_prometheus::PrometheusBuilder;
+
+use crate::prelude::*;
+
+pub const MARKETPLACE_SEARCHES: &str = "mrktpltsbot_marketplace_searches_total";
+pub const MARKETPLACE_SEARCH_DURATION: &str = "mrktpltsbot_marketplace_search_duration_seconds";
+pub const MARKETPLACE_ITEMS_FETCHED: &str = "mrktpltsbot_marketplace_items_fetched_total";
+pub const MARKETPLACE_ITEMS_DROPPED: &str = "mrktpltsbot_marketplace_items_dropped_total";
+pub const MARKETPLACE_LISTINGS_SKIPPED: &str = "mrktpltsbot_marketplace_listings_skipped_total";
+
+pub const SEARCH_BOT_SUBSCRIPTION_DURATION: &str =
+    "mrktpltsbot_search_bot_subscription_duration_seconds";
+pub const SEARCH_BOT_ITEMS: &str = "mrktpltsbot_search_bot_items_total";
+
+pub const TELEGRAM_CALLS: &str = "mrktpltsbot_telegram_calls_total";
+pub const TELEGRAM_CALL_DURATION: &str = "mrktpltsbot_telegram_call_duration_seconds";
+pub const TELEGRAM_ERRORS: &str = "mrktpltsbot_telegram_errors_total";
+
+pub const DB_ACQUIRE_DURATION: &str = "mrktpltsbot_db_acquire_duration_seconds";
+pub const DB_QUERY_DURATION: &str = "mrktpltsbot_db_query_duration_seconds";
+pub const DB_TRANSACTIONS: &str = "mrktpltsbot_db_transactions_total";
+pub const DB_CONNECTIONS: &str = "mrktpltsbot_db_connections";
+pub const DB_IDLE_CONNECTIONS: &str = "mrktpltsbot_db_idle_connections";
+
+/// Histogram buckets, in seconds, up to the Telegram long polling timeout.
+const DURATION_BUCKETS: &[f64] =
+    &[0.001, 0.005, 0.025, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Install the global recorder and serve the metrics on the address.
+///
+/// Must be called within the Tokio runtime.
+pub fn install(address: SocketAddr) -> Result {
+    PrometheusBuilder::new()
+        .with_http_listener(address)
+        .set_buckets(DURATION_BUCKETS)?
+        .install()
+        .with_context(|| format!("failed to serve metrics on `{address}`"))?;
+    describe();
+    info!("📈 Serving metrics…", address = address.to_string());
+    Ok(())
+}
+
+fn describe() {
+    describe_counter!(MARKETPLACE_SEARCHES, "Marketplace searches by marketplace and outcome");
+    describe_histogram!(MARKETPLACE_SEARCH_DURATION, Unit::Seconds, "Marketplace search latency");
+    describe_counter!(MARKETPLACE_ITEMS_FETCHED, "Items fetched from the marketplace");
+    describe_counter!(MARKETPLACE_ITEMS_DROPPED, "Fetched items, which did not match the query");
+    describe_counter!(MARKETPLACE_LISTINGS_SKIPPED, "Malformed listings, which were skipped");
+    describe_histogram!(
+        SEARCH_BOT_SUBSCRIPTION_DURATION,
+        Unit::Seconds,
+        "Time to handle a single subscription",
+    );
+    describe_counter!(SEARCH_BOT_ITEMS, "Items handled by the search bot by outcome");
+    describe_counter!(TELEGRAM_CALLS, "Telegram Bot API calls by method");
+    describe_histogram!(TELEGRAM_CALL_DURATION, Unit::Seconds, "Telegram Bot API call latency");
+    describe_counter!(TELEGRAM_ERRORS, "Failed Telegram Bot API calls by method and error code");
+    describe_histogram!(DB_ACQUIRE_DURATION, Unit::Seconds, "Database connection wait time");
+    describe_histogram!(DB_QUERY_DURATION, Unit::Seconds, "Database query latency by query");
+    describe_counter!(DB_TRANSACTIONS, "Database transactions begun");
+    describe_gauge!(DB_CONNECTIONS, "Open database connections");
+    describe_gauge!(DB_IDLE_CONNECTIONS, "Idle database connections");
+}
@@ -0,0 +1,110 @@
+//! Pull-based health and progress reporting, complementing the push-based [`crate::heartbeat`].
+
+mod server;
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+pub use self::server::StatusServer;
+
+/// Progress of the running components, shared between them and the status server.
+#[must_use]
+#[derive(Clone)]
+pub struct Status(Arc<Mutex<Snapshot>>);
+
+impl Default for Status {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Snapshot {
+            started_at: Utc::now(),
+            is_ready: false,
+            telegram_polled_at: None,
+            search_bot_iterated_at: None,
+            crawled_at: BTreeMap::new(),
+        })))
+    }
+}
+
+impl Status {
+    /// Mark the bot ready: the database is migrated, and Telegram has responded to `getMe`.
+    pub fn set_ready(&self) {
+        self.lock().is_ready = true;
+    }
+
+    /// Record the completed iteration of the Telegram polling loop.
+    pub fn on_telegram_polled(&self) {
+        self.lock().telegram_polled_at = Some(Utc::now());
+    }
+
+    /// Record the completed iteration of the search bot loop.
+    pub fn on_search_bot_iterated(&self) {
+        self.lock().search_bot_iterated_at = Some(Utc::now());
+    }
+
+    /// Record the successful search on the marketplace.
+    pub fn on_crawled(&self, marketplace: &str) {
+        self.lock().crawled_at.insert(marketplace.to_string(), Utc::now());
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Snapshot> {
+        // The snapshot stays consistent even if a holder panicked, so ignore the poisoning:
+        self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Snapshot {
+    pub started_at: DateTime<Utc>,
+    pub is_ready: bool,
+    pub telegram_polled_at: Option<DateTime<Utc>>,
+    pub search_bot_iterated_at: Option<DateTime<Utc>>,
+
+    /// Last successful search per marketplace.
+    pub crawled_at: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl Snapshot {
+    /// Names of the loops, which have not completed an iteration for longer than `max_stale`.
+    ///
+    /// The loops which have not completed any iteration yet are measured from the start.
+    pub fn stale_loops(&self, now: DateTime<Utc>, max_stale: TimeDelta) -> Vec<&'static str> {
+        [("telegram", self.telegram_polled_at), ("search_bot", self.search_bot_iterated_at)]
+            .into_iter()
+            .filter(|(_, iterated_at)| now - iterated_at.unwrap_or(self.started_at) > max_stale)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_loops_ok() {
+        let status = Status::default();
+        let max_stale = TimeDelta::minutes(10);
+        let snapshot = status.snapshot();
+        assert!(snapshot.stale_loops(snapshot.started_at, max_stale).is_empty());
+
+        let later = snapshot.started_at + TimeDelta::minutes(11);
+        assert_eq!(snapshot.stale_loops(later, max_stale), ["telegram", "search_bot"]);
+
+        status.on_telegram_polled();
+        let snapshot = status.snapshot();
+        let later = snapshot.telegram_polled_at.unwrap() + TimeDelta::minutes(1);
+        assert!(snapshot.stale_loops(later, max_stale).is_empty());
+        assert_eq!(
+            snapshot.stale_loops(later + TimeDelta::minutes(10), max_stale),
+            ["telegram", "search_bot"],
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use bon::bon;
+use bytes::Bytes;
+use chrono::{TimeDelta, Utc};
+use http::{Method, Request, Response, StatusCode, header};
+use http_body_util::Full;
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use sqlx::Connection;
+use tokio::net::TcpListener;
+
+use crate::{
+    db::{Chats, Db},
+    prelude::*,
+    status::{Snapshot, Status},
+};
+
+/// Embedded HTTP server for the liveness, readiness, and status probes.
+pub struct StatusServer {
+    listener: TcpListener,
+    probes: Probes,
+}
+
+#[derive(Clone)]
+struct Probes {
+    status: Status,
+    db: Db,
+
+    /// Liveness fails when a loop has not completed an iteration for longer than this.
+    max_stale: TimeDelta,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    #[serde(flatten)]
+    snapshot: Snapshot,
+
+    n_chats: usize,
+    n_subscriptions: i64,
+    n_paused_subscriptions: i64,
+}
+
+#[bon]
+impl StatusServer {
+    /// Bind the listener, so that the bot fails early if the address is unavailable.
+    #[builder(finish_fn = try_bind)]
+    pub async fn new(
+        status: Status,
+        db: Db,
+        address: SocketAddr,
+        max_stale: TimeDelta,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(address)
+            .await
+            .with_context(|| format!("failed to listen on `{address}`"))?;
+        info!("🩺 Listening for status requests…", address = address.to_string());
+        Ok(Self { listener, probes: Probes { status, db, max_stale } })
+    }
+
+    /// Serve the requests indefinitely.
+    pub async fn run(self) {
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    log::warn!("⚠️ Failed to accept a status connection: {error:#}");
+                    continue;
+                }
+            };
+            let probes = self.probes.clone();
+            tokio::spawn(async move {
+                let service = service_fn(|request| {
+                    let probes = probes.clone();
+                    async move { Ok::<_, Infallible>(probes.handle(&request).await) }
+                });
+                if let Err(error) =
+                    http1::Builder::new().serve_connection(TokioIo::new(stream), service).await
+                {
+                    log::debug!("Status connection failed: {error:#}");
+                }
+            });
+        }
+    }
+}
+
+impl Probes {
+    async fn handle(&self, request: &Request<Incoming>) -> Response<Full<Bytes>> {
+        if request.method() != Method::GET {
+            return text_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+        self.route(request.uri().path()).await
+    }
+
+    async fn route(&self, path: &str) -> Response<Full<Bytes>> {
+        match path {
+            "/healthz" => self.healthz(),
+            "/readyz" => self.readyz().await,
+            "/status" => match self.status_response().await {
+                Ok(response) => response,
+                Err(error) => {
+                    log::error!("‼️ Failed to collect the status: {error:#}");
+                    text_response(StatusCode::INTERNAL_SERVER_ERROR, "failed to collect the status")
+                }
+            },
+            _ => text_response(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    fn healthz(&self) -> Response<Full<Bytes>> {
+        let stale_loops = self.status.snapshot().stale_loops(Utc::now(), self.max_stale);
+        if stale_loops.is_empty() {
+            text_response(StatusCode::OK, "ok")
+        } else {
+            text_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("stale: {}", stale_loops.join(", ")),
+            )
+        }
+    }
+
+    async fn readyz(&self) -> Response<Full<Bytes>> {
+        if !self.status.snapshot().is_ready {
+            return text_response(StatusCode::SERVICE_UNAVAILABLE, "not ready");
+        }
+        let ping = async { self.db.connection().await?.ping().await.context("failed to ping") };
+        match ping.await {
+            Ok(()) => text_response(StatusCode::OK, "ok"),
+            Err(error) => {
+                log::warn!("⚠️ The database is not available: {error:#}");
+                text_response(StatusCode::SERVICE_UNAVAILABLE, "database is not available")
+            }
+        }
+    }
+
+    async fn status_response(&self) -> Result<Response<Full<Bytes>>> {
+        let chats = Chats(&mut *self.db.connection().await?).fetch_all().await?;
+        let body = StatusResponse {
+            snapshot: self.status.snapshot(),
+            n_chats: chats.len(),
+            n_subscriptions: chats.iter().map(|chat| chat.n_subscriptions).sum(),
+            n_paused_subscriptions: chats.iter().map(|chat| chat.n_paused_subscriptions).sum(),
+        };
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(serde_json::to_vec(&body)?)))?)
+    }
+}
+
+fn text_response(status: StatusCode, body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    async fn body_text(response: Response<Full<Bytes>>) -> Result<String> {
+        let body = response.into_body().collect().await?.to_bytes();
+        Ok(String::from_utf8(body.to_vec())?)
+    }
+
+    #[tokio::test]
+    async fn routes_ok() -> Result {
+        let status = Status::default();
+        let probes = Probes {
+            status: status.clone(),
+            db: Db::try_new(Path::new(":memory:")).await?,
+            max_stale: TimeDelta::minutes(10),
+        };
+
+        assert_eq!(probes.route("/healthz").await.status(), StatusCode::OK);
+        assert_eq!(probes.route("/readyz").await.status(), StatusCode::SERVICE_UNAVAILABLE);
+        status.set_ready();
+        assert_eq!(probes.route("/readyz").await.status(), StatusCode::OK);
+        assert_eq!(probes.route("/unknown").await.status(), StatusCode::NOT_FOUND);
+
+        status.on_crawled("Marktplaats");
+        let response = probes.route("/status").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body_text(response).await?)?;
+        assert_eq!(body["is_ready"], true);
+        assert_eq!(body["n_subscriptions"], 0);
+        assert!(body["crawled_at"]["Marktplaats"].is_string());
+        Ok(())
+    }
+}
@@ -1,24 +1,39 @@
 mod bot;
 pub mod commands;
+mod duration;
+#[cfg(test)]
+pub mod fake_api;
+pub mod i18n;
 pub mod methods;
 mod notification;
 pub mod objects;
 pub mod render;
 mod response;
+pub mod template;
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    path::Path,
+    time::{Duration, Instant},
+};
 
+use metrics::{counter, histogram};
+use reqwest::multipart::{Form, Part};
 use reqwest_middleware::ClientWithMiddleware;
 use secrecy::{ExposeSecret, SecretString};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use url::Url;
 
 pub use self::{bot::Bot as TelegramBot, notification::Notification as TelegramNotification};
 use crate::{
+    client,
     prelude::*,
+    prometheus::{TELEGRAM_CALL_DURATION, TELEGRAM_CALLS, TELEGRAM_ERRORS},
     telegram::{
         commands::CommandBuilder,
         methods::{GetMe, Method},
+        objects::{File, InputFile},
         response::Response,
     },
 };
@@ -30,11 +45,33 @@ pub struct Telegram {
     client: ClientWithMiddleware,
     token: SecretString,
     root_url: Url,
+    timeout: Duration,
 }
 
 impl Telegram {
-    pub fn new(client: ClientWithMiddleware, token: SecretString) -> Result<Self> {
-        Ok(Self { client, token, root_url: Url::parse("https://api.telegram.org")? })
+    /// Create the connection to the Bot API server at the root URL.
+    ///
+    /// The root URL may have a path, for example, when the self-hosted server is behind a reverse proxy.
+    pub fn new(client: ClientWithMiddleware, token: SecretString, root_url: Url) -> Result<Self> {
+        if root_url.cannot_be_a_base() {
+            bail!("`{root_url}` cannot be the Bot API root URL");
+        }
+        Ok(Self { client, token, root_url, timeout: client::DEFAULT_TIMEOUT })
+    }
+
+    /// Set the request timeout, long polling adds its own timeout on top.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Build the URL under the root URL.
+    fn url<'a>(&self, segments: impl IntoIterator<Item = &'a str>) -> Url {
+        let mut url = self.root_url.clone();
+        // The root URL is checked in the constructor:
+        if let Ok(mut path_segments) = url.path_segments_mut() {
+            path_segments.pop_if_empty().extend(segments);
+        }
+        url
     }
 
     /// Call the Telegram Bot API method.
@@ -43,24 +80,62 @@ impl Telegram {
         M: Method + ?Sized,
         R: Debug + DeserializeOwned,
     {
-        let url = {
-            let mut url = self.root_url.clone();
-            url.set_path(&format!("bot{}/{}", self.token.expose_secret(), method.name()));
-            url
-        };
+        let url = self.url([format!("bot{}", self.token.expose_secret()).as_str(), method.name()]);
         let request_body = serde_json::to_value(method)?;
         debug!("📤 Calling…", method.name = method.name(), request_body = request_body.to_string());
-        let response = self
-            .client
-            .post(url)
-            .json(&request_body)
-            .timeout(method.timeout())
-            .send()
-            .await
-            .with_context(|| format!("failed to call `{}`", method.name()))?
-            .json::<Response<R>>()
-            .await?;
-        Result::from(response).with_context(|| format!("`{}` failed", method.name()))
+        let request = match method.input_file() {
+            None => self.client.post(url).json(&request_body),
+            Some((field_name, input_file)) => self.client.post(url).multipart(multipart_form(
+                &request_body,
+                field_name,
+                input_file,
+            )?),
+        };
+        let start_time = Instant::now();
+        let response = async {
+            request
+                .timeout(self.timeout + method.poll_timeout())
+                .send()
+                .await
+                .with_context(|| format!("failed to call `{}`", method.name()))?
+                .json::<Response<R>>()
+                .await
+                .with_context(|| format!("failed to read the `{}` response", method.name()))
+        }
+        .await;
+        histogram!(TELEGRAM_CALL_DURATION, "method" => method.name()).record(start_time.elapsed());
+        counter!(TELEGRAM_CALLS, "method" => method.name()).increment(1);
+        let error_code = match &response {
+            Ok(Response::Ok { .. }) => None,
+            Ok(Response::Err { error_code, .. }) => Some(error_code.to_string()),
+            Err(_) => Some("transport".to_string()),
+        };
+        if let Some(error_code) = error_code {
+            counter!(TELEGRAM_ERRORS, "method" => method.name(), "error_code" => error_code)
+                .increment(1);
+        }
+        Result::from(response?).with_context(|| format!("`{}` failed", method.name()))
+    }
+
+    /// Download the file, which has been returned by [`crate::telegram::methods::GetFile`].
+    ///
+    /// The local Bot API server returns the absolute path on its file system,
+    /// in which case the file is read directly, since the server is running on the same host.
+    pub async fn download_file(&self, file: &File) -> Result<Vec<u8>> {
+        let file_path = file.path.as_deref().context("the file is not available for download")?;
+        if Path::new(file_path).is_absolute() {
+            return tokio::fs::read(file_path)
+                .await
+                .with_context(|| format!("failed to read the local file `{file_path}`"));
+        }
+        let token = format!("bot{}", self.token.expose_secret());
+        let url = self.url(["file", token.as_str()].into_iter().chain(file_path.split('/')));
+        let response = self.client.get(url).send().await.context("failed to download the file")?;
+        // Not `error_for_status()`, because the error would contain the URL with the token:
+        if !response.status().is_success() {
+            bail!("the file download failed with `{}`", response.status());
+        }
+        Ok(response.bytes().await.context("failed to read the file")?.to_vec())
     }
 
     pub async fn command_builder(&self) -> Result<CommandBuilder> {
@@ -73,3 +148,145 @@ impl Telegram {
         CommandBuilder::new(&me)
     }
 }
+
+/// Build the `multipart/form-data` form from the method's JSON fields and the file.
+///
+/// Non-string fields are passed as JSON, which is what the Bot API expects.
+fn multipart_form(
+    request_body: &Value,
+    field_name: &'static str,
+    input_file: &InputFile<'_>,
+) -> Result<Form> {
+    let fields = request_body.as_object().context("the method must serialize into an object")?;
+    let form = fields.iter().fold(Form::new(), |form, (name, value)| {
+        let value = match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        form.text(name.clone(), value)
+    });
+    let file = Part::bytes(input_file.content.to_vec())
+        .file_name(input_file.file_name.to_string())
+        .mime_str("application/octet-stream")?;
+    Ok(form.part(field_name, file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use serde_json::json;
+
+    use super::*;
+    use crate::telegram::{
+        fake_api::FakeBotApi,
+        methods::{GetFile, SendDocument, SendMessage},
+    };
+
+    #[tokio::test]
+    async fn call_metrics_ok() -> Result {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let fake_api = FakeBotApi::start().await?;
+        let telegram = fake_api.telegram()?;
+        fake_api.respond_err("sendMessage", 403, "Forbidden: bot was blocked by the user");
+        let _ = SendMessage::quick_html(Cow::Owned(42.into()), "Hello").call_on(&telegram).await;
+
+        let counters: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter_map(|(key, _, _, value)| match value {
+                DebugValue::Counter(value) => {
+                    let (key, labels) = key.key().clone().into_parts();
+                    let labels: Vec<_> =
+                        labels.iter().map(|label| label.value().to_owned()).collect();
+                    Some((key.as_str().to_owned(), labels, value))
+                }
+                _ => None,
+            })
+            .collect();
+        assert!(
+            counters.contains(&(TELEGRAM_CALLS.to_owned(), vec!["sendMessage".to_owned()], 1)),
+            "{counters:?}",
+        );
+        assert!(
+            counters.contains(&(
+                TELEGRAM_ERRORS.to_owned(),
+                vec!["sendMessage".to_owned(), "403".to_owned()],
+                1
+            )),
+            "{counters:?}",
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn call_fake_api_ok() -> Result {
+        let fake_api = FakeBotApi::start().await?;
+        let telegram = fake_api.telegram()?;
+
+        let me = GetMe.call_on(&telegram).await?;
+        assert_eq!(me.username.as_deref(), Some(fake_api::BOT_USERNAME));
+
+        fake_api.respond_err("sendMessage", 403, "Forbidden: bot was blocked by the user");
+        let error = SendMessage::quick_html(Cow::Owned(42.into()), "Hello")
+            .call_on(&telegram)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("`sendMessage` failed"), "{error:#}");
+
+        let document =
+            InputFile { file_name: Cow::Borrowed("export.json"), content: Cow::Borrowed(b"[]") };
+        SendDocument::builder()
+            .chat_id(Cow::Owned(42.into()))
+            .document(document)
+            .build()
+            .call_and_discard_on(&telegram)
+            .await?;
+
+        let calls = fake_api.calls();
+        let methods: Vec<_> = calls.iter().map(|call| call.method.as_str()).collect();
+        assert_eq!(methods, ["getMe", "sendMessage", "sendDocument"]);
+        assert_eq!(fake_api.calls_to("sendMessage")[0]["text"], "Hello");
+        assert_eq!(calls[2].body["chat_id"], 42);
+        assert_eq!(calls[2].body["document"], "[]");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn root_url_with_path_ok() -> Result {
+        let fake_api = FakeBotApi::start().await?;
+        let client = crate::client::try_new(&crate::client::ClientSettings::default())?;
+        let root_url = fake_api.root_url().join("/prefix/")?;
+        let telegram = Telegram::new(client, fake_api::TOKEN.to_string().into(), root_url)?;
+        assert_eq!(telegram.url(["getMe"]).path(), "/prefix/getMe");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn download_file_ok() -> Result {
+        let fake_api = FakeBotApi::start().await?;
+        let telegram = fake_api.telegram()?;
+
+        fake_api.add_file("documents/file_1.json", b"remote");
+        fake_api
+            .respond_ok("getFile", &json!({"file_id": "1", "file_path": "documents/file_1.json"}));
+        let file = GetFile { file_id: "1" }.call_on(&telegram).await?;
+        assert_eq!(telegram.download_file(&file).await?, b"remote");
+
+        // The local server returns the absolute path:
+        let local_path =
+            std::env::temp_dir().join(format!("mrktpltsbot-{}.json", std::process::id()));
+        std::fs::write(&local_path, b"local")?;
+        let file =
+            File { id: "2".to_string(), size: None, path: Some(local_path.display().to_string()) };
+        let content = telegram.download_file(&file).await;
+        std::fs::remove_file(&local_path)?;
+        assert_eq!(content?, b"local");
+        Ok(())
+    }
+}
@@ -1,20 +1,52 @@
 use std::{borrow::Cow, collections::HashSet};
 
 use bon::bon;
+use chrono::Utc;
 use maud::{Render, html};
+use prost::Message;
 
 use crate::{
-    db::{Db, SearchQueries, SearchQuery, Subscription, Subscriptions},
+    db::{
+        Chats,
+        Db,
+        Export,
+        KeyValues,
+        KeyedMessage,
+        MutedSeller,
+        MutedSellers,
+        SearchQueries,
+        SearchQuery,
+        Subscription,
+        Subscriptions,
+    },
     heartbeat::Heartbeat,
-    marketplace::Marketplaces,
+    marketplace::{
+        Marketplaces,
+        item::{ConditionClass, DeliveryMethod},
+    },
     prelude::*,
+    status::Status,
     telegram::{
         Telegram,
-        commands::{CommandBuilder, CommandPayload, SubscriptionAction},
+        commands::{
+            CommandBuilder,
+            CommandPayload,
+            Filter,
+            FilterCommand,
+            SellerAction,
+            SellerCommand,
+            SubscriptionAction,
+            SubscriptionCommand,
+            TemplateCommand,
+        },
+        duration,
+        i18n::Language,
         methods::{
             AllowedUpdate,
+            GetFile,
             GetUpdates,
             Method,
+            SendDocument,
             SendMessage,
             SetMyCommands,
             SetMyDescription,
@@ -23,6 +55,9 @@ use crate::{
         objects::{
             BotCommand,
             ChatId,
+            ChatType,
+            Document,
+            InputFile,
             LinkPreviewOptions,
             ParseMode,
             ReplyParameters,
@@ -30,10 +65,25 @@ use crate::{
             UpdatePayload,
         },
         render,
-        render::{DELIMITER, ManageSearchQuery},
+        render::{DELIMITER, ManageSearchQuery, RenderIn},
+        template::{SubscriptionTemplate, Templates},
     },
 };
 
+/// Maximal size of an imported document, far more than any export would take.
+const MAX_IMPORT_SIZE: u64 = 1024 * 1024;
+
+/// Next Telegram update ID to poll, persisted so that a restart neither skips nor repeats updates.
+#[derive(Eq, PartialEq, Message)]
+struct UpdateOffset {
+    #[prost(tag = "1", uint64)]
+    offset: u64,
+}
+
+impl KeyedMessage for UpdateOffset {
+    const KEY: &'static str = "telegram.update_offset";
+}
+
 /// Telegram [`Message`] bot.
 ///
 /// It listens to Telegram [`Update`]'s and reacts on them.
@@ -45,7 +95,9 @@ pub struct Bot {
     marketplaces: Marketplaces,
     poll_timeout_secs: u64,
     heartbeat: Heartbeat,
+    status: Status,
     command_builder: CommandBuilder,
+    templates: Templates,
 }
 
 #[bon]
@@ -57,24 +109,56 @@ impl Bot {
         db: Db,
         marketplaces: Marketplaces,
         heartbeat: Heartbeat,
+        status: Status,
         authorized_chat_ids: HashSet<i64>,
         poll_timeout_secs: u64,
+        #[builder(default)] templates: Templates,
     ) -> Result<Self> {
-        SetMyDescription::builder()
-            .description("👋 This is a private bot for Marktplaats\n\nFeel free to set up your own instance from https://github.com/eigenein/mrktpltsbot")
-            .build()
-            .call_on(&telegram)
-            .await
-            .context("failed to set the bot's description")?;
-        SetMyCommands::builder()
-            .commands(&[&BotCommand::builder()
-                .command("manage")
-                .description("List and manage your subscriptions")
-                .build()])
-            .build()
-            .call_on(&telegram)
-            .await
-            .context("failed to set the bot's commands")?;
+        for language in Language::ALL {
+            // The default language applies to everyone without a dedicated translation:
+            let language_code = (language != Language::default()).then(|| language.code());
+            let text = language.catalogue();
+            SetMyDescription::builder()
+                .description(text.bot_description)
+                .maybe_language_code(language_code)
+                .build()
+                .call_on(&telegram)
+                .await
+                .context("failed to set the bot's description")?;
+            SetMyCommands::builder()
+                .commands(&[
+                    &BotCommand::builder()
+                        .command("manage")
+                        .description(text.manage_command)
+                        .build(),
+                    &BotCommand::builder()
+                        .command("export")
+                        .description(text.export_command)
+                        .build(),
+                    &BotCommand::builder().command("pause").description(text.pause_command).build(),
+                    &BotCommand::builder()
+                        .command("pauseall")
+                        .description(text.pause_all_command)
+                        .build(),
+                    &BotCommand::builder()
+                        .command("resume")
+                        .description(text.resume_command)
+                        .build(),
+                    &BotCommand::builder()
+                        .command("language")
+                        .description(text.language_command)
+                        .build(),
+                    &BotCommand::builder()
+                        .command("template")
+                        .description(text.template_command)
+                        .build(),
+                ])
+                .maybe_language_code(language_code)
+                .build()
+                .call_on(&telegram)
+                .await
+                .context("failed to set the bot's commands")?;
+        }
         Ok(Self {
             telegram,
             authorized_chat_ids,
@@ -82,7 +166,9 @@ impl Bot {
             marketplaces,
             poll_timeout_secs,
             heartbeat,
+            status,
             command_builder,
+            templates,
         })
     }
 }
@@ -91,18 +177,44 @@ impl Bot {
     /// Run the bot indefinitely.
     pub async fn run(self) {
         info!("🔄 Running Telegram bot…", me = self.command_builder.url().to_string());
-        let mut offset = 0;
+        let mut offset = match self.fetch_offset().await.context("failed to fetch the offset") {
+            Ok(offset) => offset,
+            Err(error) => {
+                log::error!("‼️ {error:#}");
+                capture_anyhow(&error);
+                0
+            }
+        };
+        info!("📮 Polling updates…", offset = offset.cast_signed());
         loop {
-            offset = self.handle_updates(offset).await;
+            let new_offset = self.handle_updates(offset).await;
+            if new_offset != offset
+                && let Err(error) =
+                    self.store_offset(new_offset).await.context("failed to store the offset")
+            {
+                log::error!("‼️ {error:#}");
+                capture_anyhow(&error);
+            }
+            offset = new_offset;
+            self.status.on_telegram_polled();
         }
     }
 
+    async fn fetch_offset(&self) -> Result<u64> {
+        let offset = KeyValues(&mut *self.db.connection().await?).fetch::<UpdateOffset>().await?;
+        Ok(offset.unwrap_or_default().offset)
+    }
+
+    async fn store_offset(&self, offset: u64) -> Result {
+        KeyValues(&mut *self.db.connection().await?).upsert(&UpdateOffset { offset }).await
+    }
+
     /// Handle a single batch of updates.
     ///
     /// # Returns
     ///
     /// New offset.
-    async fn handle_updates(&self, offset: u64) -> u64 {
+    pub async fn handle_updates(&self, offset: u64) -> u64 {
         let get_updates = GetUpdates::builder()
             .offset(offset)
             .timeout_secs(self.poll_timeout_secs)
@@ -122,6 +234,7 @@ impl Bot {
             Err(error) => {
                 log::error!("‼️ {error:#}");
                 capture_anyhow(&error);
+                self.heartbeat.fail(&error).await;
                 return offset;
             }
         };
@@ -135,10 +248,23 @@ impl Bot {
 
         for update in updates {
             let UpdatePayload::Message(message) = update.payload else { continue };
-            let (Some(chat), Some(text)) = (message.chat, message.text) else {
-                warn!("⚠️ Received message without an associated chat or text");
+            let Some(chat) = message.chat else {
+                warn!("⚠️ Received message without an associated chat");
                 continue;
             };
+            let content = match (message.text, message.document) {
+                (_, Some(document)) => Content::Document(document),
+                (Some(text), None) => Content::Text(text),
+                (None, None) => {
+                    warn!("⚠️ Received message without a text or document");
+                    continue;
+                }
+            };
+            // Members of a group may have different languages, so only a private chat follows its user:
+            let language_code = message
+                .from
+                .filter(|_| chat.type_ == ChatType::Private)
+                .and_then(|user| user.language_code);
             let chat_id = match chat.id {
                 ChatId::Integer(chat_id) => chat_id,
                 ChatId::Username(username) => {
@@ -146,19 +272,26 @@ impl Bot {
                     continue;
                 }
             };
-            if let Err(error) =
-                self.on_message(chat_id, message.id, text.trim()).await.with_context(|| {
+            if let Err(error) = self
+                .on_message(chat_id, message.id, &content, language_code.as_deref())
+                .await
+                .with_context(|| {
                     format!("failed to handle the message #{} from chat #{chat_id}", message.id)
                 })
             {
                 log::error!("‼️ Error: {error:#}");
                 let error_id = capture_anyhow(&error);
+                // Reply in the chat's language, unless it has been the failure itself:
+                let language = self.chat_language(chat_id, None).await.unwrap_or_else(|_| {
+                    language_code.as_deref().and_then(Language::from_code).unwrap_or_default()
+                });
+                let markup = html! {
+                    (language.catalogue().internal_error) " " code { (error_id) }
+                };
                 let _ = SendMessage::builder()
                     .chat_id(Cow::Owned(ChatId::Integer(chat_id)))
                     .parse_mode(ParseMode::Html)
-                    .text(format!(
-                        "💥 An internal error occurred and has been logged <code>{error_id}</code>"
-                    ))
+                    .text(markup.render().into_string())
                     .build()
                     .call_and_discard_on(&self.telegram)
                     .await;
@@ -168,15 +301,22 @@ impl Bot {
         new_offset
     }
 
-    async fn on_message(&self, chat_id: i64, message_id: u64, text: &str) -> Result {
+    async fn on_message(
+        &self,
+        chat_id: i64,
+        message_id: u64,
+        content: &Content,
+        language_code: Option<&str>,
+    ) -> Result {
         if !self.authorized_chat_ids.contains(&chat_id) {
             warn!(
                 "⚠️ Received message from an unauthorized chat",
                 chat_id = chat_id,
-                text = text.to_string(),
+                content = format!("{content:?}"),
             );
+            let language = language_code.and_then(Language::from_code).unwrap_or_default();
             let chat_id = ChatId::Integer(chat_id);
-            let text = render::unauthorized(&chat_id).render().into_string();
+            let text = render::unauthorized(&chat_id, language).render().into_string();
             let _ =
                 SendMessage::quick_html(Cow::Owned(chat_id), text).call_on(&self.telegram).await?;
             return Ok(());
@@ -186,14 +326,35 @@ impl Bot {
             .message_id(message_id)
             .allow_sending_without_reply(true)
             .build();
+        let language = self.chat_language(chat_id, language_code).await?;
 
-        if text.starts_with('/') {
-            self.on_command(text, chat_id, reply_parameters).await?;
-        } else {
-            self.on_search(text, chat_id, reply_parameters).await?;
+        match content {
+            Content::Text(text) if text.trim().starts_with('/') => {
+                self.on_command(text.trim(), chat_id, reply_parameters, language).await?;
+            }
+            Content::Text(text) => {
+                self.on_search(text.trim(), chat_id, reply_parameters, language).await?;
+            }
+            Content::Document(document) => {
+                self.on_import(document, chat_id, reply_parameters, language).await?;
+            }
         }
         Ok(())
     }
+
+    /// Remember the user's language code, and resolve the chat's language.
+    async fn chat_language(&self, chat_id: i64, language_code: Option<&str>) -> Result<Language> {
+        let mut connection = self.db.connection().await?;
+        let mut chats = Chats(&mut connection);
+        if let Some(language_code) = language_code
+            && chats.fetch_language_code(chat_id).await?.as_deref() != Some(language_code)
+        {
+            chats.upsert_language_code(chat_id, language_code).await?;
+        }
+        let language = chats.fetch_language(chat_id).await?;
+        Ok(language.as_deref().and_then(Language::from_code).unwrap_or_default())
+    }
+
     /// Handle the search request from Telegram.
     ///
     /// A search request is just a message that is not a command.
@@ -207,10 +368,11 @@ impl Bot {
         query: &str,
         chat_id: i64,
         reply_parameters: ReplyParameters,
+        language: Language,
     ) -> Result {
         let query = SearchQuery::from(query);
 
-        let items = self.marketplaces.search_infallible(&query, Some(1)).await;
+        let items = self.marketplaces.search_infallible(&query, Some(1)).await.items;
         info!(
             "🛍️ Fetched from all marketplaces",
             query.hash = query.hash,
@@ -218,14 +380,18 @@ impl Bot {
             query.text = &query.text,
         );
 
-        SearchQueries(&mut *self.db.connection().await).upsert(&query).await?;
+        let template = {
+            let mut connection = self.db.connection().await?;
+            SearchQueries(&mut connection).upsert(&query).await?;
+            self.templates.resolve(Chats(&mut connection).fetch_template(chat_id).await?.as_deref())
+        };
 
         // We need the subscribe command anyway, even if no listings were found.
-        let subscribe_link = self.command_builder.subscribe_link(query.hash);
+        let subscribe_link = self.command_builder.subscribe_link(query.hash, language);
 
         if items.is_empty() {
             let markup = html! {
-                "There are no items matching the search query. Try a different query or subscribe anyway to wait for them to appear"
+                (language.catalogue().no_items)
                 (DELIMITER)
                 (ManageSearchQuery::new(&query.text, &[&subscribe_link]))
             };
@@ -240,14 +406,15 @@ impl Bot {
                 .await?;
         } else {
             for item in items {
-                let description = render::item_description(
+                let description = template.render(
                     &item,
                     &ManageSearchQuery::new(&query.text, &[&subscribe_link]),
+                    language,
                 );
                 Notification::builder()
                     .chat_id(Cow::Owned(chat_id.into()))
                     .text(description.into())
-                    .maybe_picture_url(item.picture_url.as_ref())
+                    .picture_urls(&item.picture_urls())
                     .reply_parameters(reply_parameters)
                     .parse_mode(ParseMode::Html)
                     .build()
@@ -265,6 +432,7 @@ impl Bot {
         text: &str,
         chat_id: i64,
         reply_parameters: ReplyParameters,
+        language: Language,
     ) -> Result {
         if text == "/start" {
             // Just an initial greeting.
@@ -277,71 +445,53 @@ impl Bot {
                 .await?;
             let _ = SendMessage::builder()
                 .chat_id(chat_id)
-                .text("Just send me a search query to start")
+                .text(language.catalogue().start)
                 .build()
                 .call_on(&self.telegram)
                 .await?;
         } else if text == "/manage" {
-            self.on_manage_subscriptions(chat_id).await?;
+            self.on_manage_subscriptions(chat_id, language).await?;
+        } else if text == "/export" {
+            self.on_export(chat_id, reply_parameters, language).await?;
+        } else if let Some(duration) = strip_command(text, "/pauseall") {
+            self.on_pause_all(chat_id, duration, reply_parameters, language).await?;
+        } else if let Some(query) = strip_command(text, "/pause") {
+            self.on_pause(chat_id, query, reply_parameters, language).await?;
+        } else if let Some(query) = strip_command(text, "/resume") {
+            self.on_resume(chat_id, query, reply_parameters, language).await?;
+        } else if let Some(code) = strip_command(text, "/language") {
+            self.on_language(chat_id, code, reply_parameters, language).await?;
+        } else if let Some(name) = strip_command(text, "/template") {
+            self.on_template(chat_id, name, reply_parameters, language).await?;
         } else if let Some(payload) = text.strip_prefix("/start ") {
             // Command with a payload.
             let command = CommandPayload::from_base64(payload)?;
             debug!("❕ Received command");
 
             if command.manage.is_some() {
-                self.on_manage_subscriptions(chat_id).await?;
+                self.on_manage_subscriptions(chat_id, language).await?;
             }
 
             if let Some(subscription_command) = command.subscription {
-                let query_hash = subscription_command.query_hash;
-                let subscription = Subscription { query_hash, chat_id };
-                let connection = &mut *self.db.connection().await;
-                let query_text = SearchQueries(connection).fetch_text(query_hash).await?;
-                let mut subscriptions = Subscriptions(connection);
-
-                match SubscriptionAction::try_from(subscription_command.action) {
-                    Ok(SubscriptionAction::Subscribe) => {
-                        info!("➕ Subscribing", query_hash = subscription.query_hash);
-                        subscriptions.upsert(subscription).await?;
-                        let unsubscribe_link =
-                            self.command_builder.unsubscribe_link(subscription.query_hash);
-                        let markup = html! {
-                            "You are now subscribed"
-                            (DELIMITER)
-                            (ManageSearchQuery::new(&query_text, &[&unsubscribe_link, &self.command_builder.manage_link()]))
-                        };
-                        let send_message = SendMessage::quick_html(
-                            Cow::Owned(chat_id.into()),
-                            markup.render().into_string(),
-                        );
-                        let _ = send_message.call_on(&self.telegram).await?;
-                    }
+                self.on_subscription_command(chat_id, &subscription_command, language).await?;
+            }
 
-                    Ok(SubscriptionAction::Unsubscribe) => {
-                        info!("➖ Unsubscribing", query_hash = subscription.query_hash);
-                        subscriptions.delete(subscription).await?;
-                        let resubscribe_link =
-                            self.command_builder.resubscribe_link(subscription.query_hash);
-                        let markup = html! {
-                            "You are now unsubscribed"
-                            (DELIMITER)
-                            (ManageSearchQuery::new(&query_text, &[&resubscribe_link, &self.command_builder.manage_link()]))
-                        };
-                        let send_message = SendMessage::quick_html(
-                            Cow::Owned(chat_id.into()),
-                            markup.render().into_string(),
-                        );
-                        let _ = send_message.call_on(&self.telegram).await?;
-                    }
+            if let Some(filter_command) = command.filter {
+                self.on_filter_command(chat_id, &filter_command, language).await?;
+            }
 
-                    _ => {} // TODO: technically, I should return a message that the action is no longer supported
-                }
+            if let Some(seller_command) = command.seller {
+                self.on_seller_command(chat_id, seller_command, language).await?;
+            }
+
+            if let Some(template_command) = command.template {
+                self.on_template_command(chat_id, &template_command, language).await?;
             }
         } else {
             // Unknown command.
             let _ = SendMessage::builder()
                 .chat_id(Cow::Owned(chat_id.into()))
-                .text("I am sorry, but I do not know this command")
+                .text(language.catalogue().unknown_command)
                 .reply_parameters(reply_parameters)
                 .build()
                 .call_on(&self.telegram)
@@ -350,18 +500,454 @@ impl Bot {
         Ok(())
     }
 
+    /// Send the chat's subscriptions and muted sellers as a JSON document.
+    async fn on_export(
+        &self,
+        chat_id: i64,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let export = self.db.export(Some(chat_id)).await?;
+        info!("📦 Exporting", n_subscriptions = export.subscriptions.len());
+        let content = serde_json::to_vec_pretty(&export)?;
+        let _ = SendDocument::builder()
+            .chat_id(Cow::Owned(chat_id.into()))
+            .document(InputFile {
+                file_name: Cow::Borrowed("mrktpltsbot.json"),
+                content: Cow::Owned(content),
+            })
+            .caption(language.catalogue().export_caption)
+            .reply_parameters(reply_parameters)
+            .build()
+            .call_on(&self.telegram)
+            .await?;
+        Ok(())
+    }
+
+    /// Import the document, which has been previously sent by `/export`, into the chat.
+    async fn on_import(
+        &self,
+        document: &Document,
+        chat_id: i64,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let markup = if document.size.is_some_and(|size| size > MAX_IMPORT_SIZE) {
+            html! { (text.import_too_large) }
+        } else {
+            let file = GetFile { file_id: &document.file_id }.call_on(&self.telegram).await?;
+            let content = self.telegram.download_file(&file).await?;
+            match serde_json::from_slice::<Export>(&content) {
+                Ok(export) => {
+                    let report = self.db.import(&export.into_chat(chat_id), false).await?;
+                    info!(
+                        "📦 Imported",
+                        n_subscriptions_added = report.n_subscriptions_added.cast_signed(),
+                        n_subscriptions_updated = report.n_subscriptions_updated.cast_signed(),
+                        n_muted_sellers_added = report.n_muted_sellers_added.cast_signed(),
+                    );
+                    (text.imported)(
+                        report.n_subscriptions_added + report.n_subscriptions_updated,
+                        report.n_muted_sellers_added,
+                    )
+                }
+                Err(error) => {
+                    warn!("⚠️ Received an invalid export", error = error.to_string());
+                    html! { (text.import_invalid) }
+                }
+            }
+        };
+        let _ = SendMessage::builder()
+            .chat_id(Cow::Owned(chat_id.into()))
+            .text(markup.render().into_string())
+            .parse_mode(ParseMode::Html)
+            .reply_parameters(reply_parameters)
+            .build()
+            .call_on(&self.telegram)
+            .await?;
+        Ok(())
+    }
+
+    /// Handle the subscription command from a `/start` payload.
+    async fn on_subscription_command(
+        &self,
+        chat_id: i64,
+        subscription_command: &SubscriptionCommand,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let query_hash = subscription_command.query_hash;
+        let subscription = Subscription::new(query_hash, chat_id);
+        // Each statement releases its connection before calling Telegram:
+        let query_text =
+            SearchQueries(&mut *self.db.connection().await?).fetch_text(query_hash).await?;
+
+        let markup = match SubscriptionAction::try_from(subscription_command.action) {
+            Ok(SubscriptionAction::Subscribe) => {
+                info!("➕ Subscribing", query_hash = subscription.query_hash);
+                Subscriptions(&mut *self.db.connection().await?).upsert(subscription).await?;
+                let unsubscribe_link =
+                    self.command_builder.unsubscribe_link(subscription.query_hash, language);
+                let settings_link =
+                    self.command_builder.settings_link(subscription.query_hash, language);
+                html! {
+                    (text.subscribed)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query_text, &[&unsubscribe_link, &settings_link, &self.command_builder.manage_link(language)]))
+                }
+            }
+
+            Ok(SubscriptionAction::Unsubscribe) => {
+                info!("➖ Unsubscribing", query_hash = subscription.query_hash);
+                Subscriptions(&mut *self.db.connection().await?).delete(subscription).await?;
+                let resubscribe_link =
+                    self.command_builder.resubscribe_link(subscription.query_hash, language);
+                html! {
+                    (text.unsubscribed)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query_text, &[&resubscribe_link, &self.command_builder.manage_link(language)]))
+                }
+            }
+
+            Ok(SubscriptionAction::Pause) => {
+                let is_paused = Subscriptions(&mut *self.db.connection().await?)
+                    .pause(query_hash, chat_id, None)
+                    .await?;
+                if !is_paused {
+                    return self.send_not_subscribed(chat_id, &query_text, language).await;
+                }
+                info!("⏸️ Pausing", query_hash = subscription.query_hash);
+                let resume_link = self.command_builder.resume_link(query_hash, language);
+                html! {
+                    (text.subscription_paused)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query_text, &[&resume_link, &self.command_builder.manage_link(language)]))
+                }
+            }
+
+            Ok(SubscriptionAction::Resume) => {
+                info!("▶️ Resuming", query_hash = subscription.query_hash);
+                Subscriptions(&mut *self.db.connection().await?)
+                    .resume(query_hash, chat_id)
+                    .await?;
+                let pause_link = self.command_builder.pause_link(query_hash, language);
+                html! {
+                    (text.subscription_resumed)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query_text, &[&pause_link, &self.command_builder.manage_link(language)]))
+                }
+            }
+
+            Ok(SubscriptionAction::Settings) => {
+                let settings = {
+                    let connection = &mut *self.db.connection().await?;
+                    match Subscriptions(connection).fetch(query_hash, chat_id).await? {
+                        Some(subscription) => {
+                            let template =
+                                self.templates.fetch_for(connection, &subscription).await?;
+                            Some((subscription, template))
+                        }
+                        None => None,
+                    }
+                };
+                return match settings {
+                    Some((subscription, template)) => {
+                        self.send_settings(&subscription, &query_text, &template, language).await
+                    }
+                    None => self.send_not_subscribed(chat_id, &query_text, language).await,
+                };
+            }
+
+            _ => return Ok(()), // TODO: technically, I should return a message that the action is no longer supported
+        };
+        let send_message =
+            SendMessage::quick_html(Cow::Owned(chat_id.into()), markup.render().into_string());
+        let _ = send_message.call_on(&self.telegram).await?;
+        Ok(())
+    }
+
+    /// Handle the filter command from a `/start` payload.
+    async fn on_filter_command(
+        &self,
+        chat_id: i64,
+        filter_command: &FilterCommand,
+        language: Language,
+    ) -> Result {
+        let query_hash = filter_command.query_hash;
+        let mut transaction = self.db.begin_immediate().await?;
+        let query_text = SearchQueries(&mut transaction).fetch_text(query_hash).await?;
+        let Some(mut subscription) =
+            Subscriptions(&mut transaction).fetch(query_hash, chat_id).await?
+        else {
+            // Release the write lock before calling Telegram:
+            transaction.rollback().await.context("failed to roll back the filters")?;
+            return self.send_not_subscribed(chat_id, &query_text, language).await;
+        };
+        let filters = &mut subscription.filters;
+        let is_excluded = filter_command.is_excluded;
+        match Filter::try_from(filter_command.filter) {
+            Ok(Filter::Business) => filters.exclude_business = is_excluded,
+            Ok(Filter::Auctions) => filters.exclude_auctions = is_excluded,
+            Ok(Filter::Promoted) => filters.exclude_promoted = is_excluded,
+            Ok(Filter::ConditionNew) => {
+                filters.set_condition_excluded(ConditionClass::New, is_excluded);
+            }
+            Ok(Filter::ConditionAsGoodAsNew) => {
+                filters.set_condition_excluded(ConditionClass::AsGoodAsNew, is_excluded);
+            }
+            Ok(Filter::ConditionUsed) => {
+                filters.set_condition_excluded(ConditionClass::Used, is_excluded);
+            }
+            Ok(Filter::ConditionNotWorking) => {
+                filters.set_condition_excluded(ConditionClass::NotWorking, is_excluded);
+            }
+            Ok(Filter::ConditionRefurbished) => {
+                filters.set_condition_excluded(ConditionClass::Refurbished, is_excluded);
+            }
+            Ok(Filter::DeliveryCollection) => {
+                filters.set_delivery_excluded(DeliveryMethod::Collection, is_excluded);
+            }
+            Ok(Filter::DeliveryShipping) => {
+                filters.set_delivery_excluded(DeliveryMethod::Shipping, is_excluded);
+            }
+            _ => return Ok(()), // TODO: technically, I should return a message that the filter is no longer supported
+        }
+        info!("🎛️ Updating filters", query_hash = query_hash, filters = format!("{filters:?}"));
+        Subscriptions(&mut transaction).set_filters(query_hash, chat_id, *filters).await?;
+        let template = self.templates.fetch_for(&mut transaction, &subscription).await?;
+        transaction.commit().await.context("failed to commit the filters")?;
+        self.send_settings(&subscription, &query_text, &template, language).await
+    }
+
+    /// Handle the subscription template command from a `/start` payload.
+    async fn on_template_command(
+        &self,
+        chat_id: i64,
+        template_command: &TemplateCommand,
+        language: Language,
+    ) -> Result {
+        let query_hash = template_command.query_hash;
+        let name = template_command.template.as_deref();
+        if let Some(name) = name
+            && self.templates.get(name).is_none()
+        {
+            warn!("⚠️ Unknown template", name = name.to_string());
+            return Ok(()); // the template file has been removed since the link was sent
+        }
+        info!(
+            "📝 Choosing subscription template",
+            query_hash = query_hash,
+            template = name.map(ToString::to_string),
+        );
+        let (query_text, settings) = {
+            let connection = &mut *self.db.connection().await?;
+            let query_text = SearchQueries(connection).fetch_text(query_hash).await?;
+            let mut subscriptions = Subscriptions(connection);
+            let settings = if subscriptions.set_template(query_hash, chat_id, name).await?
+                && let Some(subscription) = subscriptions.fetch(query_hash, chat_id).await?
+            {
+                let template = self.templates.fetch_for(connection, &subscription).await?;
+                Some((subscription, template))
+            } else {
+                None
+            };
+            (query_text, settings)
+        };
+        match settings {
+            Some((subscription, template)) => {
+                self.send_settings(&subscription, &query_text, &template, language).await
+            }
+            None => self.send_not_subscribed(chat_id, &query_text, language).await,
+        }
+    }
+
+    /// Send the subscription settings along with the links to change them.
+    async fn send_settings(
+        &self,
+        subscription: &Subscription,
+        query_text: &str,
+        template: &SubscriptionTemplate,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let query_hash = subscription.query_hash;
+        let filters = &subscription.filters;
+        let unsubscribe_link = self.command_builder.unsubscribe_link(query_hash, language);
+        let template_links: Vec<_> = template
+            .own
+            .is_some()
+            .then(|| self.command_builder.template_link(query_hash, None, language))
+            .into_iter()
+            .chain(
+                self.templates.names().filter(|name| template.own.as_deref() != Some(*name)).map(
+                    |name| self.command_builder.template_link(query_hash, Some(name), language),
+                ),
+            )
+            .collect();
+        let markup = html! {
+            (text.settings)
+            (DELIMITER)
+            (ManageSearchQuery::new(query_text, &[&unsubscribe_link, &self.command_builder.manage_link(language)]))
+            (render::pause_status(subscription, language))
+            "\n\n"
+            (render::filter_setting(
+                text.business_sellers,
+                filters.exclude_business,
+                &self.command_builder.toggle_filter_link(query_hash, Filter::Business, !filters.exclude_business, language),
+                language,
+            ))
+            "\n"
+            (render::filter_setting(
+                text.auctions,
+                filters.exclude_auctions,
+                &self.command_builder.toggle_filter_link(query_hash, Filter::Auctions, !filters.exclude_auctions, language),
+                language,
+            ))
+            "\n"
+            (render::filter_setting(
+                text.promoted_listings,
+                filters.exclude_promoted,
+                &self.command_builder.toggle_filter_link(query_hash, Filter::Promoted, !filters.exclude_promoted, language),
+                language,
+            ))
+            "\n\n"
+            strong { (text.condition) }
+            @for class in ConditionClass::ALL {
+                @let is_excluded = filters.is_condition_excluded(class);;
+                "\n"
+                (render::filter_setting(
+                    class.render_in(language),
+                    is_excluded,
+                    &self.command_builder.toggle_filter_link(query_hash, class.into(), !is_excluded, language),
+                    language,
+                ))
+            }
+            "\n\n"
+            strong { (text.delivery) }
+            @for method in DeliveryMethod::ALL {
+                @let is_excluded = filters.is_delivery_excluded(method);;
+                "\n"
+                (render::filter_setting(
+                    method.render_in(language),
+                    is_excluded,
+                    &self.command_builder.toggle_filter_link(query_hash, method.into(), !is_excluded, language),
+                    language,
+                ))
+            }
+            "\n\n"
+            strong { (text.template) } ": "
+            em { (template.effective.name()) }
+            @if template.own.is_none() {
+                " (" (text.chat_template) ")"
+            }
+            "\n"
+            @for (i, link) in template_links.iter().enumerate() {
+                @if i != 0 {
+                    (DELIMITER)
+                }
+                (link)
+            }
+        };
+        let send_message = SendMessage::quick_html(
+            Cow::Owned(subscription.chat_id.into()),
+            markup.render().into_string(),
+        );
+        let _ = send_message.call_on(&self.telegram).await?;
+        Ok(())
+    }
+
+    async fn send_not_subscribed(
+        &self,
+        chat_id: i64,
+        query_text: &str,
+        language: Language,
+    ) -> Result {
+        let markup = html! {
+            (language.catalogue().not_subscribed)
+            (DELIMITER)
+            (ManageSearchQuery::new(query_text, &[&self.command_builder.manage_link(language)]))
+        };
+        let send_message =
+            SendMessage::quick_html(Cow::Owned(chat_id.into()), markup.render().into_string());
+        let _ = send_message.call_on(&self.telegram).await?;
+        Ok(())
+    }
+
+    /// Handle the seller command from a `/start` payload.
+    async fn on_seller_command(
+        &self,
+        chat_id: i64,
+        seller_command: SellerCommand,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let SellerCommand { seller_id, action, seller_name } = seller_command;
+        let markup = match SellerAction::try_from(action) {
+            Ok(SellerAction::Mute) => {
+                info!("🔇 Muting seller", seller_id = seller_id);
+                let unmute_link =
+                    self.command_builder.unmute_seller_link(seller_id, &seller_name, language);
+                let markup = html! {
+                    ((text.seller_muted)(&seller_name))
+                    (DELIMITER) (unmute_link)
+                    (DELIMITER) (self.command_builder.manage_link(language))
+                };
+                let muted_seller = MutedSeller { chat_id, seller_id, seller_name };
+                MutedSellers(&mut *self.db.connection().await?).upsert(&muted_seller).await?;
+                markup
+            }
+            Ok(SellerAction::Unmute) => {
+                info!("🔊 Unmuting seller", seller_id = seller_id);
+                MutedSellers(&mut *self.db.connection().await?).delete(chat_id, seller_id).await?;
+                let mute_link =
+                    self.command_builder.mute_seller_link(seller_id, &seller_name, language);
+                html! {
+                    ((text.seller_unmuted)(&seller_name))
+                    (DELIMITER) (mute_link)
+                    (DELIMITER) (self.command_builder.manage_link(language))
+                }
+            }
+            _ => return Ok(()), // TODO: technically, I should return a message that the action is no longer supported
+        };
+        let send_message =
+            SendMessage::quick_html(Cow::Owned(chat_id.into()), markup.render().into_string());
+        let _ = send_message.call_on(&self.telegram).await?;
+        Ok(())
+    }
+
     /// List the user's subscriptions.
-    async fn on_manage_subscriptions(&self, chat_id: i64) -> Result {
+    async fn on_manage_subscriptions(&self, chat_id: i64, language: Language) -> Result {
+        let text = language.catalogue();
         let subscriptions = self.db.subscriptions_of(chat_id).await?;
+        let muted_sellers =
+            MutedSellers(&mut *self.db.connection().await?).fetch_all(chat_id).await?;
         let markup = html! {
             @if subscriptions.is_empty() {
-                "You do not have any subscriptions at the moment"
+                (text.no_subscriptions)
             } @else {
-                "Here are your subscriptions:\n"
+                (text.your_subscriptions) "\n"
                 @for (subscription, search_query) in subscriptions {
-                    @let unsubscribe_link = self.command_builder.unsubscribe_link(subscription.query_hash);;
+                    @let unsubscribe_link = self.command_builder.unsubscribe_link(subscription.query_hash, language);;
+                    @let toggle_link = if subscription.is_paused {
+                        self.command_builder.resume_link(subscription.query_hash, language)
+                    } else {
+                        self.command_builder.pause_link(subscription.query_hash, language)
+                    };;
+                    "\n"
+                    @let settings_link = self.command_builder.settings_link(subscription.query_hash, language);;
+                    (ManageSearchQuery::new(&search_query.text, &[&unsubscribe_link, &toggle_link, &settings_link]))
+                    (render::pause_status(&subscription, language))
+                }
+            }
+            @if !muted_sellers.is_empty() {
+                "\n\n"
+                (text.muted_sellers) "\n"
+                @for muted_seller in muted_sellers {
+                    @let unmute_link = self.command_builder.unmute_seller_link(muted_seller.seller_id, &muted_seller.seller_name, language);;
                     "\n"
-                    (ManageSearchQuery::new(&search_query.text, &[&unsubscribe_link]))
+                    "🔇 " strong { (muted_seller.seller_name) }
+                    (DELIMITER) (unmute_link)
                 }
             }
         };
@@ -375,4 +961,222 @@ impl Bot {
             .await?;
         Ok(())
     }
+
+    /// Pause the subscription to the query, or list the subscriptions – if the query is empty.
+    async fn on_pause(
+        &self,
+        chat_id: i64,
+        query: &str,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        if query.is_empty() {
+            return self.on_manage_subscriptions(chat_id, language).await;
+        }
+        let query = SearchQuery::from(query);
+        let is_paused = Subscriptions(&mut *self.db.connection().await?)
+            .pause(query.hash, chat_id, None)
+            .await?;
+        let markup = if is_paused {
+            info!("⏸️ Pausing", query_hash = query.hash);
+            let resume_link = self.command_builder.resume_link(query.hash, language);
+            html! {
+                (text.subscription_paused)
+                (DELIMITER)
+                (ManageSearchQuery::new(&query.text, &[&resume_link, &self.command_builder.manage_link(language)]))
+            }
+        } else {
+            html! {
+                (text.not_subscribed)
+                (DELIMITER)
+                (ManageSearchQuery::new(&query.text, &[&self.command_builder.manage_link(language)]))
+            }
+        };
+        self.reply_html(chat_id, markup.render().into_string(), reply_parameters).await
+    }
+
+    /// Resume the subscription to the query, or all paused subscriptions – if the query is empty.
+    async fn on_resume(
+        &self,
+        chat_id: i64,
+        query: &str,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let markup = if query.is_empty() {
+            let n_resumed =
+                Subscriptions(&mut *self.db.connection().await?).resume_all(chat_id).await?;
+            info!("▶️ Resuming all", n_resumed = n_resumed.cast_signed());
+            html! {
+                @if n_resumed == 0 {
+                    (text.no_paused_subscriptions)
+                } @else {
+                    ((text.n_resumed)(n_resumed))
+                }
+                (DELIMITER)
+                (self.command_builder.manage_link(language))
+            }
+        } else {
+            let query = SearchQuery::from(query);
+            let is_resumed = Subscriptions(&mut *self.db.connection().await?)
+                .resume(query.hash, chat_id)
+                .await?;
+            if is_resumed {
+                info!("▶️ Resuming", query_hash = query.hash);
+                let pause_link = self.command_builder.pause_link(query.hash, language);
+                html! {
+                    (text.subscription_resumed)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query.text, &[&pause_link, &self.command_builder.manage_link(language)]))
+                }
+            } else {
+                html! {
+                    (text.not_subscribed)
+                    (DELIMITER)
+                    (ManageSearchQuery::new(&query.text, &[&self.command_builder.manage_link(language)]))
+                }
+            }
+        };
+        self.reply_html(chat_id, markup.render().into_string(), reply_parameters).await
+    }
+
+    /// Pause all the chat's subscriptions, optionally for the specified duration.
+    async fn on_pause_all(
+        &self,
+        chat_id: i64,
+        duration: &str,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let resume_at = if duration.is_empty() {
+            None
+        } else {
+            match duration::after(Utc::now(), duration) {
+                Ok(resume_at) => Some(resume_at),
+                Err(error) => {
+                    let markup = (text.invalid_duration)(&format!("{error:#}"));
+                    return self
+                        .reply_html(chat_id, markup.render().into_string(), reply_parameters)
+                        .await;
+                }
+            }
+        };
+        let n_paused =
+            Subscriptions(&mut *self.db.connection().await?).pause_all(chat_id, resume_at).await?;
+        info!(
+            "⏸️ Pausing all",
+            n_paused = n_paused.cast_signed(),
+            resume_at = resume_at.map(|it| it.to_rfc3339())
+        );
+        let markup = html! {
+            @if n_paused == 0 {
+                (text.no_subscriptions)
+            } @else {
+                ((text.n_paused)(n_paused, resume_at))
+                (DELIMITER)
+                (self.command_builder.manage_link(language))
+            }
+        };
+        self.reply_html(chat_id, markup.render().into_string(), reply_parameters).await
+    }
+
+    /// Choose the chat's language, or show the available ones – if the code is empty.
+    async fn on_language(
+        &self,
+        chat_id: i64,
+        code: &str,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let markup = if code.is_empty() {
+            html! { (language.catalogue().language_name) "\n\n" (language.catalogue().language_usage) }
+        } else if code == "auto" {
+            info!("🌐 Resetting language", chat_id = chat_id);
+            Chats(&mut *self.db.connection().await?).set_language(chat_id, None).await?;
+            let language = self.chat_language(chat_id, None).await?;
+            html! { (language.catalogue().language_name) (DELIMITER) (language.catalogue().language_detected) }
+        } else if let Some(language) = Language::from_code(code) {
+            info!("🌐 Choosing language", chat_id = chat_id, language = language.code());
+            Chats(&mut *self.db.connection().await?)
+                .set_language(chat_id, Some(language.code()))
+                .await?;
+            html! { (language.catalogue().language_name) (DELIMITER) (language.catalogue().language_chosen) }
+        } else {
+            html! { (language.catalogue().language_usage) }
+        };
+        self.reply_html(chat_id, markup.render().into_string(), reply_parameters).await
+    }
+
+    /// Choose the chat's notification template, or show the available ones – if the name is unknown.
+    async fn on_template(
+        &self,
+        chat_id: i64,
+        name: &str,
+        reply_parameters: ReplyParameters,
+        language: Language,
+    ) -> Result {
+        let text = language.catalogue();
+        let mut connection = self.db.connection().await?;
+        let markup = if let Some(template) = self.templates.get(name) {
+            info!("📝 Choosing chat template", chat_id = chat_id, template = name.to_string());
+            Chats(&mut connection).set_template(chat_id, Some(template.name())).await?;
+            (text.template_chosen)(template.name())
+        } else {
+            let current = Chats(&mut connection).fetch_template(chat_id).await?;
+            let current = self.templates.resolve(current.as_deref());
+            html! {
+                strong { (text.template) } ": " em { (current.name()) }
+                "\n\n"
+                (text.template_usage)
+                "\n"
+                @for name in self.templates.names() {
+                    "\n" code { (name) }
+                }
+            }
+        };
+        drop(connection);
+        self.reply_html(chat_id, markup.render().into_string(), reply_parameters).await
+    }
+
+    async fn reply_html(
+        &self,
+        chat_id: i64,
+        html: String,
+        reply_parameters: ReplyParameters,
+    ) -> Result {
+        SendMessage::builder()
+            .chat_id(Cow::Owned(chat_id.into()))
+            .text(html)
+            .parse_mode(ParseMode::Html)
+            .reply_parameters(reply_parameters)
+            .link_preview_options(LinkPreviewOptions::DISABLED)
+            .build()
+            .call_and_discard_on(&self.telegram)
+            .await
+    }
+}
+
+/// Strip the command from the message text.
+///
+/// # Returns
+///
+/// The trimmed command argument, which is empty when the command has no argument,
+/// or [`None`] – if the text is a different command.
+fn strip_command<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let argument = text.strip_prefix(command)?;
+    if argument.is_empty() || argument.starts_with(char::is_whitespace) {
+        Some(argument.trim())
+    } else {
+        None
+    }
+}
+
+/// Message contents, which the bot handles.
+#[derive(Debug)]
+enum Content {
+    Text(String),
+    Document(Document),
 }
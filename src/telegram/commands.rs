@@ -1,10 +1,16 @@
 //! `/start` command.
 
+use std::borrow::Cow;
+
 use bon::Builder;
 use prost::{Enumeration, Message};
 use url::Url;
 
-use crate::{prelude::*, telegram::render::CommandLink};
+use crate::{
+    marketplace::item::{ConditionClass, DeliveryMethod},
+    prelude::*,
+    telegram::{i18n::Language, render::CommandLink},
+};
 
 /// Builder of `/start` commands with [deep linking][1].
 ///
@@ -26,30 +32,114 @@ impl CommandBuilder {
     }
 
     /// Build a new command link.
-    pub fn command_link(&self, content: &'static str, payload: &CommandPayload) -> CommandLink {
+    pub fn command_link(
+        &self,
+        content: impl Into<Cow<'static, str>>,
+        payload: &CommandPayload,
+    ) -> CommandLink {
         let mut url = self.0.clone();
         url.query_pairs_mut().append_pair("start", &payload.to_base64());
-        CommandLink { content, url }
+        CommandLink { content: content.into(), url }
     }
 
     /// Produce «Manage subscriptions» link.
-    pub fn manage_link(&self) -> CommandLink {
-        self.command_link("Manage subscriptions", &CommandPayload::manage())
+    pub fn manage_link(&self, language: Language) -> CommandLink {
+        self.command_link(language.catalogue().manage_link, &CommandPayload::manage())
     }
 
     /// Produce a standard «Subscribe» link.
-    pub fn subscribe_link(&self, to_query_hash: i64) -> CommandLink {
-        self.command_link("Subscribe", &CommandPayload::subscribe_to(to_query_hash))
+    pub fn subscribe_link(&self, to_query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(
+            language.catalogue().subscribe_link,
+            &CommandPayload::subscribe_to(to_query_hash),
+        )
     }
 
     /// Produce a standard «Re-subscribe» link.
-    pub fn resubscribe_link(&self, to_query_hash: i64) -> CommandLink {
-        self.command_link("Re-subscribe", &CommandPayload::subscribe_to(to_query_hash))
+    pub fn resubscribe_link(&self, to_query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(
+            language.catalogue().resubscribe_link,
+            &CommandPayload::subscribe_to(to_query_hash),
+        )
     }
 
     /// Produce a standard «Unsubscribe» link.
-    pub fn unsubscribe_link(&self, from_query_hash: i64) -> CommandLink {
-        self.command_link("Unsubscribe", &CommandPayload::unsubscribe_from(from_query_hash))
+    pub fn unsubscribe_link(&self, from_query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(
+            language.catalogue().unsubscribe_link,
+            &CommandPayload::unsubscribe_from(from_query_hash),
+        )
+    }
+
+    /// Produce a standard «Pause» link.
+    pub fn pause_link(&self, query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(language.catalogue().pause_link, &CommandPayload::pause(query_hash))
+    }
+
+    /// Produce a standard «Resume» link.
+    pub fn resume_link(&self, query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(language.catalogue().resume_link, &CommandPayload::resume(query_hash))
+    }
+
+    /// Produce a standard «Settings» link.
+    pub fn settings_link(&self, query_hash: i64, language: Language) -> CommandLink {
+        self.command_link(language.catalogue().settings_link, &CommandPayload::settings(query_hash))
+    }
+
+    /// Produce a «Hide» or «Show» link for the subscription filter.
+    pub fn toggle_filter_link(
+        &self,
+        query_hash: i64,
+        filter: Filter,
+        is_excluded: bool,
+        language: Language,
+    ) -> CommandLink {
+        let text = language.catalogue();
+        let content = if is_excluded { text.hide_link } else { text.show_link };
+        self.command_link(content, &CommandPayload::toggle_filter(query_hash, filter, is_excluded))
+    }
+
+    /// Produce a link to choose the subscription's template, named after it.
+    ///
+    /// [`None`] resets the template to the chat's one.
+    pub fn template_link(
+        &self,
+        query_hash: i64,
+        template: Option<&str>,
+        language: Language,
+    ) -> CommandLink {
+        let payload = CommandPayload::choose_template(query_hash, template);
+        let content: Cow<'static, str> = template.map_or_else(
+            || language.catalogue().chat_template_link.into(),
+            |template| template.to_string().into(),
+        );
+        self.command_link(content, &payload)
+    }
+
+    /// Produce a standard «Mute seller» link.
+    pub fn mute_seller_link(
+        &self,
+        seller_id: i64,
+        seller_name: &str,
+        language: Language,
+    ) -> CommandLink {
+        self.command_link(
+            language.catalogue().mute_seller_link,
+            &CommandPayload::mute_seller(seller_id, seller_name),
+        )
+    }
+
+    /// Produce a standard «Unmute» link.
+    pub fn unmute_seller_link(
+        &self,
+        seller_id: i64,
+        seller_name: &str,
+        language: Language,
+    ) -> CommandLink {
+        self.command_link(
+            language.catalogue().unmute_seller_link,
+            &CommandPayload::unmute_seller(seller_id, seller_name),
+        )
     }
 }
 
@@ -63,6 +153,15 @@ pub struct CommandPayload {
 
     #[prost(tag = "4", message, optional)]
     pub manage: Option<ManageCommand>,
+
+    #[prost(tag = "5", message, optional)]
+    pub seller: Option<SellerCommand>,
+
+    #[prost(tag = "6", message, optional)]
+    pub filter: Option<FilterCommand>,
+
+    #[prost(tag = "7", message, optional)]
+    pub template: Option<TemplateCommand>,
 }
 
 impl CommandPayload {
@@ -76,15 +175,106 @@ impl CommandPayload {
     }
 
     pub const fn manage() -> Self {
-        Self { subscription: None, manage: Some(ManageCommand {}) }
+        Self {
+            subscription: None,
+            manage: Some(ManageCommand {}),
+            seller: None,
+            filter: None,
+            template: None,
+        }
     }
 
     pub const fn subscribe_to(query_hash: i64) -> Self {
-        Self { subscription: Some(SubscriptionCommand::subscribe_to(query_hash)), manage: None }
+        Self {
+            subscription: Some(SubscriptionCommand::subscribe_to(query_hash)),
+            manage: None,
+            seller: None,
+            filter: None,
+            template: None,
+        }
     }
 
     pub const fn unsubscribe_from(query_hash: i64) -> Self {
-        Self { subscription: Some(SubscriptionCommand::unsubscribe_from(query_hash)), manage: None }
+        Self {
+            subscription: Some(SubscriptionCommand::unsubscribe_from(query_hash)),
+            manage: None,
+            seller: None,
+            filter: None,
+            template: None,
+        }
+    }
+
+    pub const fn pause(query_hash: i64) -> Self {
+        Self {
+            subscription: Some(SubscriptionCommand::pause(query_hash)),
+            manage: None,
+            seller: None,
+            filter: None,
+            template: None,
+        }
+    }
+
+    pub const fn resume(query_hash: i64) -> Self {
+        Self {
+            subscription: Some(SubscriptionCommand::resume(query_hash)),
+            manage: None,
+            seller: None,
+            filter: None,
+            template: None,
+        }
+    }
+
+    pub const fn settings(query_hash: i64) -> Self {
+        Self {
+            subscription: Some(SubscriptionCommand::settings(query_hash)),
+            manage: None,
+            seller: None,
+            filter: None,
+            template: None,
+        }
+    }
+
+    pub const fn toggle_filter(query_hash: i64, filter: Filter, is_excluded: bool) -> Self {
+        Self {
+            subscription: None,
+            manage: None,
+            seller: None,
+            filter: Some(FilterCommand { query_hash, filter: filter as i32, is_excluded }),
+            template: None,
+        }
+    }
+
+    pub fn mute_seller(seller_id: i64, seller_name: &str) -> Self {
+        Self {
+            subscription: None,
+            manage: None,
+            seller: Some(SellerCommand::new(seller_id, seller_name, SellerAction::Mute)),
+            filter: None,
+            template: None,
+        }
+    }
+
+    pub fn choose_template(query_hash: i64, template: Option<&str>) -> Self {
+        Self {
+            subscription: None,
+            manage: None,
+            seller: None,
+            filter: None,
+            template: Some(TemplateCommand {
+                query_hash,
+                template: template.map(ToString::to_string),
+            }),
+        }
+    }
+
+    pub fn unmute_seller(seller_id: i64, seller_name: &str) -> Self {
+        Self {
+            subscription: None,
+            manage: None,
+            seller: Some(SellerCommand::new(seller_id, seller_name, SellerAction::Unmute)),
+            filter: None,
+            template: None,
+        }
     }
 }
 
@@ -109,6 +299,18 @@ impl SubscriptionCommand {
     pub const fn unsubscribe_from(query_hash: i64) -> Self {
         Self { query_hash, action: SubscriptionAction::Unsubscribe as i32 }
     }
+
+    pub const fn pause(query_hash: i64) -> Self {
+        Self { query_hash, action: SubscriptionAction::Pause as i32 }
+    }
+
+    pub const fn resume(query_hash: i64) -> Self {
+        Self { query_hash, action: SubscriptionAction::Resume as i32 }
+    }
+
+    pub const fn settings(query_hash: i64) -> Self {
+        Self { query_hash, action: SubscriptionAction::Settings as i32 }
+    }
 }
 
 #[derive(Debug, Enumeration)]
@@ -117,6 +319,107 @@ pub enum SubscriptionAction {
     None = 0,
     Subscribe = 1,
     Unsubscribe = 2,
+    Pause = 3,
+    Resume = 4,
+
+    /// Show the subscription settings.
+    Settings = 5,
+}
+
+/// Change the subscription filter.
+#[derive(Eq, PartialEq, Message)]
+pub struct FilterCommand {
+    #[prost(tag = "1", sfixed64)]
+    pub query_hash: i64,
+
+    #[prost(tag = "2", enumeration = "Filter")]
+    pub filter: i32,
+
+    /// Whether the matching items should be excluded from notifications.
+    #[prost(tag = "3", bool)]
+    pub is_excluded: bool,
+}
+
+#[derive(Copy, Clone, Debug, Enumeration)]
+#[repr(i32)]
+pub enum Filter {
+    None = 0,
+    Business = 1,
+    Auctions = 2,
+    Promoted = 3,
+    ConditionNew = 4,
+    ConditionAsGoodAsNew = 5,
+    ConditionUsed = 6,
+    ConditionNotWorking = 7,
+    ConditionRefurbished = 8,
+    DeliveryCollection = 9,
+    DeliveryShipping = 10,
+}
+
+impl From<ConditionClass> for Filter {
+    fn from(class: ConditionClass) -> Self {
+        match class {
+            ConditionClass::New => Self::ConditionNew,
+            ConditionClass::AsGoodAsNew => Self::ConditionAsGoodAsNew,
+            ConditionClass::Used => Self::ConditionUsed,
+            ConditionClass::NotWorking => Self::ConditionNotWorking,
+            ConditionClass::Refurbished => Self::ConditionRefurbished,
+        }
+    }
+}
+
+impl From<DeliveryMethod> for Filter {
+    fn from(method: DeliveryMethod) -> Self {
+        match method {
+            DeliveryMethod::Collection => Self::DeliveryCollection,
+            DeliveryMethod::Shipping => Self::DeliveryShipping,
+        }
+    }
+}
+
+/// Choose the subscription's notification template.
+#[derive(Eq, PartialEq, Message)]
+pub struct TemplateCommand {
+    #[prost(tag = "1", sfixed64)]
+    pub query_hash: i64,
+
+    /// Template name, or the chat's template – if not set.
+    #[prost(tag = "2", string, optional)]
+    pub template: Option<String>,
+}
+
+#[derive(Eq, PartialEq, Message)]
+pub struct SellerCommand {
+    #[prost(tag = "1", int64)]
+    pub seller_id: i64,
+
+    #[prost(tag = "2", enumeration = "SellerAction")]
+    pub action: i32,
+
+    /// Seller's display name, truncated to keep the deep link within Telegram's limit.
+    #[prost(tag = "3", string)]
+    pub seller_name: String,
+}
+
+impl SellerCommand {
+    /// Maximum length of the seller name in bytes.
+    ///
+    /// The entire payload must fit in 64 characters once encoded with Base64.
+    const MAX_SELLER_NAME_LEN: usize = 24;
+
+    pub fn new(seller_id: i64, seller_name: &str, action: SellerAction) -> Self {
+        let seller_name =
+            &seller_name[..seller_name.floor_char_boundary(Self::MAX_SELLER_NAME_LEN)];
+        Self { seller_id, action: action as i32, seller_name: seller_name.to_string() }
+    }
+}
+
+#[derive(Debug, Enumeration)]
+#[repr(i32)]
+pub enum SellerAction {
+    None = 0,
+    Mute = 1,
+    Unmute = 2,
 }
 
 #[cfg(test)]
@@ -129,7 +432,8 @@ mod tests {
     #[test]
     fn test_build_subscribe_link_ok() -> Result {
         let search_query = SearchQuery::from("unifi");
-        let link = CommandBuilder::new("mrktpltsbot")?.subscribe_link(search_query.hash);
+        let link = CommandBuilder::new("mrktpltsbot")?
+            .subscribe_link(search_query.hash, Language::English);
 
         // language=html
         assert_eq!(
@@ -140,6 +444,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_pause_payload_roundtrip_ok() -> Result {
+        let payload = CommandPayload::from_base64(&CommandPayload::pause(42).to_base64())?;
+        assert_eq!(payload.subscription, Some(SubscriptionCommand::pause(42)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mute_seller_payload_fits_ok() -> Result {
+        let payload = CommandPayload::mute_seller(i64::MAX, &"ü".repeat(100));
+        assert!(payload.to_base64().len() <= 64);
+        let seller = CommandPayload::from_base64(&payload.to_base64())?.seller.unwrap();
+        assert_eq!(seller.seller_name, "ü".repeat(12));
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_payload_fits_ok() -> Result {
+        let payload = CommandPayload::choose_template(i64::MIN, Some(&"x".repeat(24)));
+        assert!(payload.to_base64().len() <= 64);
+        let template = CommandPayload::from_base64(&payload.to_base64())?.template.unwrap();
+        assert_eq!(template.template, Some("x".repeat(24)));
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_payload_ok() -> Result {
         let payload = CommandPayload::from_base64("GgsJ_5xfEFkYbu0QAQ")?;
@@ -0,0 +1,63 @@
+//! Human-friendly durations in bot commands, for example: `30m`, `12h`, `3d`, or `2w`.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::prelude::*;
+
+/// Parse the duration as an integer amount followed by an optional unit.
+///
+/// A bare number means days.
+pub fn parse(text: &str) -> Result<TimeDelta> {
+    let text = text.trim();
+    let (amount, unit) =
+        text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let amount: i64 =
+        amount.parse().with_context(|| format!("`{text}` does not start with a number"))?;
+    let duration = match unit.trim() {
+        "m" | "min" | "mins" | "minute" | "minutes" => TimeDelta::try_minutes(amount),
+        "h" | "hour" | "hours" => TimeDelta::try_hours(amount),
+        "" | "d" | "day" | "days" => TimeDelta::try_days(amount),
+        "w" | "week" | "weeks" => TimeDelta::try_weeks(amount),
+        unit => bail!("unknown duration unit `{unit}`"),
+    };
+    duration.with_context(|| format!("duration `{text}` is out of range"))
+}
+
+/// Parse the duration and add it to `now`.
+pub fn after(now: DateTime<Utc>, text: &str) -> Result<DateTime<Utc>> {
+    now.checked_add_signed(parse(text)?)
+        .with_context(|| format!("duration `{}` is too far in the future", text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ok() -> Result {
+        assert_eq!(parse("30m")?, TimeDelta::minutes(30));
+        assert_eq!(parse("12 hours")?, TimeDelta::hours(12));
+        assert_eq!(parse("3")?, TimeDelta::days(3));
+        assert_eq!(parse("2w")?, TimeDelta::weeks(2));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(parse("").is_err());
+        assert!(parse("d").is_err());
+        assert!(parse("3y").is_err());
+    }
+
+    #[test]
+    fn after_ok() -> Result {
+        let now = Utc::now();
+        assert_eq!(after(now, "2w")?, now + TimeDelta::weeks(2));
+        Ok(())
+    }
+
+    #[test]
+    fn after_out_of_range() {
+        assert!(after(Utc::now(), "99999999w").is_err());
+    }
+}
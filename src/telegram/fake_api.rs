@@ -0,0 +1,280 @@
+//! In-process fake Bot API server for the tests.
+//!
+//! It records all the method calls and replies with the queued responses,
+//! or with the minimal valid results when nothing is queued.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bytes::Bytes;
+use http::{Method, Request, Response, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use serde_json::{Map, Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::Url;
+
+use crate::{
+    client::{self, ClientSettings},
+    prelude::*,
+    telegram::Telegram,
+};
+
+/// Bot token, which the fake server accepts.
+pub const TOKEN: &str = "42:fake";
+
+/// Username returned by `getMe`.
+pub const BOT_USERNAME: &str = "mrktpltsbot";
+
+/// Method call, which the fake server has received.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub method: String,
+
+    /// JSON body, or the form fields for the `multipart/form-data` uploads.
+    pub body: Value,
+}
+
+/// The server stops when dropped.
+pub struct FakeBotApi {
+    root_url: Url,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+
+    /// Queued responses per method.
+    responses: HashMap<String, VecDeque<Value>>,
+
+    /// Downloadable files by their paths.
+    files: HashMap<String, Vec<u8>>,
+
+    n_messages: u64,
+    n_updates: u64,
+}
+
+impl Drop for FakeBotApi {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl FakeBotApi {
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let root_url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let server = tokio::spawn(serve(listener, state.clone()));
+        Ok(Self { root_url, state, server })
+    }
+
+    pub const fn root_url(&self) -> &Url {
+        &self.root_url
+    }
+
+    /// Connection to the fake server.
+    pub fn telegram(&self) -> Result<Telegram> {
+        let client = client::try_new(&ClientSettings::default())?;
+        Telegram::new(client, TOKEN.to_string().into(), self.root_url.clone())
+    }
+
+    /// Queue the successful result for the next call of the method.
+    pub fn respond_ok(&self, method: &str, result: &Value) {
+        self.respond(method, json!({"ok": true, "result": result}));
+    }
+
+    /// Queue the error for the next call of the method.
+    pub fn respond_err(&self, method: &str, error_code: i32, description: &str) {
+        self.respond(
+            method,
+            json!({"ok": false, "error_code": error_code, "description": description}),
+        );
+    }
+
+    fn respond(&self, method: &str, response: Value) {
+        self.lock().responses.entry(method.to_string()).or_default().push_back(response);
+    }
+
+    /// Queue the incoming text message for the next `getUpdates`.
+    ///
+    /// The sender is the private chat's user with the optional Telegram language code.
+    pub fn queue_message(&self, chat_id: i64, language_code: Option<&str>, text: &str) {
+        self.queue_update(chat_id, language_code, "text", &json!(text));
+    }
+
+    /// Queue the incoming document, and make it downloadable through `getFile`.
+    pub fn queue_document(&self, chat_id: i64, file_id: &str, content: &[u8]) {
+        let file_path = format!("documents/{file_id}");
+        self.add_file(&file_path, content);
+        self.respond_ok(
+            "getFile",
+            &json!({"file_id": file_id, "file_size": content.len(), "file_path": file_path}),
+        );
+        self.queue_update(
+            chat_id,
+            None,
+            "document",
+            &json!({"file_id": file_id, "file_size": content.len()}),
+        );
+    }
+
+    fn queue_update(&self, chat_id: i64, language_code: Option<&str>, key: &str, value: &Value) {
+        let update = {
+            let mut state = self.lock();
+            state.n_updates += 1;
+            json!({
+                "update_id": state.n_updates,
+                "message": {
+                    "message_id": state.n_updates,
+                    "from": {"id": chat_id, "is_bot": false, "first_name": "User", "language_code": language_code},
+                    key: value,
+                    "chat": {"id": chat_id, "type": if chat_id > 0 { "private" } else { "group" }},
+                },
+            })
+        };
+        self.respond_ok("getUpdates", &json!([update]));
+    }
+
+    /// Make the file downloadable by its path.
+    pub fn add_file(&self, path: &str, content: &[u8]) {
+        self.lock().files.insert(path.to_string(), content.to_vec());
+    }
+
+    /// All the calls received so far.
+    pub fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Bodies of the calls to the method.
+    pub fn calls_to(&self, method: &str) -> Vec<Value> {
+        self.lock()
+            .calls
+            .iter()
+            .filter(|call| call.method == method)
+            .map(|call| call.body.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(|request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle(&state, request).await) }
+            });
+            let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+        });
+    }
+}
+
+async fn handle(state: &Mutex<State>, request: Request<Incoming>) -> Response<Full<Bytes>> {
+    let path = request.uri().path().to_string();
+    if request.method() == Method::GET
+        && let Some(file_path) = path.strip_prefix(&format!("/file/bot{TOKEN}/"))
+    {
+        let content = lock(state).files.get(file_path).cloned();
+        return content.map_or_else(
+            || response(StatusCode::NOT_FOUND, b"not found".to_vec()),
+            |content| response(StatusCode::OK, content),
+        );
+    }
+    let Some(method) = path.strip_prefix(&format!("/bot{TOKEN}/")).map(ToString::to_string) else {
+        return json_response(
+            StatusCode::UNAUTHORIZED,
+            &json!({"ok": false, "error_code": 401, "description": "Unauthorized"}),
+        );
+    };
+    let content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = match request.into_body().collect().await {
+        Ok(body) => parse_body(&content_type, &body.to_bytes()),
+        Err(error) => Value::String(format!("failed to read the body: {error:#}")),
+    };
+
+    let mut state = lock(state);
+    state.calls.push(Call { method: method.clone(), body });
+    let queued = state.responses.get_mut(&method).and_then(VecDeque::pop_front);
+    let response = queued
+        .unwrap_or_else(|| json!({"ok": true, "result": default_result(&mut state, &method)}));
+    json_response(StatusCode::OK, &response)
+}
+
+/// Minimal valid result for the method.
+fn default_result(state: &mut State, method: &str) -> Value {
+    match method {
+        "getMe" => json!({"id": 42, "is_bot": true, "first_name": "Bot", "username": BOT_USERNAME}),
+        "getUpdates" => json!([]),
+        "sendMessage" | "sendPhoto" | "sendDocument" => {
+            state.n_messages += 1;
+            json!({"message_id": state.n_messages})
+        }
+        "sendMediaGroup" => {
+            state.n_messages += 1;
+            json!([{"message_id": state.n_messages}])
+        }
+        _ => json!(true),
+    }
+}
+
+/// Parse the JSON body, or the form fields.
+///
+/// The form fields are parsed as JSON where possible, the files are kept as text.
+fn parse_body(content_type: &str, body: &[u8]) -> Value {
+    let Some((_, boundary)) = content_type.split_once("boundary=") else {
+        return serde_json::from_slice(body).unwrap_or(Value::Null);
+    };
+    let body = String::from_utf8_lossy(body);
+    let mut fields = Map::new();
+    for part in body.split(&format!("--{boundary}")) {
+        let Some((headers, content)) = part.split_once("\r\n\r\n") else {
+            continue;
+        };
+        let Some(name) = headers.split("name=\"").nth(1).and_then(|rest| rest.split('"').next())
+        else {
+            continue;
+        };
+        let content = content.strip_suffix("\r\n").unwrap_or(content);
+        let value = if headers.contains("filename=") {
+            Value::String(content.to_string())
+        } else {
+            serde_json::from_str(content).unwrap_or_else(|_| Value::String(content.to_string()))
+        };
+        fields.insert(name.to_string(), value);
+    }
+    Value::Object(fields)
+}
+
+fn response(status: StatusCode, body: Vec<u8>) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    response
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let mut response = response(status, body.to_string().into_bytes());
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    response
+}
@@ -0,0 +1,169 @@
+//! Message catalogue of the bot UI.
+
+mod en;
+mod nl;
+
+use chrono::{DateTime, Utc};
+use maud::{Markup, PreEscaped};
+
+/// Language of the bot UI.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+
+    Dutch,
+}
+
+impl Language {
+    pub const ALL: [Self; 2] = [Self::English, Self::Dutch];
+
+    /// Parse the [IETF language tag][1], like Telegram's `language_code`, or `None` – if unsupported.
+    ///
+    /// [1]: https://en.wikipedia.org/wiki/IETF_language_tag
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.trim().to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Self::English),
+            "nl" => Some(Self::Dutch),
+            _ => None,
+        }
+    }
+
+    /// Two-letter ISO 639-1 code.
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Dutch => "nl",
+        }
+    }
+
+    pub const fn catalogue(self) -> &'static Catalogue {
+        match self {
+            Self::English => &en::CATALOGUE,
+            Self::Dutch => &nl::CATALOGUE,
+        }
+    }
+}
+
+/// Translated texts of a single language.
+///
+/// The plain texts are HTML-escaped when rendered, the [`PreEscaped`] ones contain trusted markup.
+pub struct Catalogue {
+    /// Language name in the language itself.
+    pub language_name: &'static str,
+
+    // Bot profile:
+    pub bot_description: &'static str,
+    pub manage_command: &'static str,
+    pub export_command: &'static str,
+    pub pause_command: &'static str,
+    pub pause_all_command: &'static str,
+    pub resume_command: &'static str,
+    pub language_command: &'static str,
+    pub template_command: &'static str,
+
+    // Command links:
+    pub manage_link: &'static str,
+    pub subscribe_link: &'static str,
+    pub resubscribe_link: &'static str,
+    pub unsubscribe_link: &'static str,
+    pub pause_link: &'static str,
+    pub resume_link: &'static str,
+    pub settings_link: &'static str,
+    pub hide_link: &'static str,
+    pub show_link: &'static str,
+    pub mute_seller_link: &'static str,
+    pub unmute_seller_link: &'static str,
+    pub chat_template_link: &'static str,
+
+    // Replies:
+    pub unauthorized: PreEscaped<&'static str>,
+    pub internal_error: &'static str,
+    pub start: &'static str,
+    pub unknown_command: &'static str,
+    pub no_items: &'static str,
+    pub export_caption: &'static str,
+    pub import_invalid: &'static str,
+    pub import_too_large: &'static str,
+    pub imported: fn(u64, u64) -> Markup,
+    pub subscribed: &'static str,
+    pub unsubscribed: &'static str,
+    pub subscription_paused: &'static str,
+    pub subscription_resumed: &'static str,
+    pub not_subscribed: &'static str,
+    pub no_subscriptions: &'static str,
+    pub no_paused_subscriptions: &'static str,
+    pub your_subscriptions: &'static str,
+    pub muted_sellers: &'static str,
+    pub seller_muted: fn(&str) -> Markup,
+    pub seller_unmuted: fn(&str) -> Markup,
+    pub n_paused: fn(u64, Option<DateTime<Utc>>) -> Markup,
+    pub n_resumed: fn(u64) -> Markup,
+    pub invalid_duration: fn(&str) -> Markup,
+    pub language_usage: PreEscaped<&'static str>,
+    pub language_chosen: &'static str,
+    pub language_detected: &'static str,
+    pub template_usage: PreEscaped<&'static str>,
+    pub template_chosen: fn(&str) -> Markup,
+
+    // Subscription settings:
+    pub settings: &'static str,
+    pub paused: &'static str,
+    pub until: &'static str,
+    pub shown: &'static str,
+    pub hidden: &'static str,
+    pub business_sellers: &'static str,
+    pub auctions: &'static str,
+    pub promoted_listings: &'static str,
+    pub condition: &'static str,
+    pub delivery: &'static str,
+    pub template: &'static str,
+    pub chat_template: &'static str,
+
+    // Items:
+    pub business: &'static str,
+    pub free: &'static str,
+    pub price_on_request: &'static str,
+    pub bidding: &'static str,
+    pub price_in_description: &'static str,
+    pub price_to_be_agreed: &'static str,
+    pub reserved: &'static str,
+    pub auction: &'static str,
+    pub exchange: &'static str,
+    pub new_with_tags: &'static str,
+    pub new_without_tags: &'static str,
+    pub as_good_as_new: &'static str,
+    pub new: &'static str,
+    pub very_good: &'static str,
+    pub good: &'static str,
+    pub satisfactory: &'static str,
+    pub used: &'static str,
+    pub not_fully_functional: &'static str,
+    pub not_working: &'static str,
+    pub refurbished: &'static str,
+    pub collection: &'static str,
+    pub shipping: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_code_ok() {
+        assert_eq!(Language::from_code("en"), Some(Language::English));
+        assert_eq!(Language::from_code("nl"), Some(Language::Dutch));
+        assert_eq!(Language::from_code("nl-BE"), Some(Language::Dutch));
+        assert_eq!(Language::from_code("NL_nl"), Some(Language::Dutch));
+        assert_eq!(Language::from_code("de"), None);
+        assert_eq!(Language::from_code(""), None);
+    }
+
+    #[test]
+    fn code_roundtrip_ok() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_code(language.code()), Some(language));
+        }
+    }
+}
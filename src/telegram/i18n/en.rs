@@ -0,0 +1,126 @@
+use maud::{PreEscaped, html};
+
+use crate::telegram::{i18n::Catalogue, render::Timestamp};
+
+pub static CATALOGUE: Catalogue = Catalogue {
+    language_name: "🇬🇧 English",
+
+    bot_description: "👋 This is a private bot for Marktplaats\n\nFeel free to set up your own instance from https://github.com/eigenein/mrktpltsbot",
+    manage_command: "List and manage your subscriptions",
+    export_command: "Export your subscriptions as a file",
+    pause_command: "Pause the subscription to a query",
+    pause_all_command: "Pause all subscriptions, optionally for a duration like 3d",
+    resume_command: "Resume all paused subscriptions",
+    language_command: "Choose the bot language",
+    template_command: "Choose how the notifications look",
+
+    manage_link: "Manage subscriptions",
+    subscribe_link: "Subscribe",
+    resubscribe_link: "Re-subscribe",
+    unsubscribe_link: "Unsubscribe",
+    pause_link: "Pause",
+    resume_link: "Resume",
+    settings_link: "Settings",
+    hide_link: "Hide",
+    show_link: "Show",
+    mute_seller_link: "Mute seller",
+    unmute_seller_link: "Unmute",
+    chat_template_link: "Chat's default",
+
+    unauthorized: PreEscaped(
+        // language=html
+        "👋 Thank you for your interest\n\n\
+        This bot cannot handle many users, so it is private and only intended for authorized users.\n\n\
+        However, <strong>its <a href=\"https://github.com/eigenein/mrktpltsbot\">source code</a> is open</strong>, \
+        and you are free to deploy your own instance.\n\n\
+        If you are already setting it up for yourself, or someone is setting it up for you, \
+        <strong>the following ID should be added to the list of authorized chat IDs:</strong>",
+    ),
+    internal_error: "💥 An internal error occurred and has been logged",
+    start: "Just send me a search query to start",
+    unknown_command: "I am sorry, but I do not know this command",
+    no_items: "There are no items matching the search query. Try a different query or subscribe anyway to wait for them to appear",
+    export_caption: "📦 Your subscriptions and muted sellers, send this file back to restore them",
+    import_invalid: "I could not read this file, only the files from /export can be imported",
+    import_too_large: "This file is too large to be an export",
+    imported: |n_subscriptions, n_muted_sellers| {
+        html! { "Imported " (n_subscriptions) " subscription(s) and " (n_muted_sellers) " muted seller(s)" }
+    },
+    subscribed: "You are now subscribed",
+    unsubscribed: "You are now unsubscribed",
+    subscription_paused: "Your subscription is now paused",
+    subscription_resumed: "Your subscription is now resumed",
+    not_subscribed: "You are not subscribed to this query",
+    no_subscriptions: "You do not have any subscriptions at the moment",
+    no_paused_subscriptions: "You do not have any paused subscriptions",
+    your_subscriptions: "Here are your subscriptions:",
+    muted_sellers: "Muted sellers:",
+    seller_muted: |seller_name| html! { "You will no longer receive items from " strong { (seller_name) } },
+    seller_unmuted: |seller_name| html! { "You will receive items from " strong { (seller_name) } " again" },
+    n_paused: |n_paused, resume_at| {
+        html! {
+            "Paused " (n_paused) " subscription(s) "
+            @if let Some(resume_at) = resume_at {
+                "until " (Timestamp(resume_at))
+            } @else {
+                "indefinitely, send /resume to resume them"
+            }
+        }
+    },
+    n_resumed: |n_resumed| html! { "Resumed " (n_resumed) " subscription(s)" },
+    invalid_duration: |error| {
+        html! {
+            "I could not understand the duration: " (error) "\n\n"
+            "Try something like " code { "/pauseall 3d" } " or " code { "/pauseall 12h" }
+        }
+    },
+    language_usage: PreEscaped(
+        // language=html
+        "Send <code>/language en</code> or <code>/language nl</code> to choose the language, \
+        or <code>/language auto</code> to follow your Telegram settings",
+    ),
+    language_chosen: "The bot speaks English now",
+    language_detected: "The bot follows your Telegram settings now",
+    template_usage: PreEscaped(
+        // language=html
+        "Send <code>/template</code> with a name to choose how the notifications look in this chat, \
+        a subscription may override it in its settings. The available templates are:",
+    ),
+    template_chosen: |template| html! { "The notifications now use the " strong { (template) } " template" },
+
+    settings: "Settings of your subscription",
+    paused: "paused",
+    until: "until",
+    shown: "shown",
+    hidden: "hidden",
+    business_sellers: "🏢 Business sellers",
+    auctions: "🔨 Auctions",
+    promoted_listings: "📣 Promoted listings",
+    condition: "Condition",
+    delivery: "Delivery",
+    template: "📝 Template",
+    chat_template: "chat's default",
+
+    business: "🏢 business",
+    free: "🆓 free",
+    price_on_request: "🙋price on request",
+    bidding: "bidding",
+    price_in_description: "📝 price in description",
+    price_to_be_agreed: "🤝 price to be agreed",
+    reserved: "⚠️ reserved",
+    auction: "⬆️ auction",
+    exchange: "💱 exchange",
+    new_with_tags: "🟢 new with tags",
+    new_without_tags: "🟢 new without tags",
+    as_good_as_new: "🟡 as good as new",
+    new: "🟢 new",
+    very_good: "🟠 very good",
+    good: "🟠 good",
+    satisfactory: "🟠 satisfactory",
+    used: "🟠 used",
+    not_fully_functional: "⛔️ not fully functional",
+    not_working: "⛔️ not working",
+    refurbished: "🟡 refurbished",
+    collection: "🚶 collection",
+    shipping: "📦 shipping",
+};
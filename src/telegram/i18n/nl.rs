@@ -0,0 +1,126 @@
+use maud::{PreEscaped, html};
+
+use crate::telegram::{i18n::Catalogue, render::Timestamp};
+
+pub static CATALOGUE: Catalogue = Catalogue {
+    language_name: "🇳🇱 Nederlands",
+
+    bot_description: "👋 Dit is een privébot voor Marktplaats\n\nJe kunt gerust je eigen exemplaar opzetten via https://github.com/eigenein/mrktpltsbot",
+    manage_command: "Je abonnementen bekijken en beheren",
+    export_command: "Je abonnementen als bestand exporteren",
+    pause_command: "Pauzeer het abonnement op een zoekopdracht",
+    pause_all_command: "Alle abonnementen pauzeren, eventueel voor een duur zoals 3d",
+    resume_command: "Alle gepauzeerde abonnementen hervatten",
+    language_command: "De taal van de bot kiezen",
+    template_command: "Kiezen hoe de meldingen eruitzien",
+
+    manage_link: "Abonnementen beheren",
+    subscribe_link: "Abonneren",
+    resubscribe_link: "Opnieuw abonneren",
+    unsubscribe_link: "Opzeggen",
+    pause_link: "Pauzeren",
+    resume_link: "Hervatten",
+    settings_link: "Instellingen",
+    hide_link: "Verbergen",
+    show_link: "Tonen",
+    mute_seller_link: "Verkoper negeren",
+    unmute_seller_link: "Niet meer negeren",
+    chat_template_link: "Standaard van de chat",
+
+    unauthorized: PreEscaped(
+        // language=html
+        "👋 Bedankt voor je interesse\n\n\
+        Deze bot kan niet veel gebruikers aan, daarom is hij privé en alleen bedoeld voor geautoriseerde gebruikers.\n\n\
+        <strong>De <a href=\"https://github.com/eigenein/mrktpltsbot\">broncode</a> is echter open</strong>, \
+        en je mag gerust je eigen exemplaar draaien.\n\n\
+        Als je hem al voor jezelf opzet, of iemand dat voor je doet, \
+        <strong>moet het volgende ID aan de lijst met geautoriseerde chat-ID's worden toegevoegd:</strong>",
+    ),
+    internal_error: "💥 Er is een interne fout opgetreden en deze is gelogd",
+    start: "Stuur me gewoon een zoekopdracht om te beginnen",
+    unknown_command: "Sorry, dit commando ken ik niet",
+    no_items: "Er zijn geen advertenties die bij de zoekopdracht passen. Probeer een andere zoekopdracht, of abonneer je toch om te wachten tot ze verschijnen",
+    export_caption: "📦 Je abonnementen en genegeerde verkopers, stuur dit bestand terug om ze te herstellen",
+    import_invalid: "Ik kan dit bestand niet lezen, alleen de bestanden van /export kunnen worden geïmporteerd",
+    import_too_large: "Dit bestand is te groot voor een export",
+    imported: |n_subscriptions, n_muted_sellers| {
+        html! { (n_subscriptions) " abonnement(en) en " (n_muted_sellers) " genegeerde verkoper(s) geïmporteerd" }
+    },
+    subscribed: "Je bent nu geabonneerd",
+    unsubscribed: "Je abonnement is opgezegd",
+    subscription_paused: "Je abonnement is nu gepauzeerd",
+    subscription_resumed: "Je abonnement is nu hervat",
+    not_subscribed: "Je bent niet geabonneerd op deze zoekopdracht",
+    no_subscriptions: "Je hebt op dit moment geen abonnementen",
+    no_paused_subscriptions: "Je hebt geen gepauzeerde abonnementen",
+    your_subscriptions: "Dit zijn je abonnementen:",
+    muted_sellers: "Genegeerde verkopers:",
+    seller_muted: |seller_name| html! { "Je ontvangt geen advertenties meer van " strong { (seller_name) } },
+    seller_unmuted: |seller_name| html! { "Je ontvangt weer advertenties van " strong { (seller_name) } },
+    n_paused: |n_paused, resume_at| {
+        html! {
+            (n_paused) " abonnement(en) gepauzeerd "
+            @if let Some(resume_at) = resume_at {
+                "tot " (Timestamp(resume_at))
+            } @else {
+                "voor onbepaalde tijd, stuur /resume om ze te hervatten"
+            }
+        }
+    },
+    n_resumed: |n_resumed| html! { (n_resumed) " abonnement(en) hervat" },
+    invalid_duration: |error| {
+        html! {
+            "Ik begrijp de duur niet: " (error) "\n\n"
+            "Probeer bijvoorbeeld " code { "/pauseall 3d" } " of " code { "/pauseall 12h" }
+        }
+    },
+    language_usage: PreEscaped(
+        // language=html
+        "Stuur <code>/language en</code> of <code>/language nl</code> om de taal te kiezen, \
+        of <code>/language auto</code> om je Telegram-instellingen te volgen",
+    ),
+    language_chosen: "De bot spreekt nu Nederlands",
+    language_detected: "De bot volgt nu je Telegram-instellingen",
+    template_usage: PreEscaped(
+        // language=html
+        "Stuur <code>/template</code> met een naam om te kiezen hoe de meldingen er in deze chat uitzien, \
+        een abonnement kan dit in zijn instellingen overschrijven. De beschikbare sjablonen zijn:",
+    ),
+    template_chosen: |template| html! { "De meldingen gebruiken nu het sjabloon " strong { (template) } },
+
+    settings: "Instellingen van je abonnement",
+    paused: "gepauzeerd",
+    until: "tot",
+    shown: "getoond",
+    hidden: "verborgen",
+    business_sellers: "🏢 Zakelijke verkopers",
+    auctions: "🔨 Veilingen",
+    promoted_listings: "📣 Uitgelichte advertenties",
+    condition: "Staat",
+    delivery: "Levering",
+    template: "📝 Sjabloon",
+    chat_template: "standaard van de chat",
+
+    business: "🏢 zakelijk",
+    free: "🆓 gratis",
+    price_on_request: "🙋prijs op aanvraag",
+    bidding: "bieden",
+    price_in_description: "📝 prijs in beschrijving",
+    price_to_be_agreed: "🤝 prijs notk",
+    reserved: "⚠️ gereserveerd",
+    auction: "⬆️ veiling",
+    exchange: "💱 ruilen",
+    new_with_tags: "🟢 nieuw met prijskaartje",
+    new_without_tags: "🟢 nieuw zonder prijskaartje",
+    as_good_as_new: "🟡 zo goed als nieuw",
+    new: "🟢 nieuw",
+    very_good: "🟠 zeer goed",
+    good: "🟠 goed",
+    satisfactory: "🟠 redelijk",
+    used: "🟠 gebruikt",
+    not_fully_functional: "⛔️ niet volledig functioneel",
+    not_working: "⛔️ niet werkend",
+    refurbished: "🟡 refurbished",
+    collection: "🚶 ophalen",
+    shipping: "📦 verzenden",
+};
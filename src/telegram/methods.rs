@@ -7,10 +7,24 @@ use serde::{
 };
 
 use crate::{
-    client,
     prelude::*,
     serde::as_inner_json,
-    telegram::{Telegram, objects::*},
+    telegram::{
+        Telegram,
+        objects::{
+            BotCommand,
+            ChatId,
+            File,
+            InputFile,
+            LinkPreviewOptions,
+            Media,
+            Message,
+            ParseMode,
+            ReplyParameters,
+            Update,
+            User,
+        },
+    },
 };
 
 /// [Telegram bot API][1] method.
@@ -21,8 +35,14 @@ pub trait Method: Serialize {
 
     fn name(&self) -> &'static str;
 
-    fn timeout(&self) -> Duration {
-        client::DEFAULT_TIMEOUT
+    /// Time the server may hold the request, on top of the connection's timeout.
+    fn poll_timeout(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// File to upload along with the method, as the form field name and the file.
+    fn input_file(&self) -> Option<(&'static str, &InputFile<'_>)> {
+        None
     }
 
     /// Call the method on the specified [`Telegram`] connection.
@@ -63,6 +83,11 @@ pub struct SetMyDescription<'a> {
     /// Pass an empty string to remove the dedicated description for the given language.
     #[builder(into)]
     pub description: Option<Cow<'a, str>>,
+
+    /// A two-letter ISO 639-1 language code.
+    /// If empty, the description will be applied to all users for whose language there is no dedicated description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<&'a str>,
 }
 
 impl Method for SetMyDescription<'_> {
@@ -113,8 +138,8 @@ impl Method for GetUpdates<'_> {
         "getUpdates"
     }
 
-    fn timeout(&self) -> Duration {
-        client::DEFAULT_TIMEOUT + Duration::from_secs(self.timeout_secs.unwrap_or_default())
+    fn poll_timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or_default())
     }
 }
 
@@ -192,6 +217,64 @@ impl Method for SendPhoto<'_> {
     }
 }
 
+/// [Send a group of photos][1] as an album.
+///
+/// [1]: https://core.telegram.org/bots/api#sendmediagroup
+#[derive(Builder, Serialize)]
+#[must_use]
+pub struct SendMediaGroup<'a> {
+    pub chat_id: Cow<'a, ChatId>,
+
+    /// From 2 to 10 items, only the first caption is shown under the album.
+    pub media: Vec<Media<'a>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+impl Method for SendMediaGroup<'_> {
+    type Response = Vec<Message>;
+
+    fn name(&self) -> &'static str {
+        "sendMediaGroup"
+    }
+}
+
+/// [Send a general file][1].
+///
+/// [1]: https://core.telegram.org/bots/api#senddocument
+#[derive(Builder, Serialize)]
+#[must_use]
+pub struct SendDocument<'a> {
+    pub chat_id: Cow<'a, ChatId>,
+
+    /// Uploaded as a separate form field.
+    #[serde(skip)]
+    pub document: InputFile<'a>,
+
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<Cow<'a, str>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+impl Method for SendDocument<'_> {
+    type Response = Message;
+
+    fn name(&self) -> &'static str {
+        "sendDocument"
+    }
+
+    fn input_file(&self) -> Option<(&'static str, &InputFile<'_>)> {
+        Some(("document", &self.document))
+    }
+}
+
 /// Use this method to [change the list of the bot's commands].
 ///
 /// See [this manual][2] for more details about bot commands. Returns [`true`] on success.
@@ -206,6 +289,12 @@ pub struct SetMyCommands<'a> {
     /// At most 100 commands can be specified.
     #[serde(serialize_with = "as_inner_json")]
     pub commands: &'a [&'a BotCommand<'a>],
+
+    /// A two-letter ISO 639-1 language code.
+    /// If empty, commands will be applied to all users from the given scope,
+    /// for whose language there are no dedicated commands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<&'a str>,
 }
 
 impl Method for SetMyCommands<'_> {
@@ -215,3 +304,20 @@ impl Method for SetMyCommands<'_> {
         "setMyCommands"
     }
 }
+
+/// Use this method to [get basic information about a file][1] and prepare it for downloading.
+///
+/// [1]: https://core.telegram.org/bots/api#getfile
+#[derive(Serialize)]
+#[must_use]
+pub struct GetFile<'a> {
+    pub file_id: &'a str,
+}
+
+impl Method for GetFile<'_> {
+    type Response = File;
+
+    fn name(&self) -> &'static str {
+        "getFile"
+    }
+}
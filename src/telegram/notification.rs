@@ -8,8 +8,8 @@ use crate::{
     prelude::*,
     telegram::{
         Telegram,
-        methods::{Method, SendMessage, SendPhoto},
-        objects::{ChatId, LinkPreviewOptions, ParseMode, ReplyParameters},
+        methods::{Method, SendMediaGroup, SendMessage, SendPhoto},
+        objects::{ChatId, InputMediaPhoto, LinkPreviewOptions, Media, ParseMode, ReplyParameters},
     },
 };
 
@@ -20,8 +20,12 @@ use crate::{
 pub enum Notification<'a> {
     Message(SendMessage<'a>),
     Photo(SendPhoto<'a>),
+    MediaGroup(SendMediaGroup<'a>),
 }
 
+/// Telegram does not accept more pictures in an album.
+const MAX_MEDIA_GROUP_SIZE: usize = 10;
+
 #[bon]
 impl<'a> Notification<'a> {
     /// Build a new reaction method from a listing contents.
@@ -30,12 +34,12 @@ impl<'a> Notification<'a> {
         chat_id: Cow<'a, ChatId>,
         text: Cow<'a, str>,
         parse_mode: ParseMode,
-        picture_url: Option<&'a Url>,
+        #[builder(default)] picture_urls: &[&'a Url],
         reply_parameters: Option<ReplyParameters>,
     ) -> Self {
         // Specific representation depends on how many pictures there are.
-        match picture_url {
-            None => Self::Message(
+        match picture_urls {
+            [] => Self::Message(
                 SendMessage::builder()
                     .chat_id(chat_id)
                     .text(text)
@@ -45,7 +49,7 @@ impl<'a> Notification<'a> {
                     .build(),
             ),
 
-            Some(url) => Self::Photo(
+            [url] => Self::Photo(
                 SendPhoto::builder()
                     .chat_id(chat_id)
                     .photo(url.as_str())
@@ -54,6 +58,32 @@ impl<'a> Notification<'a> {
                     .maybe_reply_parameters(reply_parameters)
                     .build(),
             ),
+
+            urls => {
+                // The album's caption is the first picture's one:
+                let mut caption = Some((text, parse_mode));
+                let media = urls
+                    .iter()
+                    .take(MAX_MEDIA_GROUP_SIZE)
+                    .map(|url| {
+                        let (caption, parse_mode) = caption.take().unzip();
+                        Media::InputMediaPhoto(
+                            InputMediaPhoto::builder()
+                                .media(url.as_str())
+                                .maybe_caption(caption)
+                                .maybe_parse_mode(parse_mode)
+                                .build(),
+                        )
+                    })
+                    .collect();
+                Self::MediaGroup(
+                    SendMediaGroup::builder()
+                        .chat_id(chat_id)
+                        .media(media)
+                        .maybe_reply_parameters(reply_parameters)
+                        .build(),
+                )
+            }
         }
     }
 }
@@ -63,6 +93,43 @@ impl Notification<'_> {
         match self {
             Notification::Message(inner) => inner.call_and_discard_on(telegram).await,
             Notification::Photo(inner) => inner.call_and_discard_on(telegram).await,
+            Notification::MediaGroup(inner) => inner.call_and_discard_on(telegram).await,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn media_group_ok() -> Result {
+        let picture_urls = (1..=12)
+            .map(|i| Url::parse(&format!("https://example.com/{i}.jpg")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let notification = Notification::builder()
+            .chat_id(Cow::Owned(42.into()))
+            .text("Gazelle".into())
+            .parse_mode(ParseMode::Html)
+            .picture_urls(&picture_urls.iter().collect::<Vec<_>>())
+            .build();
+        let Notification::MediaGroup(media_group) = &notification else {
+            bail!("expected a media group");
+        };
+        let media = serde_json::to_value(&media_group.media)?;
+        assert_eq!(media.as_array().map(Vec::len), Some(MAX_MEDIA_GROUP_SIZE));
+        assert_eq!(
+            media[0],
+            json!({
+                "type": "photo",
+                "media": "https://example.com/1.jpg",
+                "caption": "Gazelle",
+                "parse_mode": "HTML",
+            }),
+        );
+        assert_eq!(media[1], json!({"type": "photo", "media": "https://example.com/2.jpg"}));
+        Ok(())
+    }
+}
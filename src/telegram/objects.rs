@@ -8,6 +8,23 @@ use std::{
 use bon::Builder;
 use serde::{Deserialize, Serialize};
 
+/// This object represents a [file][1] ready to be downloaded.
+///
+/// [1]: https://core.telegram.org/bots/api#file
+#[derive(Debug, Deserialize)]
+#[must_use]
+pub struct File {
+    #[serde(rename = "file_id")]
+    pub id: String,
+
+    #[serde(rename = "file_size", default)]
+    pub size: Option<u64>,
+
+    /// Download path, or the absolute path on the server's file system for the local Bot API server.
+    #[serde(rename = "file_path", default)]
+    pub path: Option<String>,
+}
+
 /// This object represents a Telegram user or bot.
 ///
 /// See also: <https://core.telegram.org/bots/api#user>.
@@ -18,6 +35,12 @@ pub struct User {
 
     #[serde(default)]
     pub username: Option<String>,
+
+    /// [IETF language tag][1] of the user's language.
+    ///
+    /// [1]: https://en.wikipedia.org/wiki/IETF_language_tag
+    #[serde(default)]
+    pub language_code: Option<String>,
 }
 
 // This object represents an incoming [update][1].
@@ -46,6 +69,15 @@ pub enum UpdatePayload {
     Other,
 }
 
+/// Contents of a file to be uploaded using `multipart/form-data`.
+///
+/// See also: <https://core.telegram.org/bots/api#inputfile>.
+#[must_use]
+pub struct InputFile<'a> {
+    pub file_name: Cow<'a, str>,
+    pub content: Cow<'a, [u8]>,
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 #[must_use]
@@ -83,6 +115,25 @@ pub struct Message {
 
     #[serde(default)]
     pub chat: Option<Chat>,
+
+    /// Sender, empty for messages sent to channels.
+    #[serde(default)]
+    pub from: Option<User>,
+
+    #[serde(default)]
+    pub document: Option<Document>,
+}
+
+/// This object represents a general [file][1], sent as a document.
+///
+/// [1]: https://core.telegram.org/bots/api#document
+#[derive(Debug, Deserialize)]
+#[must_use]
+pub struct Document {
+    pub file_id: String,
+
+    #[serde(rename = "file_size", default)]
+    pub size: Option<u64>,
 }
 
 /// «Umbrella» for methods that may return exactly one [`Message`] or multiple messages.
@@ -110,6 +161,24 @@ impl Messages {
 #[must_use]
 pub struct Chat {
     pub id: ChatId,
+
+    #[serde(rename = "type", default)]
+    pub type_: ChatType,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatType {
+    /// One-to-one chat with a user.
+    Private,
+
+    Group,
+    Supergroup,
+    Channel,
+
+    #[default]
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Serialize)]
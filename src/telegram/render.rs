@@ -2,69 +2,184 @@
 
 use std::borrow::Cow;
 
+use chrono::{DateTime, Utc};
 use maud::{Markup, PreEscaped, Render, html};
 use url::Url;
 
 use crate::{
-    marketplace::item::{Amount, Condition, Delivery, GeoLocation, Item, Location, Price, Seller},
-    telegram::objects::ChatId,
+    db::Subscription,
+    marketplace::item::{
+        Amount,
+        Condition,
+        ConditionClass,
+        Delivery,
+        DeliveryMethod,
+        Details,
+        GeoLocation,
+        Item,
+        Location,
+        Price,
+        Seller,
+    },
+    telegram::{i18n::Language, objects::ChatId, template::Style},
 };
 
+/// Full descriptions may be long, so the standard template shortens them.
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+/// Some categories have dozens of attributes, only the first ones fit in a caption.
+const MAX_ATTRIBUTES: usize = 6;
+
 /// Just `<strong> • </strong>`.
 pub const DELIMITER: PreEscaped<&'static str> = PreEscaped(
     // language=html
     "<strong> • </strong>",
 );
 
-pub fn unauthorized(chat_id: &ChatId) -> Markup {
+/// [`Render`] in the chat's language.
+pub trait RenderIn {
+    fn render_in(&self, language: Language) -> Markup;
+}
+
+pub fn unauthorized(chat_id: &ChatId, language: Language) -> Markup {
     html! {
-        "👋 Thank you for your interest"
-        "\n\n"
-        "This bot cannot handle many users, so it is private and only intended for authorized users."
-        "\n\n"
-        "However, " strong { "its " a href="https://github.com/eigenein/mrktpltsbot" { "source code" } " is open" } ","
-        " and you are free to deploy your own instance."
-        "\n\n"
-        "If you are already setting it up for yourself, or someone is setting it up for you,"
-        " "
-        strong { "the following ID should be added to the list of authorized chat IDs:" }
+        (language.catalogue().unauthorized)
         "\n\n"
         pre { code { (chat_id) } }
     }
 }
 
-/// Render the item description.
-pub fn item_description(item: &Item, manage_search_query: &ManageSearchQuery<'_>) -> String {
+/// Render the item description in the built-in style.
+pub fn item_description(
+    item: &Item,
+    manage_search_query: &ManageSearchQuery<'_>,
+    language: Language,
+    style: Style,
+    max_description_length: usize,
+) -> String {
     let markup = html! {
         strong { a href=(item.url) { (item.title) } }
         "\n"
         (manage_search_query)
         "\n\n"
-        (item.price)
-        @if let Some(condition) = item.condition {
-            (DELIMITER)
-            (condition)
+        (item.price.render_in(language))
+        @if style.shows_summary() {
+            @if let Some(condition) = &item.condition {
+                (DELIMITER)
+                (condition.render_in(language))
+            }
+            @if let Some(delivery) = &item.delivery {
+                (DELIMITER)
+                (delivery.render_in(language))
+            }
         }
-        @if let Some(delivery) = item.delivery {
-            (DELIMITER)
-            (delivery)
+        @if style.shows_details() && let Some(details) = &item.details {
+            (item_details(details))
         }
-        @if let Some(description) = &item.description {
+        @if max_description_length != 0 && let Some(description) = description(item) {
             "\n\n"
-            blockquote { (description) }
+            blockquote { (truncate(description, max_description_length)) }
         }
-        "\n\n"
-        (item.seller)
-        @if let Some(location) = &item.location {
-            (DELIMITER)
-            (location)
+        @if style.shows_summary() {
+            "\n\n"
+            (item.seller)
+            @if item.seller.is_business {
+                (DELIMITER)
+                (language.catalogue().business)
+            }
+            @if let Some(location) = &item.location {
+                (DELIMITER)
+                (location)
+            }
         }
     };
     markup.render().into_string()
 }
 
+/// Item description from the item page, or from the search results.
+pub fn description(item: &Item) -> Option<&str> {
+    item.details
+        .as_ref()
+        .and_then(|details| details.description.as_deref())
+        .or(item.description.as_deref())
+}
+
+/// Render the posting time, statistics, and attributes from the item page.
+pub fn item_details(details: &Details) -> Markup {
+    html! {
+        @if let Some(posted_at) = details.posted_at {
+            (DELIMITER)
+            "🗓️ " (Timestamp(posted_at))
+        }
+        @if let Some(n_views) = details.n_views {
+            (DELIMITER)
+            "👀 " (n_views)
+        }
+        @if !details.attributes.is_empty() {
+            "\n"
+            @for (i, attribute) in details.attributes.iter().take(MAX_ATTRIBUTES).enumerate() {
+                @if i != 0 {
+                    (DELIMITER)
+                }
+                (attribute.label) ": " em { (attribute.value) }
+            }
+        }
+    }
+}
+
+/// Truncate the text to the maximum length in bytes, respecting the character boundaries.
+pub fn truncate(text: &str, max_length: usize) -> Cow<'_, str> {
+    if text.len() <= max_length {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{}…", text[..text.floor_char_boundary(max_length)].trim_end()))
+    }
+}
+
+/// Render the pause status, or nothing – if the subscription is active.
+pub fn pause_status(subscription: &Subscription, language: Language) -> Markup {
+    let text = language.catalogue();
+    html! {
+        @if subscription.is_paused {
+            (DELIMITER)
+            "⏸️ "
+            em {
+                (text.paused)
+                @if let Some(resume_at) = subscription.resume_at {
+                    " " (text.until) " " (Timestamp(resume_at))
+                }
+            }
+        }
+    }
+}
+
+/// Render the subscription filter setting along with the link to toggle it.
+pub fn filter_setting(
+    title: impl Render,
+    is_excluded: bool,
+    toggle_link: &CommandLink,
+    language: Language,
+) -> Markup {
+    let text = language.catalogue();
+    html! {
+        (title) ": "
+        em { @if is_excluded { (text.hidden) } @else { (text.shown) } }
+        (DELIMITER)
+        (toggle_link)
+    }
+}
+
+/// Timestamp in the bot's timezone, which is always UTC.
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl Render for Timestamp {
+    fn render(&self) -> Markup {
+        html! { (self.0.format("%Y-%m-%d %H:%M UTC")) }
+    }
+}
+
 pub struct CommandLink {
-    pub content: &'static str,
+    pub content: Cow<'static, str>,
     pub url: Url,
 }
 
@@ -85,20 +200,22 @@ impl Render for ChatId {
     }
 }
 
-impl Render for Price {
-    fn render(&self) -> Markup {
+impl RenderIn for Price {
+    fn render_in(&self, language: Language) -> Markup {
+        let text = language.catalogue();
         html! {
             @match self {
-                Self::Fixed(asking) if *asking == Amount::ZERO => { em { "🆓 free" } }
+                Self::Fixed(asking) if *asking == Amount::ZERO => { em { (text.free) } }
                 Self::Fixed(asking) => { strong { (asking) } }
-                Self::OnRequest => { "🙋price on request" }
-                Self::MinimalBid(asking) => { strong { (asking) } (DELIMITER) "⬆️ bidding" }
-                Self::MaximalBid(asking) => { strong { (asking) } (DELIMITER) "⬇️ bidding" }
-                Self::SeeDescription => { "📝 price in description" }
-                Self::ToBeAgreed => { "🤝 price to be agreed" }
-                Self::Reserved => { "⚠️ reserved" }
-                Self::FastBid => { "⬆️ auction" }
-                Self::Exchange => { "💱 exchange" }
+                Self::OnRequest => { (text.price_on_request) }
+                Self::MinimalBid(asking) => { strong { (asking) } (DELIMITER) "⬆️ " (text.bidding) }
+                Self::MaximalBid(asking) => { strong { (asking) } (DELIMITER) "⬇️ " (text.bidding) }
+                Self::SeeDescription => { (text.price_in_description) }
+                Self::ToBeAgreed => { (text.price_to_be_agreed) }
+                Self::Reserved => { (text.reserved) }
+                Self::FastBid => { (text.auction) }
+                Self::Exchange => { (text.exchange) }
+                Self::Unknown(price_type) => { "❓ " code { (price_type) } }
             }
         }
     }
@@ -125,32 +242,63 @@ impl Render for Seller {
     }
 }
 
-impl Render for Condition {
-    fn render(&self) -> Markup {
+impl RenderIn for Condition {
+    fn render_in(&self, language: Language) -> Markup {
+        let text = language.catalogue();
         html! {
             @match self {
-                Self::New(crate::marketplace::item::New::WithTags) => "🟢 new with tags",
-                Self::New(crate::marketplace::item::New::WithoutTags) => "🟢 new without tags",
-                Self::New(crate::marketplace::item::New::AsGood) => "🟡 as good as new",
-                Self::New(crate::marketplace::item::New::Unspecified) => "🟢 new",
-                Self::Used(crate::marketplace::item::Used::VeryGood) => "🟠 very good",
-                Self::Used(crate::marketplace::item::Used::Good) => "🟠 good",
-                Self::Used(crate::marketplace::item::Used::Satisfactory) => "🟠 satisfactory",
-                Self::Used(crate::marketplace::item::Used::Unspecified) => "🟠 used",
-                Self::Used(crate::marketplace::item::Used::NotFullyFunctional) => "⛔️ not fully functional",
-                Self::Refurbished => "🟡 refurbished",
+                Self::New(crate::marketplace::item::New::WithTags) => (text.new_with_tags),
+                Self::New(crate::marketplace::item::New::WithoutTags) => (text.new_without_tags),
+                Self::New(crate::marketplace::item::New::AsGood) => (text.as_good_as_new),
+                Self::New(crate::marketplace::item::New::Unspecified) => (text.new),
+                Self::Used(crate::marketplace::item::Used::VeryGood) => (text.very_good),
+                Self::Used(crate::marketplace::item::Used::Good) => (text.good),
+                Self::Used(crate::marketplace::item::Used::Satisfactory) => (text.satisfactory),
+                Self::Used(crate::marketplace::item::Used::Unspecified) => (text.used),
+                Self::Used(crate::marketplace::item::Used::NotFullyFunctional) => (text.not_fully_functional),
+                Self::Refurbished => (text.refurbished),
+                Self::Unknown(condition) => { "❓ " code { (condition) } },
             }
         }
     }
 }
 
-impl Render for Delivery {
-    fn render(&self) -> Markup {
+impl RenderIn for Delivery {
+    fn render_in(&self, language: Language) -> Markup {
+        let text = language.catalogue();
+        html! {
+            @match self {
+                Self::CollectionOnly => (text.collection),
+                Self::ShippingOnly => (text.shipping),
+                Self::Both => { (text.shipping) (DELIMITER) (text.collection) }
+                Self::Unknown(delivery) => { "❓ " code { (delivery) } }
+            }
+        }
+    }
+}
+
+impl RenderIn for ConditionClass {
+    fn render_in(&self, language: Language) -> Markup {
+        let text = language.catalogue();
+        html! {
+            @match self {
+                Self::New => (text.new),
+                Self::AsGoodAsNew => (text.as_good_as_new),
+                Self::Used => (text.used),
+                Self::NotWorking => (text.not_working),
+                Self::Refurbished => (text.refurbished),
+            }
+        }
+    }
+}
+
+impl RenderIn for DeliveryMethod {
+    fn render_in(&self, language: Language) -> Markup {
+        let text = language.catalogue();
         html! {
             @match self {
-                Self::CollectionOnly => "🚶 collection",
-                Self::ShippingOnly => "📦 shipping",
-                Self::Both => { (Self::ShippingOnly) (DELIMITER) (Self::CollectionOnly) }
+                Self::Collection => (text.collection),
+                Self::Shipping => (text.shipping),
             }
         }
     }
@@ -0,0 +1,698 @@
+//! Notification templates: the built-in styles and the user-provided ones.
+
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use maud::{Markup, Render, html};
+use sqlx::SqliteConnection;
+
+use crate::{
+    db::{Chats, Subscription, Subscriptions},
+    marketplace::item::Item,
+    prelude::*,
+    telegram::{
+        i18n::Language,
+        render,
+        render::{ManageSearchQuery, RenderIn},
+    },
+};
+
+/// Maximum message text length, counted by Telegram after parsing the entities.
+pub const MAX_TEXT_LENGTH: usize = 4096;
+
+/// Maximum photo caption length, counted by Telegram after parsing the entities.
+pub const MAX_CAPTION_LENGTH: usize = 1024;
+
+/// Maximum template name length, so that the name fits in a deep link.
+const MAX_NAME_LENGTH: usize = 24;
+
+/// Tags supported by [Telegram HTML][1].
+///
+/// [1]: https://core.telegram.org/bots/api#html-style
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "code",
+    "del",
+    "em",
+    "i",
+    "ins",
+    "pre",
+    "s",
+    "span",
+    "strike",
+    "strong",
+    "tg-emoji",
+    "tg-spoiler",
+    "u",
+];
+
+/// Built-in notification layout.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Style {
+    /// Everything, with the description shortened.
+    #[default]
+    Standard,
+
+    /// No details and no description.
+    Compact,
+
+    /// Everything, with the description as long as Telegram allows.
+    Full,
+
+    /// Only the title and the price, leaving the rest to the picture.
+    PhotoOnly,
+}
+
+impl Style {
+    pub const ALL: [Self; 4] = [Self::Standard, Self::Compact, Self::Full, Self::PhotoOnly];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Compact => "compact",
+            Self::Full => "full",
+            Self::PhotoOnly => "photo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|style| style.name() == name)
+    }
+
+    /// Whether the posting time, statistics, and attributes are shown.
+    pub const fn shows_details(self) -> bool {
+        matches!(self, Self::Standard | Self::Full)
+    }
+
+    /// Whether the condition, delivery, seller, and location are shown.
+    pub const fn shows_summary(self) -> bool {
+        !matches!(self, Self::PhotoOnly)
+    }
+
+    /// Initial description length budget in bytes.
+    const fn max_description_length(self) -> usize {
+        match self {
+            Self::Standard => render::MAX_DESCRIPTION_LENGTH,
+            Self::Full => usize::MAX,
+            Self::Compact | Self::PhotoOnly => 0,
+        }
+    }
+
+    /// Shorter style to try when the rendered item does not fit.
+    const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Standard | Self::Full => Some(Self::Compact),
+            Self::Compact => Some(Self::PhotoOnly),
+            Self::PhotoOnly => None,
+        }
+    }
+
+    fn render(
+        self,
+        item: &Item,
+        manage_search_query: &ManageSearchQuery<'_>,
+        language: Language,
+        max_length: usize,
+    ) -> String {
+        let max_description_length = self.max_description_length();
+        let rendered = fit(item, max_length, max_description_length, |max_description_length| {
+            render::item_description(
+                item,
+                manage_search_query,
+                language,
+                self,
+                max_description_length,
+            )
+        });
+        match (rendered, self.fallback()) {
+            (Ok(text), _) => text,
+            (Err(_), Some(fallback)) => {
+                fallback.render(item, manage_search_query, language, max_length)
+            }
+            (Err(text), None) => {
+                warn!("⚠️ The item does not fit in any template", item_id = item.id.clone());
+                text
+            }
+        }
+    }
+}
+
+/// Placeholder in a user-provided template.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Placeholder {
+    Title,
+    Url,
+    Query,
+    Price,
+    Condition,
+    Delivery,
+    Details,
+    Description,
+    Seller,
+    Location,
+}
+
+impl Placeholder {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "title" => Some(Self::Title),
+            "url" => Some(Self::Url),
+            "query" => Some(Self::Query),
+            "price" => Some(Self::Price),
+            "condition" => Some(Self::Condition),
+            "delivery" => Some(Self::Delivery),
+            "details" => Some(Self::Details),
+            "description" => Some(Self::Description),
+            "seller" => Some(Self::Seller),
+            "location" => Some(Self::Location),
+            _ => None,
+        }
+    }
+
+    /// Render the placeholder value, HTML-escaped.
+    fn render(
+        self,
+        item: &Item,
+        manage_search_query: &ManageSearchQuery<'_>,
+        language: Language,
+        max_description_length: usize,
+    ) -> Markup {
+        match self {
+            Self::Title => html! { (item.title) },
+            Self::Url => html! { (item.url) },
+            Self::Query => manage_search_query.render(),
+            Self::Price => item.price.render_in(language),
+            Self::Condition => html! {
+                @if let Some(condition) = &item.condition { (condition.render_in(language)) }
+            },
+            Self::Delivery => html! {
+                @if let Some(delivery) = &item.delivery { (delivery.render_in(language)) }
+            },
+            Self::Details => html! {
+                @if let Some(details) = &item.details { (render::item_details(details)) }
+            },
+            Self::Description => html! {
+                @if let Some(description) = render::description(item) {
+                    (render::truncate(description, max_description_length))
+                }
+            },
+            Self::Seller => item.seller.render(),
+            Self::Location => html! {
+                @if let Some(location) = &item.location { (location) }
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Part {
+    /// Trusted markup from the template file.
+    Literal(String),
+
+    Placeholder(Placeholder),
+}
+
+/// User-provided template, loaded from disk.
+///
+/// The template is Telegram HTML with `{placeholder}`'s, and `{{` and `}}` stand for the braces.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CustomTemplate {
+    name: String,
+    parts: Vec<Part>,
+}
+
+impl CustomTemplate {
+    pub fn parse(name: &str, source: &str) -> Result<Self> {
+        if !is_valid_name(name) {
+            bail!(
+                "template name `{name}` must consist of up to {MAX_NAME_LENGTH} lowercase letters, digits, `-`, or `_`"
+            );
+        }
+        if Style::from_name(name).is_some() {
+            bail!("template name `{name}` is reserved");
+        }
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.trim_end().chars().peekable();
+        while let Some(character) = chars.next() {
+            match character {
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(character) => name.push(character),
+                            None => bail!("unclosed `{{{name}`"),
+                        }
+                    }
+                    let placeholder = Placeholder::from_name(name.trim())
+                        .with_context(|| format!("unknown placeholder `{{{name}}}`"))?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Placeholder(placeholder));
+                }
+                '}' => bail!("unmatched `}}`, use `}}}}` for a literal brace"),
+                _ => literal.push(character),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        let markup: String = parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Literal(literal) => Some(literal.as_str()),
+                Part::Placeholder(_) => None,
+            })
+            .collect();
+        validate_markup(&markup)?;
+
+        Ok(Self { name: name.to_string(), parts })
+    }
+
+    fn render(
+        &self,
+        item: &Item,
+        manage_search_query: &ManageSearchQuery<'_>,
+        language: Language,
+        max_length: usize,
+    ) -> Result<String, String> {
+        fit(item, max_length, usize::MAX, |max_description_length| {
+            let mut text = String::new();
+            for part in &self.parts {
+                match part {
+                    Part::Literal(literal) => text.push_str(literal),
+                    Part::Placeholder(placeholder) => text.push_str(
+                        &placeholder
+                            .render(item, manage_search_query, language, max_description_length)
+                            .into_string(),
+                    ),
+                }
+            }
+            text
+        })
+    }
+}
+
+/// Notification template chosen for a chat or subscription.
+#[derive(Clone, Debug)]
+pub enum Template {
+    BuiltIn(Style),
+    Custom(Arc<CustomTemplate>),
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Self::BuiltIn(Style::default())
+    }
+}
+
+impl Template {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::BuiltIn(style) => style.name(),
+            Self::Custom(template) => &template.name,
+        }
+    }
+
+    /// Render the item, so that it fits within Telegram's limit.
+    ///
+    /// The description gets shortened first, and then a shorter built-in style is used.
+    pub fn render(
+        &self,
+        item: &Item,
+        manage_search_query: &ManageSearchQuery<'_>,
+        language: Language,
+    ) -> String {
+        let max_length = max_length(item);
+        match self {
+            Self::BuiltIn(style) => style.render(item, manage_search_query, language, max_length),
+            Self::Custom(template) => template
+                .render(item, manage_search_query, language, max_length)
+                .unwrap_or_else(|_| {
+                    warn!(
+                        "⚠️ The item does not fit in the template",
+                        template = template.name.clone(),
+                        item_id = item.id.clone(),
+                    );
+                    Style::Compact.render(item, manage_search_query, language, max_length)
+                }),
+        }
+    }
+}
+
+/// Template of the subscription's notifications.
+pub struct SubscriptionTemplate {
+    /// Template chosen for the subscription itself, overrides the chat's one.
+    pub own: Option<String>,
+
+    /// Template, which the notifications actually use.
+    pub effective: Template,
+}
+
+/// Built-in templates and the user-provided ones.
+#[derive(Clone, Debug, Default)]
+pub struct Templates(Arc<BTreeMap<String, Arc<CustomTemplate>>>);
+
+impl Templates {
+    /// Load the `*.html` templates from the directory, named after the files.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut templates = BTreeMap::new();
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("failed to read `{}`", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "html") {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .with_context(|| format!("invalid template file name `{}`", path.display()))?;
+            let source = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            let template = CustomTemplate::parse(name, &source)
+                .with_context(|| format!("invalid template `{}`", path.display()))?;
+            info!("📝 Loaded template", name = name.to_string());
+            templates.insert(name.to_string(), Arc::new(template));
+        }
+        Ok(Self(Arc::new(templates)))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Template> {
+        Style::from_name(name)
+            .map(Template::BuiltIn)
+            .or_else(|| self.0.get(name).cloned().map(Template::Custom))
+    }
+
+    /// Get the chosen template, or the default one – if none is chosen or it is gone.
+    pub fn resolve(&self, name: Option<&str>) -> Template {
+        name.map_or_else(Template::default, |name| {
+            self.get(name).unwrap_or_else(|| {
+                warn!("⚠️ Unknown template, using the default one", name = name.to_string());
+                Template::default()
+            })
+        })
+    }
+
+    /// Fetch the subscription's template, falling back to the chat's one.
+    pub async fn fetch_for(
+        &self,
+        connection: &mut SqliteConnection,
+        subscription: &Subscription,
+    ) -> Result<SubscriptionTemplate> {
+        let own = Subscriptions(connection)
+            .fetch_template(subscription.query_hash, subscription.chat_id)
+            .await?;
+        let chat = match own {
+            Some(_) => None,
+            None => Chats(connection).fetch_template(subscription.chat_id).await?,
+        };
+        let effective = self.resolve(own.as_deref().or(chat.as_deref()));
+        Ok(SubscriptionTemplate { own, effective })
+    }
+
+    /// Names of all the available templates, the built-in ones first.
+    pub fn names<'a>(&'a self) -> impl Iterator<Item = &'a str> {
+        let built_in = Style::ALL.into_iter().map(|style| -> &'a str { style.name() });
+        built_in.chain(self.0.keys().map(String::as_str))
+    }
+}
+
+/// Maximum visible text length, depending on whether the item gets sent with pictures.
+fn max_length(item: &Item) -> usize {
+    if item.picture_urls().is_empty() { MAX_TEXT_LENGTH } else { MAX_CAPTION_LENGTH }
+}
+
+/// Minimal cut in bytes: at least two characters, so that the ellipsis is outweighed.
+const MIN_DESCRIPTION_CUT: usize = 8;
+
+/// Render the item, shortening the description until the visible text fits.
+///
+/// # Returns
+///
+/// The rendered text, or the shortest one as an error – if it does not fit.
+fn fit(
+    item: &Item,
+    max_length: usize,
+    max_description_length: usize,
+    render: impl Fn(usize) -> String,
+) -> Result<String, String> {
+    let mut max_description_length =
+        max_description_length.min(render::description(item).map_or(0, str::len));
+    let mut previous_length = None;
+    loop {
+        let text = render(max_description_length);
+        let length = visible_length(&text);
+        if length <= max_length {
+            break Ok(text);
+        }
+        if max_description_length == 0 {
+            break Err(text);
+        }
+        max_description_length = if previous_length.is_some_and(|previous| length >= previous) {
+            // The description does not make it any shorter, for example, a custom template without it.
+            0
+        } else {
+            max_description_length.saturating_sub((length - max_length).max(MIN_DESCRIPTION_CUT))
+        };
+        previous_length = Some(length);
+    }
+}
+
+/// Text length as Telegram counts it: in UTF-16 code units, after parsing the entities.
+fn visible_length(html: &str) -> usize {
+    let mut length = 0;
+    let mut chars = html.chars();
+    while let Some(char_) = chars.next() {
+        match char_ {
+            '<' => {
+                chars.by_ref().take_while(|char_| *char_ != '>').for_each(drop);
+            }
+            '&' => {
+                chars.by_ref().take_while(|char_| *char_ != ';').for_each(drop);
+                length += 1;
+            }
+            _ => {
+                length += char_.len_utf16();
+            }
+        }
+    }
+    length
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name.bytes().all(|byte| {
+            byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' || byte == b'_'
+        })
+}
+
+/// Check whether the entity, without `&` and `;`, is supported by Telegram.
+fn is_valid_entity(entity: &str) -> bool {
+    matches!(entity, "lt" | "gt" | "amp" | "quot")
+        || entity.strip_prefix('#').is_some_and(|code| {
+            code.parse::<u32>().is_ok()
+                || code.strip_prefix('x').is_some_and(|hex| u32::from_str_radix(hex, 16).is_ok())
+        })
+}
+
+/// Check that the template markup is balanced, and only uses what Telegram supports.
+fn validate_markup(markup: &str) -> Result {
+    let mut open_tags = Vec::new();
+    let mut rest = markup;
+    while let Some(index) = rest.find(['<', '>', '&']) {
+        let (special, tail) = rest[index..].split_at(1);
+        match special {
+            "<" => {
+                let end = tail.find('>').context("unclosed tag")?;
+                let tag = &tail[..end];
+                if let Some(name) = tag.strip_prefix('/') {
+                    let open =
+                        open_tags.pop().with_context(|| format!("unexpected `</{name}>`"))?;
+                    if open != name.trim() {
+                        bail!("`<{open}>` is closed by `</{name}>`");
+                    }
+                } else {
+                    let name = tag.split_whitespace().next().unwrap_or_default();
+                    if !ALLOWED_TAGS.contains(&name) {
+                        bail!("unsupported tag `<{tag}>`");
+                    }
+                    open_tags.push(name);
+                }
+                rest = &tail[end + 1..];
+            }
+            "&" => {
+                let entity = tail.split_once(';').map(|(entity, _)| entity);
+                if !entity.is_some_and(is_valid_entity) {
+                    bail!("unescaped `&`, use `&amp;`");
+                }
+                rest = tail;
+            }
+            _ => bail!("unescaped `>`, use `&gt;`"),
+        }
+    }
+    if let Some(open) = open_tags.pop() {
+        bail!("unclosed `<{open}>`");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        marketplace::item::{Amount, Price, Seller},
+        telegram::commands::CommandBuilder,
+    };
+
+    fn item(description: &str) -> Result<Item> {
+        Ok(Item::builder()
+            .id("m1".to_string())
+            .url(Url::parse("https://www.marktplaats.nl/v/m1")?)
+            .title("Gazelle <fiets>".to_string())
+            .description(description.to_string())
+            .price(Price::Fixed(Amount::ZERO))
+            .seller(
+                Seller::builder()
+                    .id(1)
+                    .username("FlimDeal".to_string())
+                    .profile_url(Url::parse("https://www.marktplaats.nl/u/1")?)
+                    .build(),
+            )
+            .build())
+    }
+
+    #[test]
+    fn parse_ok() -> Result {
+        let template = CustomTemplate::parse("mine", "<b>{title}</b> {{{price}}}\n")?;
+        assert_eq!(
+            template.parts,
+            [
+                Part::Literal("<b>".to_string()),
+                Part::Placeholder(Placeholder::Title),
+                Part::Literal("</b> {".to_string()),
+                Part::Placeholder(Placeholder::Price),
+                Part::Literal("}".to_string()),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_ok() {
+        assert!(CustomTemplate::parse("compact", "{title}").is_err(), "reserved name");
+        assert!(CustomTemplate::parse("Mine", "{title}").is_err(), "invalid name");
+        assert!(CustomTemplate::parse("mine", "{unknown}").is_err());
+        assert!(CustomTemplate::parse("mine", "{title").is_err());
+        assert!(CustomTemplate::parse("mine", "title}").is_err());
+        assert!(CustomTemplate::parse("mine", "<b>{title}").is_err());
+        assert!(CustomTemplate::parse("mine", "<b>{title}</i>").is_err());
+        assert!(CustomTemplate::parse("mine", "<div>{title}</div>").is_err());
+        assert!(CustomTemplate::parse("mine", "{title} & {price}").is_err());
+        assert!(CustomTemplate::parse("mine", "{title} -> {price}").is_err());
+        assert!(
+            CustomTemplate::parse("mine", r#"<a href="{url}">{title}</a> &amp; &#128512;"#).is_ok()
+        );
+    }
+
+    #[test]
+    fn visible_length_ok() {
+        assert_eq!(visible_length("<b>a &amp; b</b> 😀"), 8);
+    }
+
+    #[test]
+    fn render_custom_escapes_ok() -> Result {
+        let template =
+            CustomTemplate::parse("mine", r#"<a href="{url}">{title}</a>: {description}"#)?;
+        let item = item("<b>not bold</b>")?;
+        let manage_search_query = ManageSearchQuery::new("fiets", &[]);
+        let text = Template::Custom(Arc::new(template)).render(
+            &item,
+            &manage_search_query,
+            Language::English,
+        );
+        assert_eq!(
+            text,
+            r#"<a href="https://www.marktplaats.nl/v/m1">Gazelle &lt;fiets&gt;</a>: &lt;b&gt;not bold&lt;/b&gt;"#,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_fits_ok() -> Result {
+        let command_builder = CommandBuilder::new("mrktpltsbot")?;
+        let link = command_builder.subscribe_link(42, Language::English);
+        let links = [&link];
+        let manage_search_query = ManageSearchQuery::new("fiets", &links);
+        let mut item = item(&"ü".repeat(10_000))?;
+
+        let text =
+            Template::BuiltIn(Style::Full).render(&item, &manage_search_query, Language::English);
+        let length = visible_length(&text);
+        assert!((MAX_TEXT_LENGTH - 10..=MAX_TEXT_LENGTH).contains(&length), "{length}");
+        assert!(text.contains("…</blockquote>"), "{text}");
+
+        item.picture_url = Some(Url::parse("https://example.com/picture.jpg")?);
+        let text =
+            Template::BuiltIn(Style::Full).render(&item, &manage_search_query, Language::English);
+        assert!(visible_length(&text) <= MAX_CAPTION_LENGTH);
+
+        let text = Template::BuiltIn(Style::Compact).render(
+            &item,
+            &manage_search_query,
+            Language::English,
+        );
+        assert!(!text.contains("ü"), "{text}");
+        Ok(())
+    }
+
+    #[test]
+    fn fit_without_description_ok() -> Result {
+        let item = item(&"ü".repeat(10_000))?;
+        let n_renders = std::cell::Cell::new(0);
+        let text = fit(&item, 5, usize::MAX, |_| {
+            n_renders.set(n_renders.get() + 1);
+            "Too long without the description".to_string()
+        });
+        assert!(text.is_err());
+        assert_eq!(n_renders.get(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn load_ok() -> Result {
+        let dir =
+            std::env::temp_dir().join(format!("mrktpltsbot-templates-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("mine.html"), "<b>{title}</b> {price}\n")?;
+        fs::write(dir.join("README.md"), "Not a template")?;
+        let templates = Templates::load(&dir);
+        fs::remove_dir_all(&dir)?;
+        let templates = templates?;
+        assert_eq!(
+            templates.names().collect::<Vec<_>>(),
+            ["standard", "compact", "full", "photo", "mine"],
+        );
+        assert_eq!(templates.resolve(Some("mine")).name(), "mine");
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_ok() {
+        let templates = Templates::default();
+        assert_eq!(templates.resolve(None).name(), "standard");
+        assert_eq!(templates.resolve(Some("compact")).name(), "compact");
+        assert_eq!(templates.resolve(Some("gone")).name(), "standard");
+        assert_eq!(templates.names().collect::<Vec<_>>(), ["standard", "compact", "full", "photo"]);
+    }
+}